@@ -169,6 +169,34 @@ fn can_model_2pc() {
     checker.assert_properties();
 }
 
+// Implementing this trait enables symmetry reduction to speed up model checking (optional).
+impl Representative for TwoPhaseState {
+    fn representative(&self) -> Self {
+        let plan = RewritePlan::from_values_to_sort(&self.rm_state);
+        Self {
+            rm_state: plan.reindex(&self.rm_state),
+            tm_state: self.tm_state.clone(),
+            tm_prepared: plan.reindex(&self.tm_prepared),
+            msgs: self
+                .msgs
+                .iter()
+                .map(|m| match m {
+                    Message::Prepared { rm } => Message::Prepared {
+                        rm: plan.rewrite(rm),
+                    },
+                    Message::Commit => Message::Commit,
+                    Message::Abort => Message::Abort,
+                })
+                .collect(),
+        }
+    }
+}
+impl<T> Rewrite<T> for RmState {
+    fn rewrite<S>(&self, _: &RewritePlan<T, S>) -> Self {
+        self.clone()
+    }
+}
+
 fn main() -> Result<(), pico_args::Error> {
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info")); // `RUST_LOG=${LEVEL}` env variable to override
 
@@ -198,34 +226,6 @@ fn main() -> Result<(), pico_args::Error> {
                 .symmetry()
                 .spawn_dfs()
                 .report(&mut WriteReporter::new(&mut std::io::stdout()));
-
-            // Implementing this trait enables symmetry reduction to speed up model checking (optional).
-            impl Representative for TwoPhaseState {
-                fn representative(&self) -> Self {
-                    let plan = RewritePlan::from_values_to_sort(&self.rm_state);
-                    Self {
-                        rm_state: plan.reindex(&self.rm_state),
-                        tm_state: self.tm_state.clone(),
-                        tm_prepared: plan.reindex(&self.tm_prepared),
-                        msgs: self
-                            .msgs
-                            .iter()
-                            .map(|m| match m {
-                                Message::Prepared { rm } => Message::Prepared {
-                                    rm: plan.rewrite(rm),
-                                },
-                                Message::Commit => Message::Commit,
-                                Message::Abort => Message::Abort,
-                            })
-                            .collect(),
-                    }
-                }
-            }
-            impl<T> Rewrite<T> for RmState {
-                fn rewrite<S>(&self, _: &RewritePlan<T, S>) -> Self {
-                    self.clone()
-                }
-            }
         }
         Some("explore") => {
             let rm_count = args.opt_free_from_str()?.unwrap_or(2);
@@ -65,6 +65,7 @@ impl Actor for AbdActor {
     type Msg = RegisterMsg<RequestId, Value, AbdMsg>;
     type State = AbdState;
     type Timer = ();
+    type Storage = ();
 
     fn on_start(&self, id: Id, _o: &mut Out<Self>) -> Self::State {
         AbdState {
@@ -409,6 +410,8 @@ fn main() -> Result<(), pico_args::Error> {
                     ),
                 ],
             )
+            .unwrap()
+            .join()
             .unwrap();
         }
         _ => {
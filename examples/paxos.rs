@@ -112,6 +112,7 @@ impl Actor for PaxosActor {
     type Msg = RegisterMsg<RequestId, Value, PaxosMsg>;
     type State = PaxosState;
     type Timer = ();
+    type Storage = ();
 
     fn name(&self) -> String {
         "Paxos Server".to_owned()
@@ -489,6 +490,8 @@ fn main() -> Result<(), pico_args::Error> {
                     ),
                 ],
             )
+            .unwrap()
+            .join()
             .unwrap();
         }
         _ => {
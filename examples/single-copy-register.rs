@@ -19,6 +19,7 @@ impl Actor for SingleCopyActor {
     type Msg = RegisterMsg<RequestId, Value, ()>;
     type State = Value;
     type Timer = ();
+    type Storage = ();
 
     fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State {
         Value::default()
@@ -215,6 +216,8 @@ fn main() -> Result<(), pico_args::Error> {
                     SingleCopyActor,
                 )],
             )
+            .unwrap()
+            .join()
             .unwrap();
         }
         _ => {
@@ -1,5 +1,9 @@
 //! This module provides an [Actor] trait, which can be model checked using [`ActorModel`].  You
-//! can also [`spawn()`] the actor in which case it will communicate over a UDP socket.
+//! can also [`spawn()`] the actor in which case it will communicate over a UDP socket, or
+//! [`spawn_tcp()`] it to communicate over TCP instead. Both return a [`SpawnHandle`] for
+//! inspecting and stopping the spawned actors. With the `tokio` feature, `spawn_async()`
+//! runs actors as tasks on a Tokio runtime. A [`Simulator`] runs actors deterministically against
+//! a virtual clock.
 //!
 //! ## Example
 //!
@@ -31,6 +35,7 @@
 //!     type Msg = MsgWithTimestamp;
 //!     type State = Timestamp;
 //!     type Timer = ();
+//!     type Storage = ();
 //!
 //!     fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
 //!         // The actor either bootstraps or starts at time zero.
@@ -85,7 +90,10 @@ use choice::{Choice, Never};
 mod model;
 mod model_state;
 mod network;
+mod simulator;
 mod spawn;
+#[cfg(feature = "tokio")]
+mod spawn_async;
 mod timers;
 use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
@@ -99,14 +107,17 @@ pub mod actor_test_util;
 pub use model::*;
 pub use model_state::*;
 pub use network::*;
+pub use simulator::*;
 pub use timers::*;
 pub mod ordered_reliable_link;
 pub mod register;
 pub mod write_once_register;
 pub use spawn::*;
+#[cfg(feature = "tokio")]
+pub use spawn_async::*;
 
 /// Uniquely identifies an [`Actor`]. Encodes the socket address for spawned
-/// actors. Encodes an index for model checked actors.
+/// actors unless they use an [`AddressBook`]. Encodes an index for model checked actors.
 #[derive(
     Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize,
 )]
@@ -157,17 +168,20 @@ impl From<usize> for Id {
 
 /// Commands with which an actor can respond.
 #[derive(Debug, serde::Serialize)]
-pub enum Command<Msg, Timer> {
+pub enum Command<Msg, Timer, Storage = ()> {
     /// Cancel the timer if one is set.
     CancelTimer(Timer),
     /// Set/reset the timer.
     SetTimer(Timer, Range<Duration>),
     /// Send a message to a destination.
     Send(Id, Msg),
+    /// Replace the durable state.
+    Persist(Storage),
 }
 
-/// Holds [`Command`]s output by an actor.
-pub struct Out<A: Actor>(Vec<Command<A::Msg, A::Timer>>);
+/// Holds [`Command`]s output by an actor, along with the reading of the actor's local clock if
+/// available. See [`Out::clock`].
+pub struct Out<A: Actor>(Vec<Command<A::Msg, A::Timer, A::Storage>>, Option<Duration>);
 
 impl<A: Actor> Default for Out<A> {
     fn default() -> Self {
@@ -178,13 +192,27 @@ impl<A: Actor> Default for Out<A> {
 impl<A: Actor> Out<A> {
     /// Constructs an empty `Out`.
     pub fn new() -> Self {
-        Self(Vec::new())
+        Self(Vec::new(), None)
+    }
+
+    /// Constructs an empty `Out` for an actor whose local clock has a particular reading. Actors
+    /// that wrap other actors can use this to pass along [`Out::clock`].
+    pub fn with_clock(clock: Option<Duration>) -> Self {
+        Self(Vec::new(), clock)
+    }
+
+    /// The time according to the actor's local clock when the actor was notified of an event, if
+    /// available. [`spawn`] reports the time since the Unix epoch. [`ActorModel`] reports the
+    /// time since the system started, adjusted by the actor's clock skew, but only if
+    /// [`ActorModel::discrete_time`] is enabled.
+    pub fn clock(&self) -> Option<Duration> {
+        self.1
     }
 
     /// Moves all [`Command`]s of `other` into `Self`, leaving `other` empty.
     pub fn append<B>(&mut self, other: &mut Out<B>)
     where
-        B: Actor<Msg = A::Msg, Timer = A::Timer>,
+        B: Actor<Msg = A::Msg, Timer = A::Timer, Storage = A::Storage>,
     {
         self.0.append(&mut other.0)
     }
@@ -213,6 +241,12 @@ impl<A: Actor> Out<A> {
             self.send(*recipient, msg.clone());
         }
     }
+
+    /// Records the need to persist durable state, which survives crashes. Each call replaces the
+    /// previously persisted state. See [`Actor::on_recover`].
+    pub fn persist(&mut self, storage: A::Storage) {
+        self.0.push(Command::Persist(storage));
+    }
 }
 
 impl<A: Actor> Debug for Out<A> {
@@ -222,21 +256,21 @@ impl<A: Actor> Debug for Out<A> {
 }
 
 impl<A: Actor> std::ops::Deref for Out<A> {
-    type Target = [Command<A::Msg, A::Timer>];
+    type Target = [Command<A::Msg, A::Timer, A::Storage>];
     fn deref(&self) -> &Self::Target {
         self.0.deref()
     }
 }
 
-impl<A: Actor> std::iter::FromIterator<Command<A::Msg, A::Timer>> for Out<A> {
-    fn from_iter<I: IntoIterator<Item = Command<A::Msg, A::Timer>>>(iter: I) -> Self {
-        Out(Vec::from_iter(iter))
+impl<A: Actor> std::iter::FromIterator<Command<A::Msg, A::Timer, A::Storage>> for Out<A> {
+    fn from_iter<I: IntoIterator<Item = Command<A::Msg, A::Timer, A::Storage>>>(iter: I) -> Self {
+        Out(Vec::from_iter(iter), None)
     }
 }
 
 impl<A: Actor> IntoIterator for Out<A> {
-    type Item = Command<A::Msg, A::Timer>;
-    type IntoIter = std::vec::IntoIter<Command<A::Msg, A::Timer>>;
+    type Item = Command<A::Msg, A::Timer, A::Storage>;
+    type IntoIter = std::vec::IntoIter<Command<A::Msg, A::Timer, A::Storage>>;
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
     }
@@ -301,6 +335,21 @@ pub trait Actor: Sized {
     /// ```
     type State: Clone + Debug + PartialEq + Hash;
 
+    /// The type of durable state maintained by the actor, which survives crashes unlike
+    /// [`Actor::State`]. Use `()` if the actor does not persist state. See [`Out::persist`].
+    ///
+    /// Model checking uses the typed value, and only [`spawn`] and the other runtimes serialize
+    /// it (as JSON), so they require it to implement [`serde::Serialize`] and
+    /// [`serde::de::DeserializeOwned`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    /// struct MyActorStorage { write_ahead_log: Vec<u64> }
+    /// ```
+    type Storage: Clone + Debug + PartialEq + Hash;
+
     /// Indicates the initial state and commands.
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State;
 
@@ -335,6 +384,19 @@ pub trait Actor: Sized {
         let _ = o;
     }
 
+    /// Indicates the state and commands when the actor restarts after a crash, given the durable
+    /// state last recorded via [`Out::persist`] (if any). See [`ActorModel::max_recoveries`] and
+    /// [`Spawner::storage_dir`]. Defaults to restarting via [`Actor::on_start`].
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        let _ = storage;
+        self.on_start(id, o)
+    }
+
     fn name(&self) -> String {
         String::new()
     }
@@ -347,10 +409,11 @@ where
     type Msg = A::Msg;
     type State = Choice<A::State, Never>;
     type Timer = A::Timer;
+    type Storage = A::Storage;
 
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
         let actor = self.get();
-        let mut o_prime = Out::new();
+        let mut o_prime = Out::with_clock(o.clock());
         let state = actor.on_start(id, &mut o_prime);
 
         o.append(&mut o_prime);
@@ -367,7 +430,7 @@ where
     ) {
         let actor = self.get();
         let mut state_prime = Cow::Borrowed(state.get());
-        let mut o_prime = Out::new();
+        let mut o_prime = Out::with_clock(o.clock());
         actor.on_msg(id, &mut state_prime, src, msg, &mut o_prime);
 
         o.append(&mut o_prime);
@@ -385,7 +448,7 @@ where
     ) {
         let actor = self.get();
         let mut state_prime = Cow::Borrowed(state.get());
-        let mut o_prime = Out::new();
+        let mut o_prime = Out::with_clock(o.clock());
         actor.on_timeout(id, &mut state_prime, timer, &mut o_prime);
 
         o.append(&mut o_prime);
@@ -394,32 +457,48 @@ where
         }
     }
 
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        let actor = self.get();
+        let mut o_prime = Out::with_clock(o.clock());
+        let state = actor.on_recover(id, storage, &mut o_prime);
+
+        o.append(&mut o_prime);
+        Choice::new(state)
+    }
+
     fn name(&self) -> String {
         self.get().name()
     }
 }
 
-impl<Msg, Timer, A1, A2> Actor for Choice<A1, A2>
+impl<Msg, Timer, Storage, A1, A2> Actor for Choice<A1, A2>
 where
     Msg: Clone + Debug + Eq + Hash,
     Timer: Clone + Debug + Eq + Hash + serde::Serialize,
-    A1: Actor<Msg = Msg, Timer = Timer>,
-    A2: Actor<Msg = Msg, Timer = Timer>,
+    Storage: Clone + Debug + PartialEq + Hash,
+    A1: Actor<Msg = Msg, Timer = Timer, Storage = Storage>,
+    A2: Actor<Msg = Msg, Timer = Timer, Storage = Storage>,
 {
     type Msg = Msg;
     type State = Choice<A1::State, A2::State>;
     type Timer = Timer;
+    type Storage = Storage;
 
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
         match self {
             Choice::L(actor) => {
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 let state = actor.on_start(id, &mut o_prime);
                 o.append(&mut o_prime);
                 Choice::L(state)
             }
             Choice::R(actor) => {
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 let state = actor.on_start(id, &mut o_prime);
                 o.append(&mut o_prime);
                 Choice::R(state)
@@ -438,7 +517,7 @@ where
         match (self, &**state) {
             (Choice::L(actor), Choice::L(state_prime)) => {
                 let mut state_prime = Cow::Borrowed(state_prime);
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 actor.on_msg(id, &mut state_prime, src, msg, &mut o_prime);
                 o.append(&mut o_prime);
                 if let Cow::Owned(state_prime) = state_prime {
@@ -447,7 +526,7 @@ where
             }
             (Choice::R(actor), Choice::R(state_prime)) => {
                 let mut state_prime = Cow::Borrowed(state_prime);
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 actor.on_msg(id, &mut state_prime, src, msg, &mut o_prime);
                 o.append(&mut o_prime);
                 if let Cow::Owned(state_prime) = state_prime {
@@ -468,7 +547,7 @@ where
         match (self, &**state) {
             (Choice::L(actor), Choice::L(state_prime)) => {
                 let mut state_prime = Cow::Borrowed(state_prime);
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 actor.on_timeout(id, &mut state_prime, timer, &mut o_prime);
                 o.append(&mut o_prime);
                 if let Cow::Owned(state_prime) = state_prime {
@@ -477,7 +556,7 @@ where
             }
             (Choice::R(actor), Choice::R(state_prime)) => {
                 let mut state_prime = Cow::Borrowed(state_prime);
-                let mut o_prime = Out::new();
+                let mut o_prime = Out::with_clock(o.clock());
                 actor.on_timeout(id, &mut state_prime, timer, &mut o_prime);
                 o.append(&mut o_prime);
                 if let Cow::Owned(state_prime) = state_prime {
@@ -488,6 +567,28 @@ where
         }
     }
 
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        match self {
+            Choice::L(actor) => {
+                let mut o_prime = Out::with_clock(o.clock());
+                let state = actor.on_recover(id, storage, &mut o_prime);
+                o.append(&mut o_prime);
+                Choice::L(state)
+            }
+            Choice::R(actor) => {
+                let mut o_prime = Out::with_clock(o.clock());
+                let state = actor.on_recover(id, storage, &mut o_prime);
+                o.append(&mut o_prime);
+                Choice::R(state)
+            }
+        }
+    }
+
     fn name(&self) -> String {
         match self {
             Choice::L(a) => a.name(),
@@ -503,6 +604,7 @@ impl Actor for () {
     type State = ();
     type Msg = ();
     type Timer = ();
+    type Storage = ();
     fn on_start(&self, _: Id, _o: &mut Out<Self>) -> Self::State {}
     fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
     fn name(&self) -> String {
@@ -519,6 +621,7 @@ where
     type Msg = Msg;
     type State = usize;
     type Timer = ();
+    type Storage = ();
 
     fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
         if let Some((dst, msg)) = self.first() {
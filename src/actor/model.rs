@@ -1,9 +1,10 @@
 //! Private module for selective re-export.
 
 use crate::actor::{
-    is_no_op, is_no_op_with_timer, Actor, ActorModelState, Command, Envelope, Id, Network, Out,
+    is_no_op, is_no_op_with_timer, Actor, ActorModelState, Command, Deadline, Envelope, Id,
+    Network, Out,
 };
-use crate::{Expectation, Model, Path, Property};
+use crate::{Expectation, Fairness, Ltl, Model, Path, Property, Rewrite, RewritePlan};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
@@ -30,12 +31,40 @@ where
     pub init_history: H,
     pub init_network: Network<A::Msg>,
     pub lossy_network: LossyNetwork,
+    /// Overrides [`ActorModel::lossy_network`] for specific directed links. See
+    /// [`ActorModel::link_policy`].
+    pub link_policies: HashMap<(Id, Id), LinkPolicy>,
     /// Maximum number of actors that can be contemporarily crashed
     pub max_crashes: usize,
+    /// Maximum number of times that crashed actors can recover, in total
+    pub max_recoveries: usize,
+    /// Groups of actors that can be cut off from the rest. See [`ActorModel::partition_shape`].
+    pub partition_shapes: Vec<Vec<Id>>,
+    /// Maximum number of times that the network can be partitioned, in total
+    pub max_partitions: usize,
+    /// Maximum number of actors that can be Byzantine. See [`ActorModel::max_byzantine`].
+    pub max_byzantine: usize,
+    /// Actors that are initially [`Membership::Pending`]. See [`ActorModel::pool_actor`].
+    pub pool: Vec<Id>,
+    /// Maximum number of pooled actors that can join, in total
+    pub max_joins: usize,
+    /// Maximum number of actors that can leave, in total
+    pub max_leaves: usize,
+    /// Whether timers fire according to their durations. See [`ActorModel::discrete_time`].
+    pub discrete_time: bool,
+    /// Maximum difference between an actor's local clock and the global clock. See
+    /// [`ActorModel::max_clock_skew`].
+    pub max_clock_skew: Duration,
+    /// Whether actors' clock skews can change as time passes. See [`ActorModel::clock_drift`].
+    pub clock_drift: bool,
+    /// Whether to skip redundant orderings of independent steps. See [`ActorModel::sleep_sets`].
+    pub sleep_sets: bool,
     pub properties: Vec<Property<ActorModel<A, C, H>>>,
     pub record_msg_in: fn(cfg: &C, history: &H, envelope: Envelope<&A::Msg>) -> Option<H>,
     pub record_msg_out: fn(cfg: &C, history: &H, envelope: Envelope<&A::Msg>) -> Option<H>,
     pub within_boundary: fn(cfg: &C, state: &ActorModelState<A, H>) -> bool,
+    #[allow(clippy::type_complexity)]
+    pub byzantine_msgs: fn(cfg: &C, state: &ActorModelState<A, H>, src: Id) -> Vec<(Id, A::Msg)>,
 }
 
 /// Indicates possible steps that an actor system can take as it evolves.
@@ -52,6 +81,26 @@ pub enum ActorModelAction<Msg, Timer> {
     /// An actor can by notified after a timeout.
     Timeout(Id, Timer),
     Crash(Id),
+    /// A crashed actor can restart. See [`Actor::on_recover`].
+    Recover(Id),
+    /// The network can be cut between a group of actors and the rest. Indicates the position of
+    /// the group within [`ActorModel::partition_shapes`].
+    Partition(usize),
+    /// A partitioned network can be repaired.
+    Heal,
+    /// An honest actor can become Byzantine. See [`ActorModel::max_byzantine`].
+    Corrupt(Id),
+    /// A Byzantine actor can send an arbitrary message. See [`ActorModel::byzantine_msgs`].
+    Inject(Envelope<Msg>),
+    /// Time can pass until another timer can fire. See [`ActorModel::discrete_time`].
+    AdvanceClock,
+    /// Time can pass as with [`ActorModelAction::AdvanceClock`] while an actor's clock drifts to
+    /// an adjacent skew. See [`ActorModel::clock_drift`].
+    DriftClock(Id, ClockSkew),
+    /// A pooled actor can start. See [`ActorModel::pool_actor`].
+    Join(Id),
+    /// An actor can be decommissioned, never to return. See [`ActorModel::max_leaves`].
+    Leave(Id),
 }
 
 /// Indicates whether the network loses messages. Note that as long as invariants do not check
@@ -63,6 +112,96 @@ pub enum LossyNetwork {
     No,
 }
 
+/// Indicates how a directed link from one actor to another behaves. Links without a policy
+/// deliver messages and drop them only if [`ActorModel::lossy_network`] is [`LossyNetwork::Yes`].
+///
+/// A policy restricts the network rather than replacing it, so a link over a
+/// [`Network::UnorderedDuplicating`] or [`Network::UnorderedBoundedDuplicating`] network can
+/// redeliver messages even if [`LinkPolicy::duplicate`] is `false`.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LinkPolicy {
+    /// Whether messages can be delivered over the link.
+    pub deliver: bool,
+    /// Whether messages can be lost.
+    pub drop: bool,
+    /// Whether a delivered message remains in the network and can be redelivered, even if the
+    /// network does not otherwise duplicate messages.
+    pub duplicate: bool,
+}
+
+impl LinkPolicy {
+    /// A link that never loses messages, and that delivers each message once unless the network
+    /// duplicates messages.
+    pub const RELIABLE: Self = LinkPolicy {
+        deliver: true,
+        drop: false,
+        duplicate: false,
+    };
+
+    /// A link that can lose messages.
+    pub const LOSSY: Self = LinkPolicy {
+        deliver: true,
+        drop: true,
+        duplicate: false,
+    };
+
+    /// A link that never delivers messages. Messages remain in the network, which is
+    /// indistinguishable from an unlimited delay as long as invariants do not check the network
+    /// state.
+    pub const DOWN: Self = LinkPolicy {
+        deliver: false,
+        drop: false,
+        duplicate: false,
+    };
+}
+
+/// Indicates how an actor's local clock relates to the global clock. An actor's skew is fixed
+/// for the entire run unless [`ActorModel::clock_drift`] is enabled. See
+/// [`ActorModel::max_clock_skew`].
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+pub enum ClockSkew {
+    /// The local clock lags the global clock by the maximum skew.
+    Behind,
+    /// The local clock matches the global clock.
+    Synchronized,
+    /// The local clock leads the global clock by the maximum skew.
+    Ahead,
+}
+
+impl ClockSkew {
+    /// The skews to which a clock with this skew can drift in one step.
+    fn adjacent(self) -> &'static [ClockSkew] {
+        match self {
+            ClockSkew::Behind => &[ClockSkew::Synchronized],
+            ClockSkew::Synchronized => &[ClockSkew::Behind, ClockSkew::Ahead],
+            ClockSkew::Ahead => &[ClockSkew::Synchronized],
+        }
+    }
+}
+
+impl<R> Rewrite<R> for ClockSkew {
+    fn rewrite<S>(&self, _: &RewritePlan<R, S>) -> Self {
+        *self
+    }
+}
+
+/// Indicates whether an actor is part of the system. See [`ActorModel::pool_actor`].
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+pub enum Membership {
+    /// The actor is pooled and has not started yet.
+    Pending,
+    /// The actor has started.
+    Member,
+    /// The actor has left.
+    Departed,
+}
+
+impl<R> Rewrite<R> for Membership {
+    fn rewrite<S>(&self, _: &RewritePlan<R, S>) -> Self {
+        *self
+    }
+}
+
 /// The specific timeout value is not relevant for model checking, so this helper can be used to
 /// generate an arbitrary timeout range. The specific value is subject to change, so this helper
 /// must only be used for model checking.
@@ -92,11 +231,24 @@ where
             init_history,
             init_network: Network::new_unordered_duplicating([]),
             lossy_network: LossyNetwork::No,
+            link_policies: HashMap::new(),
             max_crashes: 0,
+            max_recoveries: 0,
+            partition_shapes: Vec::new(),
+            max_partitions: 0,
+            max_byzantine: 0,
+            pool: Vec::new(),
+            max_joins: 0,
+            max_leaves: 0,
+            discrete_time: false,
+            max_clock_skew: Duration::ZERO,
+            clock_drift: false,
+            sleep_sets: false,
             properties: Default::default(),
             record_msg_in: |_, _, _| None,
             record_msg_out: |_, _, _| None,
             within_boundary: |_, _| true,
+            byzantine_msgs: |_, _, _| Vec::new(),
         }
     }
 
@@ -126,12 +278,196 @@ where
         self
     }
 
+    /// Adds an [`Actor`] that is not started until it joins the system via
+    /// [`ActorModelAction::Join`], for instance to check reconfiguration protocols. Until then
+    /// the actor's state is whatever [`Actor::on_start`] returns, but messages are not delivered
+    /// to it. See [`ActorModel::max_joins`].
+    pub fn pool_actor(mut self, actor: A) -> Self {
+        self.pool.push(Id::from(self.actors.len()));
+        self.actors.push(actor);
+        self
+    }
+
+    /// Adds multiple pooled [`Actor`]s to this model. See [`ActorModel::pool_actor`].
+    pub fn pool_actors(mut self, actors: impl IntoIterator<Item = A>) -> Self {
+        for actor in actors {
+            self = self.pool_actor(actor);
+        }
+        self
+    }
+
+    /// Specifies the maximum number of pooled actors that can join, in total.
+    pub fn max_joins(mut self, max_joins: usize) -> Self {
+        self.max_joins = max_joins;
+        self
+    }
+
+    /// Specifies the maximum number of actors that can leave, in total. An actor that leaves
+    /// stops permanently, and messages are no longer delivered to it.
+    pub fn max_leaves(mut self, max_leaves: usize) -> Self {
+        self.max_leaves = max_leaves;
+        self
+    }
+
+    /// Defines how the directed link from `src` to `dst` behaves, overriding
+    /// [`ActorModel::lossy_network`] for that link. For instance a one-way link failure can be
+    /// modeled by specifying [`LinkPolicy::DOWN`] for only one direction.
+    ///
+    /// A duplicating link leaves delivered messages in the network, so they can be redelivered
+    /// until dropped. On an ordered network this means that later messages on the link are not
+    /// delivered until the duplicated message is dropped. A non-duplicating link does not prevent
+    /// a duplicating network from redelivering messages.
+    pub fn link_policy(mut self, src: Id, dst: Id, policy: LinkPolicy) -> Self {
+        self.link_policies.insert((src, dst), policy);
+        self
+    }
+
     /// Specifies the maximum number of actors that can be contemporarily crashed
     pub fn max_crashes(mut self, max_crashes: usize) -> Self {
         self.max_crashes = max_crashes;
         self
     }
 
+    /// Specifies the maximum number of times that crashed actors can recover, in total. Each
+    /// recovery restarts an actor via [`Actor::on_recover`].
+    pub fn max_recoveries(mut self, max_recoveries: usize) -> Self {
+        self.max_recoveries = max_recoveries;
+        self
+    }
+
+    /// Adds a group of actors that can be cut off from the rest of the actors by a network
+    /// partition, for instance a minority of a cluster. While partitioned, messages are not
+    /// delivered between the group and the rest until the network heals, although they can still
+    /// be dropped if the network is lossy. See [`ActorModel::max_partitions`].
+    pub fn partition_shape(mut self, group: impl IntoIterator<Item = Id>) -> Self {
+        let mut group: Vec<_> = group.into_iter().collect();
+        group.sort();
+        group.dedup();
+        self.partition_shapes.push(group);
+        self
+    }
+
+    /// Specifies the maximum number of times that the network can be partitioned, in total.
+    /// Only one partition is in effect at a time.
+    pub fn max_partitions(mut self, max_partitions: usize) -> Self {
+        self.max_partitions = max_partitions;
+        self
+    }
+
+    /// Specifies the maximum number of actors that can become Byzantine, for instance `f` when
+    /// checking a protocol that tolerates `f` faulty actors. A Byzantine actor continues to run
+    /// its [`Actor`] implementation, but it can additionally send any message supplied by
+    /// [`ActorModel::byzantine_msgs`], and it can replay any message it has sent or can receive
+    /// to any actor (possibly equivocating by sending different peers different messages).
+    ///
+    /// Messages sent this way are not recorded in the history, and properties should generally
+    /// only consider honest actors. See [`ActorModelState::honest_actor_states`].
+    pub fn max_byzantine(mut self, max_byzantine: usize) -> Self {
+        self.max_byzantine = max_byzantine;
+        self
+    }
+
+    /// Defines the messages that a Byzantine actor `src` can send in a given state, as
+    /// `(dst, msg)` pairs. Only messages that are not already pending in the network are sent.
+    /// See [`ActorModel::max_byzantine`].
+    #[allow(clippy::type_complexity)]
+    pub fn byzantine_msgs(
+        mut self,
+        byzantine_msgs: fn(cfg: &C, state: &ActorModelState<A, H>, src: Id) -> Vec<(Id, A::Msg)>,
+    ) -> Self {
+        self.byzantine_msgs = byzantine_msgs;
+        self
+    }
+
+    /// Enables a discrete-time mode in which the system tracks a global clock, and a timer set
+    /// with a [`Range<Duration>`] can only fire after the range's start has elapsed and must fire
+    /// before time passes the range's end. Time only passes via
+    /// [`ActorModelAction::AdvanceClock`] (or [`ActorModelAction::DriftClock`]), which jumps to
+    /// the next time at which a timer can fire. Otherwise any set timer can fire at any time.
+    ///
+    /// The clock is part of the state, so systems whose timers are perpetually renewed have an
+    /// unbounded state space unless [`ActorModel::within_boundary`] limits
+    /// [`ActorModelState::now`].
+    pub fn discrete_time(mut self, discrete_time: bool) -> Self {
+        self.discrete_time = discrete_time;
+        self
+    }
+
+    /// Specifies the maximum difference between an actor's local clock, as reported by
+    /// [`Out::clock`], and the global clock, which is only tracked if
+    /// [`ActorModel::discrete_time`] is enabled. Each actor's clock is either behind, synchronized
+    /// with, or ahead of the global clock by this amount, and every combination is checked
+    /// initially. Skews are fixed for the entire run unless [`ActorModel::clock_drift`] is enabled.
+    /// Timer durations are measured by the global clock, so they are unaffected, although a clock
+    /// that is behind reads zero until the global clock reaches the maximum skew.
+    ///
+    /// # Limitations
+    ///
+    /// The only skews checked are zero and the maximum in either direction, so bugs that only
+    /// occur at intermediate skews are not found. Consider checking several maximum skews to cover
+    /// them.
+    pub fn max_clock_skew(mut self, max_clock_skew: Duration) -> Self {
+        self.max_clock_skew = max_clock_skew;
+        self
+    }
+
+    /// Lets clocks drift, so that an actor's skew can change during a run, as when a lease expires
+    /// early because the holder's clock runs slow. The change is bounded per step: whenever time
+    /// passes, one actor's clock can also drift by the maximum skew to an adjacent skew (from
+    /// behind to synchronized, from synchronized to behind or ahead, or from ahead to
+    /// synchronized) via [`ActorModelAction::DriftClock`]. How far a clock can drift therefore
+    /// depends on how often time passes rather than on how much, and only one clock drifts at a
+    /// time. Only has an effect if [`ActorModel::max_clock_skew`] is nonzero.
+    pub fn clock_drift(mut self, clock_drift: bool) -> Self {
+        self.clock_drift = clock_drift;
+        self
+    }
+
+    /// The reading of an actor's local clock. See [`Out::clock`].
+    pub fn clock(&self, state: &ActorModelState<A, H>, id: Id) -> Option<Duration> {
+        if !self.discrete_time {
+            return None;
+        }
+        Some(match state.clock_skews[usize::from(id)] {
+            ClockSkew::Behind => state.now.saturating_sub(self.max_clock_skew),
+            ClockSkew::Synchronized => state.now,
+            ClockSkew::Ahead => state.now + self.max_clock_skew,
+        })
+    }
+
+    /// Enables "sleep sets", a partial-order reduction that avoids taking multiple orderings of
+    /// independent steps. Steps are independent if they are taken by different actors and
+    /// commute, for instance when two messages are delivered to different actors. If a step was
+    /// already taken from an earlier state, then it is put to sleep along each independent
+    /// step, until a dependent step wakes it.
+    ///
+    /// This prunes transitions rather than states: all reachable states are still visited, so
+    /// properties are checked as before, but each state is reached via fewer transitions, which
+    /// saves computing and looking up the states that those transitions reach. The number of
+    /// unique states is unchanged, so memory use is not reduced. For instance, checking the Paxos
+    /// example with two clients generates 24,078 rather than 32,971 states (16,668 unique either
+    /// way), and with three clients 1,686,132 rather than 2,420,477 (1,194,428 unique), in about
+    /// the same time. The two-phase commit example implements [`Model`] directly rather than via
+    /// actors, so the reduction does not apply to it. Sleeping actions are skipped via
+    /// [`Model::is_redundant`], so [`Model::actions`] still returns every action (as seen by the
+    /// explorer and liveness checking), and checkers that need every transition, such as with
+    /// [`CheckerBuilder::sound_eventually`], take them all. Fewer paths are explored though, so
+    /// [`Expectation::Eventually`] properties are more likely to be satisfied by chance when a
+    /// state is reachable via multiple paths.
+    ///
+    /// A step that updates the history is considered dependent on every other step, as are
+    /// deliveries on an [`Network::UnorderedDuplicating`] network (which remembers the last
+    /// delivered message), so recording steps reduce less.
+    /// The reduction assumes that [`ActorModel::within_boundary`] does not exclude states based
+    /// on the order of steps taken by different actors, and that whether a message is recorded
+    /// does not depend on the history.
+    ///
+    /// [`CheckerBuilder::sound_eventually`]: crate::CheckerBuilder::sound_eventually
+    pub fn sleep_sets(mut self, sleep_sets: bool) -> Self {
+        self.sleep_sets = sleep_sets;
+        self
+    }
+
     /// Adds a [`Property`] to this model.
     #[allow(clippy::type_complexity)]
     pub fn property(
@@ -144,10 +480,17 @@ where
             expectation,
             name,
             condition,
+            formula: None,
         });
         self
     }
 
+    /// Adds a [linear temporal logic](Ltl) [`Property`] to this model. See [`Property::ltl`].
+    pub fn ltl_property(mut self, name: &'static str, formula: Ltl<Self>) -> Self {
+        self.properties.push(Property::ltl(name, formula));
+        self
+    }
+
     /// Defines whether/how an incoming message contributes to relevant history. Returning
     /// `Some(new_history)` updates the relevant history, while `None` does not.
     pub fn record_msg_in(
@@ -177,9 +520,16 @@ where
         self
     }
 
-    /// Updates the actor state, sends messages, and configures the timers.
-    fn process_commands(&self, id: Id, commands: Out<A>, state: &mut ActorModelState<A, H>) {
+    /// Updates the actor state, sends messages, and configures the timers. Returns whether the
+    /// history was updated.
+    fn process_commands(
+        &self,
+        id: Id,
+        commands: Out<A>,
+        state: &mut ActorModelState<A, H>,
+    ) -> bool {
         let index = usize::from(id);
+        let mut recorded = false;
         for c in commands {
             match c {
                 Command::Send(dst, msg) => {
@@ -193,108 +543,109 @@ where
                         },
                     ) {
                         state.history = history;
+                        recorded = true;
                     }
                     state.network.send(Envelope { src: id, dst, msg });
                 }
-                Command::SetTimer(timer, _) => {
+                Command::SetTimer(timer, range) => {
                     // must use the index to infer how large as actor state may not be initialized yet
                     if state.timers_set.len() <= index {
                         state.timers_set.resize_with(index + 1, Timers::new);
                     }
-                    state.timers_set[index].set(timer);
+                    if self.discrete_time {
+                        let deadline = Deadline {
+                            earliest: state.now + range.start,
+                            latest: state.now + std::cmp::max(range.start, range.end),
+                        };
+                        state.timers_set[index].set_with_deadline(timer, deadline);
+                    } else {
+                        state.timers_set[index].set(timer);
+                    }
                 }
                 Command::CancelTimer(timer) => {
                     state.timers_set[index].cancel(&timer);
                 }
+                Command::Persist(storage) => {
+                    // must use the index to infer how large as actor state may not be initialized yet
+                    if state.persisted.len() <= index {
+                        state.persisted.resize(index + 1, None);
+                    }
+                    state.persisted[index] = Some(Arc::new(storage));
+                }
             }
         }
+        recorded
     }
-}
-
-impl<A, C, H> Model for ActorModel<A, C, H>
-where
-    A: Actor,
-    H: Clone + Debug + Hash,
-{
-    type State = ActorModelState<A, H>;
-    type Action = ActorModelAction<A::Msg, A::Timer>;
 
-    fn init_states(&self) -> Vec<Self::State> {
+    /// Initializes the system with particular clock skews.
+    fn init_state(&self, clock_skews: Vec<ClockSkew>) -> ActorModelState<A, H> {
         let mut init_sys_state = ActorModelState {
             actor_states: Vec::with_capacity(self.actors.len()),
+            persisted: vec![None; self.actors.len()],
             history: self.init_history.clone(),
             timers_set: vec![Timers::new(); self.actors.len()],
             network: self.init_network.clone(),
             crashed: vec![false; self.actors.len()],
+            recoveries: 0,
+            partition: None,
+            partitions: 0,
+            byzantine: vec![false; self.actors.len()],
+            now: Duration::ZERO,
+            clock_skews,
+            membership: vec![Membership::Member; self.actors.len()],
+            sleep: Vec::new(),
         };
 
         // init each actor
         for (index, actor) in self.actors.iter().enumerate() {
             let id = Id::from(index);
-            let mut out = Out::new();
+            let mut out = Out::with_clock(self.clock(&init_sys_state, id));
             let state = actor.on_start(id, &mut out);
             init_sys_state.actor_states.push(Arc::new(state));
+            if self.pool.contains(&id) {
+                init_sys_state.membership[index] = Membership::Pending;
+                continue;
+            }
             self.process_commands(id, out, &mut init_sys_state);
         }
 
-        vec![init_sys_state]
+        init_sys_state
     }
 
-    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
-        let mut prev_channel = None; // Only deliver the head of a channel.
-        for env in state.network.iter_deliverable() {
-            // option 1: message is lost
-            if self.lossy_network == LossyNetwork::Yes {
-                actions.push(ActorModelAction::Drop(env.to_cloned_msg()));
-            }
-
-            // option 2: message is delivered
-            if usize::from(env.dst) < self.actors.len() {
-                // ignored if recipient DNE
-                if matches!(self.init_network, Network::Ordered(_)) {
-                    let curr_channel = (env.src, env.dst);
-                    if prev_channel == Some(curr_channel) {
-                        continue;
-                    } // queued behind previous
-                    prev_channel = Some(curr_channel);
-                }
-                actions.push(ActorModelAction::Deliver {
-                    src: env.src,
-                    dst: env.dst,
-                    msg: env.msg.clone(),
-                });
-            }
-        }
-
-        // option 3: actor timeout
-        for (index, timers) in state.timers_set.iter().enumerate() {
-            for timer in timers.iter() {
-                actions.push(ActorModelAction::Timeout(Id::from(index), timer.clone()));
-            }
+    /// The time to which the clock can advance, which is the next time at which a timer can fire
+    /// as long as no other timer must fire first.
+    fn next_clock(&self, state: &ActorModelState<A, H>) -> Option<Duration> {
+        let deadlines = || state.timers_set.iter().flat_map(|t| t.deadlines());
+        let next = deadlines()
+            .map(|(_, d)| d.earliest)
+            .filter(|earliest| *earliest > state.now)
+            .min()?;
+        if deadlines().any(|(_, d)| d.latest < next) {
+            return None;
         }
+        Some(next)
+    }
 
-        // option 4: actor crash
-        let n_crashed = state.crashed.iter().filter(|&crashed| *crashed).count();
-        if n_crashed < self.max_crashes {
-            state
-                .crashed
-                .iter()
-                .enumerate()
-                .filter_map(|(index, &crashed)| if !crashed { Some(index) } else { None })
-                .for_each(|index| actions.push(ActorModelAction::Crash(Id::from(index))));
+    /// The policy for the directed link from `src` to `dst`.
+    fn policy_for(&self, src: Id, dst: Id) -> LinkPolicy {
+        match self.link_policies.get(&(src, dst)) {
+            Some(policy) => *policy,
+            None if self.lossy_network == LossyNetwork::Yes => LinkPolicy::LOSSY,
+            None => LinkPolicy::RELIABLE,
         }
     }
 
-    fn next_state(
+    /// Computes the next state, also indicating whether the history was updated.
+    fn step(
         &self,
-        last_sys_state: &Self::State,
-        action: Self::Action,
-    ) -> Option<Self::State> {
+        last_sys_state: &ActorModelState<A, H>,
+        action: ActorModelAction<A::Msg, A::Timer>,
+    ) -> Option<(ActorModelState<A, H>, bool)> {
         match action {
             ActorModelAction::Drop(env) => {
                 let mut next_state = last_sys_state.clone();
                 next_state.network.on_drop(env);
-                Some(next_state)
+                Some((next_state, false))
             }
             ActorModelAction::Deliver { src, dst: id, msg } => {
                 let index = usize::from(id);
@@ -307,14 +658,30 @@ where
                 if last_sys_state.crashed[index] {
                     return None;
                 }
+                if last_sys_state.membership[index] != Membership::Member {
+                    return None;
+                }
+                if last_sys_state.is_partitioned(src, id) {
+                    return None;
+                }
+                let policy = self.policy_for(src, id);
+                if !policy.deliver {
+                    return None;
+                }
 
                 let last_actor_state = &**last_actor_state.unwrap();
                 let mut state = Cow::Borrowed(last_actor_state);
 
                 // Some operations are no-ops, so ignore those as well.
-                let mut out = Out::new();
+                let mut out = Out::with_clock(self.clock(last_sys_state, id));
                 self.actors[index].on_msg(id, &mut state, src, msg.clone(), &mut out);
-                if is_no_op(&state, &out) && !matches!(self.init_network, Network::Ordered(_)) {
+                // Messages must still be consumed from a queue though.
+                if is_no_op(&state, &out)
+                    && !matches!(
+                        self.init_network,
+                        Network::Ordered(_) | Network::BoundedReordering(..)
+                    )
+                {
                     return None;
                 }
                 let history = (self.record_msg_in)(
@@ -328,7 +695,7 @@ where
                 );
 
                 // Update the state as necessary:
-                // - Drop delivered message if not a duplicating network.
+                // - Drop delivered message if not a duplicating network or link.
                 // - Swap out revised actor state.
                 // - Track message input history.
                 // - Handle effect of commands on timers, network, and message output history.
@@ -338,24 +705,31 @@ where
                 // safe if invariants do not relate to the existence of envelopes on the
                 // network.
                 let mut next_sys_state = last_sys_state.clone();
-                let env = Envelope { src, dst: id, msg };
-                next_sys_state.network.on_deliver(env);
+                if !policy.duplicate
+                    || matches!(next_sys_state.network, Network::UnorderedDuplicating(..))
+                {
+                    let env = Envelope { src, dst: id, msg };
+                    next_sys_state.network.on_deliver(env);
+                }
                 if let Cow::Owned(next_actor_state) = state {
                     next_sys_state.actor_states[index] = Arc::new(next_actor_state);
                 }
+                let mut recorded = false;
                 if let Some(history) = history {
                     next_sys_state.history = history;
+                    recorded = true;
                 }
-                self.process_commands(id, out, &mut next_sys_state);
-                Some(next_sys_state)
+                recorded |= self.process_commands(id, out, &mut next_sys_state);
+                Some((next_sys_state, recorded))
             }
             ActorModelAction::Timeout(id, timer) => {
                 // Clone new state if necessary (otherwise early exit).
                 let index = usize::from(id);
                 let mut state = Cow::Borrowed(&*last_sys_state.actor_states[index]);
-                let mut out = Out::new();
+                let mut out = Out::with_clock(self.clock(last_sys_state, id));
                 self.actors[index].on_timeout(id, &mut state, &timer, &mut out);
-                if is_no_op_with_timer(&state, &out, &timer) {
+                // With discrete time, even a no-op consumes or renews the timer's deadline.
+                if !self.discrete_time && is_no_op_with_timer(&state, &out, &timer) {
                     return None;
                 }
                 let mut next_sys_state = last_sys_state.clone();
@@ -366,8 +740,8 @@ where
                 if let Cow::Owned(next_actor_state) = state {
                     next_sys_state.actor_states[index] = Arc::new(next_actor_state);
                 }
-                self.process_commands(id, out, &mut next_sys_state);
-                Some(next_sys_state)
+                let recorded = self.process_commands(id, out, &mut next_sys_state);
+                Some((next_sys_state, recorded))
             }
             ActorModelAction::Crash(id) => {
                 let index = usize::from(id);
@@ -376,104 +750,566 @@ where
                 next_sys_state.timers_set[index].cancel_all();
                 next_sys_state.crashed[index] = true;
 
-                Some(next_sys_state)
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::Recover(id) => {
+                let index = usize::from(id);
+                let mut out = Out::with_clock(self.clock(last_sys_state, id));
+                let storage = last_sys_state.persisted[index].as_deref();
+                let state = self.actors[index].on_recover(id, storage, &mut out);
+
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.actor_states[index] = Arc::new(state);
+                next_sys_state.crashed[index] = false;
+                next_sys_state.recoveries += 1;
+                let recorded = self.process_commands(id, out, &mut next_sys_state);
+                Some((next_sys_state, recorded))
+            }
+            ActorModelAction::Partition(shape) => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.partition = Some(self.partition_shapes[shape].clone());
+                next_sys_state.partitions += 1;
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::Heal => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.partition = None;
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::Corrupt(id) => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.byzantine[usize::from(id)] = true;
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::Inject(env) => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.network.send(env);
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::AdvanceClock => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.now = self.next_clock(last_sys_state)?;
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::DriftClock(id, clock_skew) => {
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.now = self.next_clock(last_sys_state)?;
+                next_sys_state.clock_skews[usize::from(id)] = clock_skew;
+                Some((next_sys_state, false))
+            }
+            ActorModelAction::Join(id) => {
+                let index = usize::from(id);
+                let mut out = Out::with_clock(self.clock(last_sys_state, id));
+                let state = self.actors[index].on_start(id, &mut out);
+
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.actor_states[index] = Arc::new(state);
+                next_sys_state.membership[index] = Membership::Member;
+                let recorded = self.process_commands(id, out, &mut next_sys_state);
+                Some((next_sys_state, recorded))
+            }
+            ActorModelAction::Leave(id) => {
+                let index = usize::from(id);
+
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.timers_set[index].cancel_all();
+                next_sys_state.crashed[index] = false;
+                next_sys_state.membership[index] = Membership::Departed;
+
+                Some((next_sys_state, false))
             }
         }
     }
 
-    fn format_action(&self, action: &Self::Action) -> String {
-        if let ActorModelAction::Deliver { src, dst, msg } = action {
-            format!("{:?} → {:?} → {:?}", src, msg, dst)
-        } else {
-            format!("{:?}", action)
+    /// Computes the sleep set after taking a step: the actions that need not be taken because
+    /// they were already taken (or will be) from an earlier state, and they are independent of
+    /// the step. Actions are taken in order, so the `earlier` ones, which precede the step among
+    /// those collected from the state, were taken already.
+    fn sleep_after(
+        &self,
+        last_sys_state: &ActorModelState<A, H>,
+        earlier: &[ActorModelAction<A::Msg, A::Timer>],
+        action: &ActorModelAction<A::Msg, A::Timer>,
+        recorded: bool,
+    ) -> Vec<ActorModelAction<A::Msg, A::Timer>> {
+        // History updates need not commute, and learning whether another step updates the
+        // history would require taking it, so a step that does wakes every other.
+        if recorded {
+            return Vec::new();
         }
+        last_sys_state
+            .sleep
+            .iter()
+            .chain(earlier.iter().filter(|a| !last_sys_state.sleep.contains(a)))
+            .filter(|other| self.is_independent(last_sys_state, action, other))
+            .cloned()
+            .collect()
     }
 
-    fn format_step(&self, last_state: &Self::State, action: Self::Action) -> Option<String>
-    where
-        Self::State: Debug,
-    {
-        struct ActorStep<'a, A: Actor> {
-            last_state: &'a A::State,
-            next_state: Option<A::State>,
-            out: Out<A>,
+    /// Indicates whether two actions enabled in a state commute, aside from their effect on the
+    /// history.
+    fn is_independent(
+        &self,
+        last_sys_state: &ActorModelState<A, H>,
+        action: &ActorModelAction<A::Msg, A::Timer>,
+        other: &ActorModelAction<A::Msg, A::Timer>,
+    ) -> bool {
+        use ActorModelAction::*;
+        match (action.actor(), other.actor()) {
+            (Some(id), Some(other_id)) if id != other_id => {}
+            _ => return false, // same actor, or partitions affecting all actors
         }
-        impl<'a, A: Actor> Display for ActorStep<'a, A> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-                writeln!(f, "OUT: {:?}", self.out)?;
-                writeln!(f)?;
-                if let Some(next_state) = &self.next_state {
-                    writeln!(f, "NEXT_STATE: {:#?}", next_state)?;
-                    writeln!(f)?;
-                    writeln!(f, "PREV_STATE: {:#?}", self.last_state)
-                } else {
-                    writeln!(f, "UNCHANGED: {:#?}", self.last_state)
-                }
+        match (action, other) {
+            // A crash can prevent another once `max_crashes` is reached, a recovery can allow
+            // one, and a recovery can prevent another once `max_recoveries` is reached.
+            (Crash(_), Crash(_))
+            | (Crash(_), Recover(_))
+            | (Recover(_), Crash(_))
+            | (Recover(_), Recover(_)) => false,
+            // Joins and leaves are bounded by `max_joins` and `max_leaves`, and a crashed actor
+            // that leaves no longer counts toward `max_crashes`.
+            (Join(_), Join(_))
+            | (Leave(_), Leave(_))
+            | (Crash(_), Leave(_))
+            | (Leave(_), Crash(_)) => false,
+            // Each delivery replaces the last delivered message.
+            (Deliver { .. }, Deliver { .. })
+                if matches!(last_sys_state.network, Network::UnorderedDuplicating(..)) =>
+            {
+                false
             }
+            _ => true,
         }
+    }
+}
 
-        match action {
-            ActorModelAction::Drop(env) => Some(format!("DROP: {:?}", env)),
-            ActorModelAction::Deliver { src, dst: id, msg } => {
-                let index = usize::from(id);
-                let last_actor_state = match last_state.actor_states.get(index) {
-                    None => return None,
-                    Some(last_actor_state) => &**last_actor_state,
-                };
-                let mut actor_state = Cow::Borrowed(last_actor_state);
-                let mut out = Out::new();
-                self.actors[index].on_msg(id, &mut actor_state, src, msg, &mut out);
-                Some(format!(
-                    "{}",
-                    ActorStep {
-                        last_state: last_actor_state,
-                        next_state: match actor_state {
-                            Cow::Borrowed(_) => None,
-                            Cow::Owned(next_actor_state) => Some(next_actor_state),
-                        },
-                        out,
-                    }
-                ))
-            }
-            ActorModelAction::Timeout(id, timer) => {
-                let index = usize::from(id);
-                let last_actor_state = match last_state.actor_states.get(index) {
-                    None => return None,
-                    Some(last_actor_state) => &**last_actor_state,
-                };
-                let mut actor_state = Cow::Borrowed(last_actor_state);
-                let mut out = Out::new();
-                self.actors[index].on_timeout(id, &mut actor_state, &timer, &mut out);
-                Some(format!(
-                    "{}",
-                    ActorStep {
-                        last_state: last_actor_state,
-                        next_state: match actor_state {
-                            Cow::Borrowed(_) => None,
-                            Cow::Owned(next_actor_state) => Some(next_actor_state),
-                        },
-                        out,
-                    }
-                ))
-            }
-            ActorModelAction::Crash(id) => {
-                let index = usize::from(id);
-                last_state.actor_states.get(index).map(|last_actor_state| {
-                    format!(
-                        "{}",
-                        ActorStep {
-                            last_state: &**Cow::Borrowed(last_actor_state),
-                            next_state: None,
-                            out: Out::new() as Out<A>,
-                        }
-                    )
-                })
-            }
+impl<Msg, Timer> ActorModelAction<Msg, Timer> {
+    /// The actor whose state or inbound messages are affected by this action, if only one.
+    fn actor(&self) -> Option<Id> {
+        match self {
+            ActorModelAction::Deliver { dst, .. } => Some(*dst),
+            ActorModelAction::Drop(env) => Some(env.dst),
+            ActorModelAction::Timeout(id, _) => Some(*id),
+            ActorModelAction::Crash(id) => Some(*id),
+            ActorModelAction::Recover(id) => Some(*id),
+            ActorModelAction::Join(id) => Some(*id),
+            ActorModelAction::Leave(id) => Some(*id),
+            // These affect what other actors can do.
+            ActorModelAction::Partition(_)
+            | ActorModelAction::Heal
+            | ActorModelAction::Corrupt(_)
+            | ActorModelAction::Inject(_)
+            | ActorModelAction::AdvanceClock
+            | ActorModelAction::DriftClock(..) => None,
         }
     }
+}
 
-    /// Draws a sequence diagram for the actor system.
-    fn as_svg(&self, path: Path<Self::State, Self::Action>) -> Option<String> {
+impl<A, C, H> Model for ActorModel<A, C, H>
+where
+    A: Actor,
+    H: Clone + Debug + Hash,
+{
+    type State = ActorModelState<A, H>;
+    type Action = ActorModelAction<A::Msg, A::Timer>;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        // Every combination of clock skews is a distinct initial state.
+        let mut all_clock_skews = vec![Vec::with_capacity(self.actors.len())];
+        for _ in &self.actors {
+            if !self.discrete_time || self.max_clock_skew.is_zero() {
+                all_clock_skews
+                    .iter_mut()
+                    .for_each(|skews| skews.push(ClockSkew::Synchronized));
+                continue;
+            }
+            all_clock_skews = all_clock_skews
+                .into_iter()
+                .flat_map(|skews| {
+                    [ClockSkew::Behind, ClockSkew::Synchronized, ClockSkew::Ahead].map(|skew| {
+                        let mut skews = skews.clone();
+                        skews.push(skew);
+                        skews
+                    })
+                })
+                .collect();
+        }
+        all_clock_skews
+            .into_iter()
+            .map(|clock_skews| self.init_state(clock_skews))
+            .collect()
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        let mut prev_channel = None; // Only deliver the head of a channel.
+        for env in state.network.iter_deliverable() {
+            // option 1: message is lost
+            let policy = self.policy_for(env.src, env.dst);
+            if policy.drop {
+                actions.push(ActorModelAction::Drop(env.to_cloned_msg()));
+            }
+
+            // option 2: message is delivered
+            if usize::from(env.dst) < self.actors.len() {
+                // ignored if recipient DNE
+                if matches!(self.init_network, Network::Ordered(_)) {
+                    let curr_channel = (env.src, env.dst);
+                    if prev_channel == Some(curr_channel) {
+                        continue;
+                    } // queued behind previous
+                    prev_channel = Some(curr_channel);
+                }
+                if !policy.deliver
+                    || state.is_partitioned(env.src, env.dst)
+                    || state.membership[usize::from(env.dst)] != Membership::Member
+                {
+                    continue;
+                }
+                actions.push(ActorModelAction::Deliver {
+                    src: env.src,
+                    dst: env.dst,
+                    msg: env.msg.clone(),
+                });
+            }
+        }
+
+        // option 3: actor timeout
+        for (index, timers) in state.timers_set.iter().enumerate() {
+            for (timer, deadline) in timers.deadlines() {
+                if self.discrete_time && deadline.earliest > state.now {
+                    continue;
+                }
+                actions.push(ActorModelAction::Timeout(Id::from(index), timer.clone()));
+            }
+        }
+
+        // option 4: actor crash
+        let n_crashed = state.crashed.iter().filter(|&crashed| *crashed).count();
+        if n_crashed < self.max_crashes {
+            state
+                .crashed
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| state.membership[*index] == Membership::Member)
+                .filter_map(|(index, &crashed)| if !crashed { Some(index) } else { None })
+                .for_each(|index| actions.push(ActorModelAction::Crash(Id::from(index))));
+        }
+
+        // option 5: actor recovery
+        if state.recoveries < self.max_recoveries {
+            state
+                .crashed
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| state.membership[*index] == Membership::Member)
+                .filter_map(|(index, &crashed)| if crashed { Some(index) } else { None })
+                .for_each(|index| actions.push(ActorModelAction::Recover(Id::from(index))));
+        }
+
+        // option 6: network partition or heal
+        if state.partition.is_some() {
+            actions.push(ActorModelAction::Heal);
+        } else if state.partitions < self.max_partitions {
+            for shape in 0..self.partition_shapes.len() {
+                actions.push(ActorModelAction::Partition(shape));
+            }
+        }
+
+        // option 7: actor becomes Byzantine
+        let n_byzantine = state
+            .byzantine
+            .iter()
+            .filter(|&byzantine| *byzantine)
+            .count();
+        if n_byzantine < self.max_byzantine {
+            state
+                .byzantine
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| state.membership[*index] == Membership::Member)
+                .filter_map(|(index, &byzantine)| if !byzantine { Some(index) } else { None })
+                .for_each(|index| actions.push(ActorModelAction::Corrupt(Id::from(index))));
+        }
+
+        // option 8: Byzantine actor sends an adversarial or replayed message
+        for (index, _) in state
+            .byzantine
+            .iter()
+            .enumerate()
+            .filter(|(index, b)| **b && state.membership[*index] == Membership::Member)
+        {
+            let src = Id::from(index);
+            let mut msgs = (self.byzantine_msgs)(&self.cfg, state, src);
+            for env in state.network.iter_all() {
+                if env.src == src || env.dst == src {
+                    for dst in 0..self.actors.len() {
+                        msgs.push((Id::from(dst), env.msg.clone()));
+                    }
+                }
+            }
+            let start = actions.len();
+            for (dst, msg) in msgs {
+                let pending = state
+                    .network
+                    .iter_all()
+                    .any(|e| e.src == src && e.dst == dst && *e.msg == msg);
+                let action = ActorModelAction::Inject(Envelope { src, dst, msg });
+                if dst != src && !pending && !actions[start..].contains(&action) {
+                    actions.push(action);
+                }
+            }
+        }
+
+        // option 9: time passes
+        if self.discrete_time && self.next_clock(state).is_some() {
+            actions.push(ActorModelAction::AdvanceClock);
+            if self.clock_drift && !self.max_clock_skew.is_zero() {
+                for (index, clock_skew) in state.clock_skews.iter().enumerate() {
+                    if state.membership[index] == Membership::Departed {
+                        continue;
+                    }
+                    for &next_clock_skew in clock_skew.adjacent() {
+                        actions.push(ActorModelAction::DriftClock(
+                            Id::from(index),
+                            next_clock_skew,
+                        ));
+                    }
+                }
+            }
+        }
+
+        // option 10: pooled actor joins
+        //
+        // Pooled actors are identified by their membership rather than by `pool`, as symmetry
+        // reduction can reorder actors.
+        let n_pending = state
+            .membership
+            .iter()
+            .filter(|&membership| *membership == Membership::Pending)
+            .count();
+        if self.pool.len() - n_pending < self.max_joins {
+            state
+                .membership
+                .iter()
+                .enumerate()
+                .filter(|(_, &membership)| membership == Membership::Pending)
+                .for_each(|(index, _)| actions.push(ActorModelAction::Join(Id::from(index))));
+        }
+
+        // option 11: actor leaves
+        let n_departed = state
+            .membership
+            .iter()
+            .filter(|&membership| *membership == Membership::Departed)
+            .count();
+        if n_departed < self.max_leaves {
+            state
+                .membership
+                .iter()
+                .enumerate()
+                .filter(|(_, &membership)| membership == Membership::Member)
+                .for_each(|(index, _)| actions.push(ActorModelAction::Leave(Id::from(index))));
+        }
+    }
+
+    fn next_state(
+        &self,
+        last_sys_state: &Self::State,
+        action: Self::Action,
+    ) -> Option<Self::State> {
+        if !self.sleep_sets {
+            return self.step(last_sys_state, action).map(|(s, _)| s);
+        }
+        let mut enabled = Vec::new();
+        self.actions(last_sys_state, &mut enabled);
+        let earlier = match enabled.iter().position(|a| a == &action) {
+            Some(i) => &enabled[..i],
+            None => &[],
+        };
+        let (mut next_sys_state, recorded) = self.step(last_sys_state, action.clone())?;
+        next_sys_state.sleep = self.sleep_after(last_sys_state, earlier, &action, recorded);
+        Some(next_sys_state)
+    }
+
+    fn next_states_for(
+        &self,
+        last_sys_state: &Self::State,
+        actions: &mut Vec<Self::Action>,
+    ) -> Vec<Option<Self::State>> {
+        if !self.sleep_sets {
+            return actions
+                .drain(..)
+                .map(|action| self.step(last_sys_state, action).map(|(s, _)| s))
+                .collect();
+        }
+        // Unlike `next_state`, the enabled actions are already known, so they are not collected
+        // again for each step. Skipped actions are asleep, so they are in the sleep set already.
+        let next_sys_states = actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let (mut next_sys_state, recorded) = self.step(last_sys_state, action.clone())?;
+                next_sys_state.sleep =
+                    self.sleep_after(last_sys_state, &actions[..i], action, recorded);
+                Some(next_sys_state)
+            })
+            .collect();
+        actions.clear();
+        next_sys_states
+    }
+
+    fn is_redundant(&self, state: &Self::State, action: &Self::Action) -> bool {
+        self.sleep_sets && state.sleep.contains(action)
+    }
+
+    fn format_action(&self, action: &Self::Action) -> String {
+        if let ActorModelAction::Deliver { src, dst, msg } = action {
+            format!("{:?} → {:?} → {:?}", src, msg, dst)
+        } else {
+            format!("{:?}", action)
+        }
+    }
+
+    fn format_step(&self, last_state: &Self::State, action: Self::Action) -> Option<String>
+    where
+        Self::State: Debug,
+    {
+        struct ActorStep<'a, A: Actor> {
+            last_state: &'a A::State,
+            next_state: Option<A::State>,
+            out: Out<A>,
+        }
+        impl<'a, A: Actor> Display for ActorStep<'a, A> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                writeln!(f, "OUT: {:?}", self.out)?;
+                writeln!(f)?;
+                if let Some(next_state) = &self.next_state {
+                    writeln!(f, "NEXT_STATE: {:#?}", next_state)?;
+                    writeln!(f)?;
+                    writeln!(f, "PREV_STATE: {:#?}", self.last_state)
+                } else {
+                    writeln!(f, "UNCHANGED: {:#?}", self.last_state)
+                }
+            }
+        }
+
+        match action {
+            ActorModelAction::Drop(env) => Some(format!("DROP: {:?}", env)),
+            ActorModelAction::Deliver { src, dst: id, msg } => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let mut actor_state = Cow::Borrowed(last_actor_state);
+                let mut out = Out::with_clock(self.clock(last_state, id));
+                self.actors[index].on_msg(id, &mut actor_state, src, msg, &mut out);
+                Some(format!(
+                    "{}",
+                    ActorStep {
+                        last_state: last_actor_state,
+                        next_state: match actor_state {
+                            Cow::Borrowed(_) => None,
+                            Cow::Owned(next_actor_state) => Some(next_actor_state),
+                        },
+                        out,
+                    }
+                ))
+            }
+            ActorModelAction::Timeout(id, timer) => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let mut actor_state = Cow::Borrowed(last_actor_state);
+                let mut out = Out::with_clock(self.clock(last_state, id));
+                self.actors[index].on_timeout(id, &mut actor_state, &timer, &mut out);
+                Some(format!(
+                    "{}",
+                    ActorStep {
+                        last_state: last_actor_state,
+                        next_state: match actor_state {
+                            Cow::Borrowed(_) => None,
+                            Cow::Owned(next_actor_state) => Some(next_actor_state),
+                        },
+                        out,
+                    }
+                ))
+            }
+            ActorModelAction::Crash(id) => {
+                let index = usize::from(id);
+                last_state.actor_states.get(index).map(|last_actor_state| {
+                    format!(
+                        "{}",
+                        ActorStep {
+                            last_state: &**Cow::Borrowed(last_actor_state),
+                            next_state: None,
+                            out: Out::new() as Out<A>,
+                        }
+                    )
+                })
+            }
+            ActorModelAction::Recover(id) => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let storage = last_state.persisted[index].as_deref();
+                let mut out = Out::with_clock(self.clock(last_state, id));
+                let next_actor_state = self.actors[index].on_recover(id, storage, &mut out);
+                Some(format!(
+                    "{}",
+                    ActorStep {
+                        last_state: last_actor_state,
+                        next_state: Some(next_actor_state),
+                        out,
+                    }
+                ))
+            }
+            ActorModelAction::Partition(shape) => {
+                Some(format!("PARTITION: {:?}", self.partition_shapes[shape]))
+            }
+            ActorModelAction::Heal => Some("HEAL".to_string()),
+            ActorModelAction::Corrupt(id) => Some(format!("CORRUPT: {:?}", id)),
+            ActorModelAction::Inject(env) => Some(format!("INJECT: {:?}", env)),
+            ActorModelAction::AdvanceClock => Some(format!(
+                "ADVANCE CLOCK: {:?} → {:?}",
+                last_state.now,
+                self.next_clock(last_state)?
+            )),
+            ActorModelAction::DriftClock(id, clock_skew) => Some(format!(
+                "ADVANCE CLOCK: {:?} → {:?}, DRIFT {:?}: {:?} → {:?}",
+                last_state.now,
+                self.next_clock(last_state)?,
+                id,
+                last_state.clock_skews[usize::from(id)],
+                clock_skew
+            )),
+            ActorModelAction::Join(id) => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let mut out = Out::with_clock(self.clock(last_state, id));
+                let next_actor_state = self.actors[index].on_start(id, &mut out);
+                Some(format!(
+                    "{}",
+                    ActorStep {
+                        last_state: last_actor_state,
+                        next_state: Some(next_actor_state),
+                        out,
+                    }
+                ))
+            }
+            ActorModelAction::Leave(id) => Some(format!("LEAVE: {:?}", id)),
+        }
+    }
+
+    /// Draws a sequence diagram for the actor system.
+    fn as_svg(&self, path: Path<Self::State, Self::Action>) -> Option<String> {
         use std::fmt::Write;
 
         let approximate_letter_width_px = 10;
@@ -556,7 +1392,7 @@ where
                     let index = usize::from(id);
                     if let Some(actor_state) = state.actor_states.get(index) {
                         let mut actor_state = Cow::Borrowed(&**actor_state);
-                        let mut out = Out::new();
+                        let mut out = Out::with_clock(self.clock(&state, id));
                         self.actors[index].on_msg(id, &mut actor_state, src, msg, &mut out);
                         for command in out {
                             if let Command::Send(dst, msg) = command {
@@ -578,7 +1414,7 @@ where
                     let index = usize::from(actor_id);
                     if let Some(actor_state) = state.actor_states.get(index) {
                         let mut actor_state = Cow::Borrowed(&**actor_state);
-                        let mut out = Out::new();
+                        let mut out = Out::with_clock(self.clock(&state, actor_id));
                         self.actors[index].on_timeout(actor_id, &mut actor_state, &timer, &mut out);
                         for command in out {
                             if let Command::Send(dst, msg) = command {
@@ -596,20 +1432,82 @@ where
                     )
                     .unwrap();
                 }
-                _ => {}
-            }
-        }
-
-        // Handle event labels last to ensure they are drawn over shapes.
-        for (time, (_state, action)) in path.into_iter().enumerate() {
-            let time = time + 1; // action is for the next step
-            match action {
-                Some(ActorModelAction::Deliver { dst: id, msg, .. }) => {
-                    let (x, y) = plot(id.into(), time);
+                Some(ActorModelAction::Recover(actor_id)) => {
+                    let (x, y) = plot(actor_id.into(), time);
                     writeln!(
                         &mut svg,
-                        "<text x='{}' y='{}' class='svg-event-label'>{:?}</text>",
-                        x, y, msg
+                        "<circle cx='{}' cy='{}' r='10' class='svg-event-shape' />",
+                        x, y
+                    )
+                    .unwrap();
+
+                    // Track sends to facilitate building arrows.
+                    let index = usize::from(actor_id);
+                    if let Some(storage) = state.persisted.get(index) {
+                        let mut out = Out::with_clock(self.clock(&state, actor_id));
+                        self.actors[index].on_recover(actor_id, storage.as_deref(), &mut out);
+                        for command in out {
+                            if let Command::Send(dst, msg) = command {
+                                send_time.insert((actor_id, dst, msg), time);
+                            }
+                        }
+                    }
+                }
+                Some(ActorModelAction::Corrupt(actor_id))
+                | Some(ActorModelAction::Leave(actor_id)) => {
+                    let (x, y) = plot(actor_id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<circle cx='{}' cy='{}' r='10' class='svg-event-shape' />",
+                        x, y
+                    )
+                    .unwrap();
+                }
+                Some(ActorModelAction::Join(actor_id)) => {
+                    let (x, y) = plot(actor_id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<circle cx='{}' cy='{}' r='10' class='svg-event-shape' />",
+                        x, y
+                    )
+                    .unwrap();
+
+                    // Track sends to facilitate building arrows.
+                    let index = usize::from(actor_id);
+                    let mut out = Out::with_clock(self.clock(&state, actor_id));
+                    self.actors[index].on_start(actor_id, &mut out);
+                    for command in out {
+                        if let Command::Send(dst, msg) = command {
+                            send_time.insert((actor_id, dst, msg), time);
+                        }
+                    }
+                }
+                Some(ActorModelAction::Inject(env)) => {
+                    let (x, y) = plot(env.src.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<circle cx='{}' cy='{}' r='10' class='svg-event-shape' />",
+                        x, y
+                    )
+                    .unwrap();
+
+                    // Track sends to facilitate building arrows.
+                    send_time.insert((env.src, env.dst, env.msg), time);
+                }
+                _ => {}
+            }
+        }
+
+        // Handle event labels last to ensure they are drawn over shapes.
+        for (time, (_state, action)) in path.into_iter().enumerate() {
+            let time = time + 1; // action is for the next step
+            match action {
+                Some(ActorModelAction::Deliver { dst: id, msg, .. }) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>{:?}</text>",
+                        x, y, msg
                     )
                     .unwrap();
                 }
@@ -631,6 +1529,51 @@ where
                     )
                     .unwrap();
                 }
+                Some(ActorModelAction::Recover(id)) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>Recover</text>",
+                        x, y
+                    )
+                    .unwrap();
+                }
+                Some(ActorModelAction::Corrupt(id)) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>Corrupt</text>",
+                        x, y
+                    )
+                    .unwrap();
+                }
+                Some(ActorModelAction::Join(id)) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>Join</text>",
+                        x, y
+                    )
+                    .unwrap();
+                }
+                Some(ActorModelAction::Leave(id)) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>Leave</text>",
+                        x, y
+                    )
+                    .unwrap();
+                }
+                Some(ActorModelAction::Inject(env)) => {
+                    let (x, y) = plot(env.src.into(), time);
+                    writeln!(
+                        &mut svg,
+                        "<text x='{}' y='{}' class='svg-event-label'>Inject({:?})</text>",
+                        x, y, env.msg
+                    )
+                    .unwrap();
+                }
                 _ => {}
             }
         }
@@ -646,6 +1589,16 @@ where
     fn within_boundary(&self, state: &Self::State) -> bool {
         (self.within_boundary)(&self.cfg, state)
     }
+
+    fn fairness(&self, action: &Self::Action) -> Option<Fairness> {
+        match action {
+            // A channel that can repeatedly deliver eventually does, even if it also drops.
+            ActorModelAction::Deliver { src, dst, .. } => Some(Fairness::strong((src, dst))),
+            // An actor with a pending timer eventually times out.
+            ActorModelAction::Timeout(id, _) => Some(Fairness::weak(id)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -654,7 +1607,7 @@ mod test {
     use crate::actor::actor_test_util::ping_pong::{PingPongCfg, PingPongMsg, PingPongMsg::*};
     use crate::actor::ActorModelAction::*;
     use crate::{Checker, PathRecorder, StateRecorder};
-    use std::collections::HashSet;
+    use std::collections::{BTreeSet, HashSet};
     use std::sync::Arc;
 
     #[test]
@@ -668,11 +1621,23 @@ mod test {
              last_msg: Option<Envelope<PingPongMsg>>| {
                 let timers_set = vec![Timers::new(); states.len()];
                 let crashed = vec![false; states.len()];
+                let byzantine = vec![false; states.len()];
+                let clock_skews = vec![ClockSkew::Synchronized; states.len()];
+                let membership = vec![Membership::Member; states.len()];
                 ActorModelState {
+                    persisted: vec![None; states.len()],
                     actor_states: states.into_iter().map(Arc::new).collect::<Vec<_>>(),
                     network: Network::new_unordered_duplicating_with_last_msg(envelopes, last_msg),
                     timers_set,
                     crashed,
+                    recoveries: 0,
+                    partition: None,
+                    partitions: 0,
+                    byzantine,
+                    now: Duration::ZERO,
+                    clock_skews,
+                    membership,
+                    sleep: Vec::new(),
                     history: (0_u32, 0_u32), // constant as `maintains_history: false`
                 }
             };
@@ -792,6 +1757,7 @@ mod test {
             type Msg = Msg;
             type State = String;
             type Timer = ();
+            type Storage = ();
             fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
                 if let MyActor::Client { server } = self {
                     o.send(*server, Msg::Ignored);
@@ -975,6 +1941,97 @@ mod test {
         );
     }
 
+    #[test]
+    fn handles_bounded_reordering_network() {
+        // The first actor counts down, and the second records the order of receipt.
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = u8;
+            type State = Vec<u8>;
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == 0.into() {
+                    o.send(1.into(), 3);
+                    o.send(1.into(), 2);
+                    o.send(1.into(), 1);
+                }
+                Vec::new()
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                msg: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                state.to_mut().push(msg);
+            }
+        }
+
+        // Each message can overtake at most one predecessor.
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        ActorModel::new((), ())
+            .actors([TestActor, TestActor])
+            .init_network(Network::new_bounded_reordering(1, []))
+            .property(Expectation::Always, "", |_, _| true)
+            .checker()
+            .visitor(recorder)
+            .spawn_bfs()
+            .join();
+        let receipt_orders: BTreeSet<Vec<u8>> = accessor()
+            .into_iter()
+            .map(|s| (*s.actor_states[1]).clone())
+            .filter(|receipt_order| receipt_order.len() == 3)
+            .collect();
+        assert_eq!(
+            receipt_orders,
+            BTreeSet::from([vec![2, 1, 3], vec![2, 3, 1], vec![3, 1, 2], vec![3, 2, 1]])
+        );
+    }
+
+    #[test]
+    fn handles_bounded_duplicating_network() {
+        // The first actor sends a single message, and the second counts receipts.
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = ();
+            type State = u8;
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == 0.into() {
+                    o.send(1.into(), ());
+                }
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() += 1;
+            }
+        }
+
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        ActorModel::new((), ())
+            .actors([TestActor, TestActor])
+            .init_network(Network::new_unordered_bounded_duplicating(2, []))
+            .property(Expectation::Always, "", |_, _| true)
+            .checker()
+            .visitor(recorder)
+            .spawn_bfs()
+            .join();
+        let receipt_counts: BTreeSet<u8> =
+            accessor().into_iter().map(|s| *s.actor_states[1]).collect();
+        assert_eq!(receipt_counts, BTreeSet::from([0, 1, 2, 3]));
+    }
+
     #[test]
     fn handles_ordered_network_flag() {
         #[derive(Clone)]
@@ -983,6 +2040,7 @@ mod test {
             type Msg = u8;
             type State = Vec<u8>;
             type Timer = ();
+            type Storage = ();
             fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
                 if id == 0.into() {
                     // Count down.
@@ -1059,6 +2117,7 @@ mod test {
                 type Msg = ();
                 type State = usize; // receipt count
                 type Timer = ();
+                type Storage = ();
 
                 fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
                     if id == 0.into() {
@@ -1147,6 +2206,57 @@ mod test {
         assert!(unord_nondup_lossy.contains(&vec![drop, drop]));
     }
 
+    #[test]
+    fn timers_eventually_fire_despite_other_activity() {
+        #[derive(Clone)]
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = bool;
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id(0) {
+                    o.set_timer((), model_timeout());
+                    o.send(Id(1), ());
+                }
+                false
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                _: &mut Cow<Self::State>,
+                src: Id,
+                _: Self::Msg,
+                o: &mut Out<Self>,
+            ) {
+                o.send(src, ());
+            }
+            fn on_timeout(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: &Self::Timer,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() = true;
+            }
+        }
+
+        // Messages bounce back and forth forever, but that does not starve the timer.
+        ActorModel::new((), ())
+            .actors([TestActor, TestActor])
+            .init_network(Network::new_unordered_nonduplicating([]))
+            .property(Expectation::Eventually, "fired", |_, state| {
+                *state.actor_states[0]
+            })
+            .checker()
+            .liveness()
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+
     #[test]
     fn resets_timer() {
         struct TestActor;
@@ -1154,6 +2264,7 @@ mod test {
             type State = ();
             type Msg = ();
             type Timer = ();
+            type Storage = ();
             fn on_start(&self, _: Id, o: &mut Out<Self>) {
                 o.set_timer((), model_timeout());
             }
@@ -1180,6 +2291,646 @@ mod test {
             2
         );
     }
+
+    #[test]
+    fn recovers_durable_state_after_crash() {
+        // The first actor notifies the second, which records the notification in durable and
+        // volatile state. Only the durable state survives a crash.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = (bool, bool);
+            type Msg = ();
+            type Timer = ();
+            type Storage = bool;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id(0) {
+                    o.send(Id(1), ());
+                }
+                (false, false)
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                o: &mut Out<Self>,
+            ) {
+                *state.to_mut() = (true, true);
+                o.persist(true);
+            }
+            fn on_recover(
+                &self,
+                _: Id,
+                storage: Option<&Self::Storage>,
+                _: &mut Out<Self>,
+            ) -> Self::State {
+                (storage.copied().unwrap_or(false), false)
+            }
+        }
+        let model = |max_recoveries| {
+            ActorModel::new((), ())
+                .actors([TestActor, TestActor])
+                .init_network(Network::new_unordered_nonduplicating([]))
+                .max_crashes(1)
+                .max_recoveries(max_recoveries)
+                .property(Expectation::Sometimes, "volatile state lost", |_, state| {
+                    *state.actor_states[1] == (true, false)
+                })
+        };
+
+        let checker = model(1).checker().spawn_bfs().join();
+        checker.assert_discovery(
+            "volatile state lost",
+            vec![
+                Deliver {
+                    src: Id(0),
+                    dst: Id(1),
+                    msg: (),
+                },
+                Crash(Id(1)),
+                Recover(Id(1)),
+            ],
+        );
+        let last_state = checker
+            .discovery("volatile state lost")
+            .unwrap()
+            .last_state()
+            .clone();
+        assert!(!last_state.crashed[1]);
+        assert_eq!(last_state.persisted, vec![None, Some(Arc::new(true))]);
+
+        // Crashed actors remain crashed without recoveries.
+        model(0)
+            .checker()
+            .spawn_bfs()
+            .join()
+            .assert_no_discovery("volatile state lost");
+    }
+
+    #[test]
+    fn link_policies_control_delivery_drops_and_duplicates() {
+        // Each actor greets the other and counts greetings received.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                o.send(Id::from(1 - usize::from(id)), ());
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() += 1;
+            }
+        }
+        let model = |policy_0_to_1, policy_1_to_0| {
+            ActorModel::new((), ())
+                .actors([TestActor, TestActor])
+                .init_network(Network::new_unordered_nonduplicating([]))
+                .link_policy(Id(0), Id(1), policy_0_to_1)
+                .link_policy(Id(1), Id(0), policy_1_to_0)
+                .within_boundary(|_, state| state.actor_states.iter().all(|s| **s < 3))
+                .property(Expectation::Always, "", |_, _| true)
+        };
+        let counts = |model: ActorModel<TestActor>| {
+            let (recorder, accessor) = StateRecorder::new_with_accessor();
+            model.checker().visitor(recorder).spawn_bfs().join();
+            accessor()
+                .into_iter()
+                .map(|s| (*s.actor_states[0], *s.actor_states[1], s.network.len()))
+                .collect::<BTreeSet<_>>()
+        };
+
+        // One-way link failure.
+        assert_eq!(
+            counts(model(LinkPolicy::DOWN, LinkPolicy::RELIABLE)),
+            BTreeSet::from([(0, 0, 2), (1, 0, 1)])
+        );
+
+        // Only one link is lossy.
+        assert_eq!(
+            counts(model(LinkPolicy::LOSSY, LinkPolicy::RELIABLE)),
+            BTreeSet::from([
+                (0, 0, 2),
+                (0, 0, 1),
+                (1, 0, 1),
+                (0, 1, 1),
+                (1, 1, 0),
+                (1, 0, 0)
+            ])
+        );
+
+        // Only one link duplicates.
+        let duplicating = LinkPolicy {
+            duplicate: true,
+            ..LinkPolicy::RELIABLE
+        };
+        assert_eq!(
+            counts(model(duplicating, LinkPolicy::RELIABLE)),
+            BTreeSet::from([
+                (0, 0, 2),
+                (1, 0, 1),
+                (0, 1, 2),
+                (1, 1, 1),
+                (0, 2, 2),
+                (1, 2, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn discrete_time_orders_timers_by_duration() {
+        // A short and a long timer are set, and the actor records the order in which they fire.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = ();
+            type Timer = u8;
+            type Storage = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(1, Duration::from_secs(1)..Duration::from_secs(2));
+                o.set_timer(2, Duration::from_secs(3)..Duration::from_secs(4));
+                Vec::new()
+            }
+            fn on_timeout(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                timer: &Self::Timer,
+                _: &mut Out<Self>,
+            ) {
+                state.to_mut().push(*timer);
+            }
+        }
+        let model = |discrete_time| {
+            ActorModel::new((), ())
+                .actor(TestActor)
+                .discrete_time(discrete_time)
+                .property(Expectation::Sometimes, "long timer first", |_, state| {
+                    state.actor_states[0].first() == Some(&2)
+                })
+                .property(Expectation::Sometimes, "both fired", |_, state| {
+                    state.actor_states[0].len() == 2
+                })
+        };
+
+        let checker = model(true).checker().spawn_bfs().join();
+        checker.assert_no_discovery("long timer first");
+        checker.assert_discovery(
+            "both fired",
+            vec![
+                AdvanceClock,
+                Timeout(Id(0), 1),
+                AdvanceClock,
+                Timeout(Id(0), 2),
+            ],
+        );
+        let last_state = checker
+            .discovery("both fired")
+            .unwrap()
+            .last_state()
+            .clone();
+        assert_eq!(last_state.now, Duration::from_secs(3));
+
+        // Durations are otherwise ignored.
+        model(false)
+            .checker()
+            .spawn_bfs()
+            .join()
+            .assert_discovery("long timer first", vec![Timeout(Id(0), 2)]);
+    }
+
+    #[test]
+    fn local_clocks_are_skewed_from_global_clock() {
+        // The actor reads its clock when starting and again when a timer fires.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = (Option<Duration>, Option<Duration>);
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer((), Duration::from_secs(2)..Duration::from_secs(2));
+                (o.clock(), None)
+            }
+            fn on_timeout(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: &Self::Timer,
+                o: &mut Out<Self>,
+            ) {
+                state.to_mut().1 = o.clock();
+            }
+        }
+        let clock_readings = |discrete_time, clock_drift| {
+            let (recorder, accessor) = StateRecorder::new_with_accessor();
+            ActorModel::new((), ())
+                .actor(TestActor)
+                .discrete_time(discrete_time)
+                .max_clock_skew(Duration::from_secs(1))
+                .clock_drift(clock_drift)
+                .property(Expectation::Always, "", |_, _| true)
+                .checker()
+                .visitor(recorder)
+                .spawn_bfs()
+                .join();
+            accessor()
+                .into_iter()
+                .filter(|s| s.actor_states[0].1.is_some())
+                .map(|s| *s.actor_states[0])
+                .collect::<BTreeSet<_>>()
+        };
+
+        let secs = |secs| Some(Duration::from_secs(secs));
+        assert_eq!(
+            clock_readings(true, false),
+            BTreeSet::from([
+                (secs(0), secs(1)), // behind, but clocks cannot be negative
+                (secs(0), secs(2)),
+                (secs(1), secs(3)),
+            ])
+        );
+
+        // Clocks can drift to an adjacent skew as time passes.
+        assert_eq!(
+            clock_readings(true, true),
+            BTreeSet::from([
+                (secs(0), secs(1)),
+                (secs(0), secs(2)),
+                (secs(0), secs(3)), // synchronized, then ahead
+                (secs(1), secs(2)), // ahead, then synchronized
+                (secs(1), secs(3)),
+            ])
+        );
+
+        // Clocks are only tracked in discrete-time mode.
+        assert_eq!(clock_readings(false, false), BTreeSet::new());
+    }
+
+    #[test]
+    fn partition_suppresses_delivery_across_cut_until_healed() {
+        // The first actor notifies the others.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = ();
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id(0) {
+                    o.broadcast(&[Id(1), Id(2)], &());
+                }
+            }
+        }
+        let model = ActorModel::new((), ())
+            .actors([TestActor, TestActor, TestActor])
+            .init_network(Network::new_unordered_nonduplicating([]))
+            .partition_shape([Id(2)])
+            .max_partitions(1);
+        let deliver = |dst| Deliver {
+            src: Id(0),
+            dst,
+            msg: (),
+        };
+        let actions_from = |state: &ActorModelState<TestActor>| {
+            let mut actions = Vec::new();
+            model.actions(state, &mut actions);
+            actions.sort();
+            actions
+        };
+
+        let init_state = &model.init_states()[0];
+        assert_eq!(
+            actions_from(init_state),
+            vec![deliver(Id(1)), deliver(Id(2)), Partition(0)]
+        );
+
+        let partitioned = model.next_state(init_state, Partition(0)).unwrap();
+        assert_eq!(partitioned.partition, Some(vec![Id(2)]));
+        assert_eq!(actions_from(&partitioned), vec![deliver(Id(1)), Heal]);
+        assert_eq!(model.next_state(&partitioned, deliver(Id(2))), None);
+
+        // Messages across the cut were delayed rather than lost, and the network cannot be
+        // partitioned again.
+        let healed = model.next_state(&partitioned, Heal).unwrap();
+        assert_eq!(healed.partitions, 1);
+        assert_eq!(actions_from(&healed), vec![deliver(Id(1)), deliver(Id(2))]);
+    }
+
+    #[test]
+    fn pooled_actors_can_join_and_members_can_leave() {
+        // An actor greets the first actor upon starting.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = ();
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id != Id(0) {
+                    o.send(Id(0), ());
+                }
+            }
+        }
+        let model = ActorModel::new((), ())
+            .actor(TestActor)
+            .pool_actor(TestActor)
+            .init_network(Network::new_unordered_nonduplicating([]))
+            .max_joins(1)
+            .max_leaves(1);
+        let greeting = Deliver {
+            src: Id(1),
+            dst: Id(0),
+            msg: (),
+        };
+        let actions_from = |state: &ActorModelState<TestActor>| {
+            let mut actions = Vec::new();
+            model.actions(state, &mut actions);
+            actions.sort();
+            actions
+        };
+
+        // The pooled actor has not started, so it has not sent anything.
+        let init_state = &model.init_states()[0];
+        assert_eq!(
+            init_state.membership,
+            vec![Membership::Member, Membership::Pending]
+        );
+        assert_eq!(init_state.network.len(), 0);
+        assert_eq!(actions_from(init_state), vec![Join(Id(1)), Leave(Id(0))]);
+
+        let joined = model.next_state(init_state, Join(Id(1))).unwrap();
+        assert_eq!(joined.membership, vec![Membership::Member; 2]);
+        assert_eq!(
+            actions_from(&joined),
+            vec![greeting, Leave(Id(0)), Leave(Id(1))]
+        );
+
+        // Messages are not delivered to actors that have left, and no other actor can leave.
+        let departed = model.next_state(&joined, Leave(Id(0))).unwrap();
+        assert_eq!(
+            departed.membership,
+            vec![Membership::Departed, Membership::Member]
+        );
+        assert_eq!(actions_from(&departed), vec![]);
+        assert_eq!(model.next_state(&departed, greeting), None);
+
+        // Pooled actors are tracked by the state, which symmetry reduction can reorder.
+        let mut reordered = init_state.clone();
+        reordered.membership.swap(0, 1);
+        assert_eq!(actions_from(&reordered), vec![Join(Id(0)), Leave(Id(1))]);
+
+        // Only members can become Byzantine.
+        let model = model.max_byzantine(1);
+        let init_state = &model.init_states()[0];
+        let mut actions = Vec::new();
+        model.actions(init_state, &mut actions);
+        assert!(actions.contains(&Corrupt(Id(0))));
+        assert!(!actions.contains(&Corrupt(Id(1))));
+    }
+
+    #[test]
+    fn byzantine_actors_can_forge_and_replay_messages() {
+        // The first actor sends `1` to the second, and each actor records the messages it
+        // receives as a bitmask. A Byzantine actor can also send `2`.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = u8;
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id(0) {
+                    o.send(Id(1), 1);
+                }
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                msg: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() |= 1 << msg;
+            }
+        }
+        let model = |max_byzantine| {
+            ActorModel::new((), ())
+                .actors([TestActor, TestActor, TestActor])
+                .init_network(Network::new_unordered_nonduplicating([]))
+                .max_byzantine(max_byzantine)
+                .byzantine_msgs(|_, _, _| vec![(Id(1), 2)])
+                .property(Expectation::Sometimes, "forged", |_, state| {
+                    state.honest_actor_states().any(|(_, s)| s & (1 << 2) != 0)
+                })
+                .property(Expectation::Sometimes, "replayed", |_, state| {
+                    state
+                        .honest_actor_states()
+                        .any(|(id, s)| id == Id(2) && s & (1 << 1) != 0)
+                })
+        };
+
+        let checker = model(1).checker().spawn_bfs().join();
+        for name in ["forged", "replayed"] {
+            let last_state = checker.discovery(name).unwrap().last_state().clone();
+            assert_eq!(last_state.byzantine.iter().filter(|b| **b).count(), 1);
+            assert_eq!(last_state.honest_actor_states().count(), 2);
+        }
+
+        // Only Byzantine actors misbehave.
+        let checker = model(0).checker().spawn_bfs().join();
+        checker.assert_no_discovery("forged");
+        checker.assert_no_discovery("replayed");
+    }
+
+    #[test]
+    fn sleep_sets_visit_same_states() {
+        // Each actor greets every other, and the history records the order of greetings to the
+        // first actor when `cfg` is true.
+        #[derive(Clone)]
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                o.broadcast(&model_peers(id.into(), 3), &());
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                src: Id,
+                _: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() |= 1 << usize::from(src);
+            }
+        }
+        let model = |network: Network<()>, record: bool, max_crashes: usize| {
+            ActorModel::new(record, Vec::new())
+                .actors([TestActor, TestActor, TestActor])
+                .init_network(network)
+                .max_crashes(max_crashes)
+                .record_msg_in(|record, history: &Vec<Id>, env| {
+                    if !record || env.dst != Id::from(0) {
+                        return None;
+                    }
+                    let mut history = history.clone();
+                    history.push(env.src);
+                    Some(history)
+                })
+                .property(Expectation::Always, "unused", |_, _| true) // force full traversal
+                .property(Expectation::Eventually, "greeted", |_, state| {
+                    // Crashed actors are not greeted.
+                    (0..3).all(|i| state.crashed[i] || *state.actor_states[i] == 0b111 & !(1 << i))
+                })
+        };
+
+        for network in Network::<()>::names() {
+            for (record, max_crashes) in [(false, 0), (true, 0), (false, 1)] {
+                let network: Network<()> = network.parse().unwrap();
+                if record && matches!(network, Network::UnorderedDuplicating(..)) {
+                    continue; // redelivery would grow the history without bound
+                }
+                let full = model(network.clone(), record, max_crashes)
+                    .checker()
+                    .spawn_bfs()
+                    .join();
+                let reduced_bfs = model(network.clone(), record, max_crashes)
+                    .sleep_sets(true)
+                    .checker()
+                    .spawn_bfs()
+                    .join();
+                let reduced_dfs = model(network.clone(), record, max_crashes)
+                    .sleep_sets(true)
+                    .checker()
+                    .spawn_dfs()
+                    .join();
+                assert_eq!(reduced_bfs.unique_state_count(), full.unique_state_count());
+                assert_eq!(reduced_dfs.unique_state_count(), full.unique_state_count());
+                if !matches!(network, Network::UnorderedDuplicating(..)) {
+                    assert!(reduced_bfs.state_count() < full.state_count());
+                    assert!(reduced_dfs.state_count() < full.state_count());
+                }
+
+                // States with only sleeping actions are not terminal.
+                if max_crashes == 0 {
+                    full.assert_no_discovery("greeted");
+                    reduced_bfs.assert_no_discovery("greeted");
+                    reduced_dfs.assert_no_discovery("greeted");
+                }
+
+                // IDDFS can check a state more than once, and redelivery leads to many paths.
+                if !matches!(
+                    network,
+                    Network::UnorderedDuplicating(..) | Network::UnorderedBoundedDuplicating(..)
+                ) {
+                    let (recorder, accessor) = StateRecorder::new_with_accessor();
+                    let reduced_iddfs = model(network.clone(), record, max_crashes)
+                        .sleep_sets(true)
+                        .checker()
+                        .visitor(recorder)
+                        .spawn_iddfs()
+                        .join();
+                    let states: HashSet<_> = accessor().iter().map(crate::fingerprint).collect();
+                    assert_eq!(states.len(), full.unique_state_count());
+                    if max_crashes == 0 {
+                        reduced_iddfs.assert_no_discovery("greeted");
+                    }
+                }
+            }
+        }
+
+        // Sleeping actions are skipped by searches but remain available to the explorer and
+        // liveness checking.
+        let model = model(Network::new_unordered_nonduplicating([]), false, 0).sleep_sets(true);
+        let init = &model.init_states()[0];
+        let (_, last) = model.next_steps(init).pop().unwrap();
+        let mut actions = Vec::new();
+        model.actions(&last, &mut actions);
+        assert_eq!(actions.len(), 5);
+        assert_eq!(
+            actions
+                .iter()
+                .filter(|a| model.is_redundant(&last, a))
+                .count(),
+            4
+        );
+
+        // Expanding a state with the actions a checker collected yields the same sleep sets as
+        // taking each action on its own.
+        let mut actions = Vec::new();
+        model.actions(init, &mut actions);
+        let each: Vec<_> = actions
+            .iter()
+            .map(|a| model.next_state(init, *a).unwrap().sleep)
+            .collect();
+        assert_eq!(
+            each.iter().map(Vec::len).collect::<Vec<_>>(),
+            [0, 1, 2, 2, 3, 4]
+        );
+        let all: Vec<_> = model
+            .next_states_for(init, &mut actions)
+            .into_iter()
+            .map(|s| s.unwrap().sleep)
+            .collect();
+        assert_eq!(all, each);
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn sleep_sets_preserve_discoveries() {
+        let checker = PingPongCfg {
+            maintains_history: true,
+            max_nat: 2,
+        }
+        .into_model()
+        .init_network(Network::new_unordered_nonduplicating([]))
+        .sleep_sets(true)
+        .checker()
+        .spawn_bfs()
+        .join();
+        checker.assert_discovery(
+            "can reach max",
+            vec![
+                Deliver {
+                    src: Id::from(0),
+                    dst: Id::from(1),
+                    msg: Ping(0),
+                },
+                Deliver {
+                    src: Id::from(1),
+                    dst: Id::from(0),
+                    msg: Pong(0),
+                },
+                Deliver {
+                    src: Id::from(0),
+                    dst: Id::from(1),
+                    msg: Ping(1),
+                },
+                Deliver {
+                    src: Id::from(1),
+                    dst: Id::from(0),
+                    msg: Pong(1),
+                },
+            ],
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1196,6 +2947,7 @@ mod choice_test {
         type State = u8;
         type Msg = ();
         type Timer = ();
+        type Storage = ();
         fn on_start(&self, _: Id, _: &mut Out<Self>) -> Self::State {
             1
         }
@@ -1220,6 +2972,7 @@ mod choice_test {
         type State = char;
         type Msg = ();
         type Timer = ();
+        type Storage = ();
         fn on_start(&self, _: Id, _: &mut Out<Self>) -> Self::State {
             'a'
         }
@@ -1244,6 +2997,7 @@ mod choice_test {
         type State = String;
         type Msg = ();
         type Timer = ();
+        type Storage = ();
         fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
             o.send(self.a, ());
             "I".to_string()
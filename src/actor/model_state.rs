@@ -1,20 +1,77 @@
 //! Private module for selective re-export.
 
-use crate::actor::{Actor, Id, Network};
+use crate::actor::{Actor, ActorModelAction, ClockSkew, Id, Membership, Network};
 use crate::{Representative, Rewrite, RewritePlan};
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::timers::Timers;
 
 /// Represents a snapshot in time for the entire actor system.
 pub struct ActorModelState<A: Actor, H = ()> {
     pub actor_states: Vec<Arc<A::State>>,
+    /// The durable state last persisted by each actor. See [`Out::persist`].
+    ///
+    /// [`Out::persist`]: crate::actor::Out::persist
+    pub persisted: Vec<Option<Arc<A::Storage>>>,
     pub network: Network<A::Msg>,
     pub timers_set: Vec<Timers<A::Timer>>,
     pub crashed: Vec<bool>,
+    /// The number of times that crashed actors have recovered.
+    pub recoveries: usize,
+    /// The actors currently cut off from the rest by a network partition, if any. See
+    /// [`ActorModel::partition_shape`].
+    ///
+    /// [`ActorModel::partition_shape`]: crate::actor::ActorModel::partition_shape
+    pub partition: Option<Vec<Id>>,
+    /// The number of times that the network has been partitioned.
+    pub partitions: usize,
+    /// Which actors are Byzantine. See [`ActorModel::max_byzantine`].
+    ///
+    /// [`ActorModel::max_byzantine`]: crate::actor::ActorModel::max_byzantine
+    pub byzantine: Vec<bool>,
+    /// The time elapsed since the system started. Only advances if
+    /// [`ActorModel::discrete_time`] is enabled.
+    ///
+    /// [`ActorModel::discrete_time`]: crate::actor::ActorModel::discrete_time
+    pub now: Duration,
+    /// How each actor's local clock relates to [`ActorModelState::now`]. See
+    /// [`ActorModel::max_clock_skew`].
+    ///
+    /// [`ActorModel::max_clock_skew`]: crate::actor::ActorModel::max_clock_skew
+    pub clock_skews: Vec<ClockSkew>,
+    /// Whether each actor has joined or left the system. See [`ActorModel::pool_actor`].
+    ///
+    /// [`ActorModel::pool_actor`]: crate::actor::ActorModel::pool_actor
+    pub membership: Vec<Membership>,
     pub history: H,
+    /// Actions that need not be taken from this state when [`ActorModel::sleep_sets`] is
+    /// enabled. Not part of the state's identity.
+    ///
+    /// [`ActorModel::sleep_sets`]: crate::actor::ActorModel::sleep_sets
+    pub sleep: Vec<ActorModelAction<A::Msg, A::Timer>>,
+}
+
+impl<A: Actor, H> ActorModelState<A, H> {
+    /// Indicates whether a network partition currently separates `src` from `dst`.
+    pub fn is_partitioned(&self, src: Id, dst: Id) -> bool {
+        match &self.partition {
+            None => false,
+            Some(group) => group.contains(&src) != group.contains(&dst),
+        }
+    }
+
+    /// Returns the states of actors that are not Byzantine, which are typically the only ones
+    /// that properties consider.
+    pub fn honest_actor_states(&self) -> impl Iterator<Item = (Id, &A::State)> {
+        self.actor_states
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.byzantine.get(*index).copied().unwrap_or(false))
+            .map(|(index, state)| (Id::from(index), &**state))
+    }
 }
 
 impl<A, H> serde::Serialize for ActorModelState<A, H>
@@ -23,14 +80,24 @@ where
     A::State: serde::Serialize,
     A::Msg: serde::Serialize,
     A::Timer: serde::Serialize,
+    A::Storage: serde::Serialize,
     H: serde::Serialize,
 {
     fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
         use serde::ser::SerializeStruct;
-        let mut out = ser.serialize_struct("ActorModelState", 4)?;
+        let mut out = ser.serialize_struct("ActorModelState", 13)?;
         out.serialize_field("actor_states", &self.actor_states)?;
+        out.serialize_field("persisted", &self.persisted)?;
         out.serialize_field("network", &self.network)?;
         out.serialize_field("is_timer_set", &self.timers_set)?;
+        out.serialize_field("crashed", &self.crashed)?;
+        out.serialize_field("recoveries", &self.recoveries)?;
+        out.serialize_field("partition", &self.partition)?;
+        out.serialize_field("partitions", &self.partitions)?;
+        out.serialize_field("byzantine", &self.byzantine)?;
+        out.serialize_field("now", &self.now)?;
+        out.serialize_field("clock_skews", &self.clock_skews)?;
+        out.serialize_field("membership", &self.membership)?;
         out.serialize_field("history", &self.history)?;
         out.end()
     }
@@ -46,10 +113,19 @@ where
     fn clone(&self) -> Self {
         ActorModelState {
             actor_states: self.actor_states.clone(),
+            persisted: self.persisted.clone(),
             history: self.history.clone(),
             timers_set: self.timers_set.clone(),
             network: self.network.clone(),
             crashed: self.crashed.clone(),
+            recoveries: self.recoveries,
+            partition: self.partition.clone(),
+            partitions: self.partitions,
+            byzantine: self.byzantine.clone(),
+            now: self.now,
+            clock_skews: self.clock_skews.clone(),
+            membership: self.membership.clone(),
+            sleep: self.sleep.clone(),
         }
     }
 }
@@ -64,9 +140,18 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut builder = f.debug_struct("ActorModelState");
         builder.field("actor_states", &self.actor_states);
+        builder.field("persisted", &self.persisted);
         builder.field("history", &self.history);
         builder.field("is_timer_set", &self.timers_set);
         builder.field("network", &self.network);
+        builder.field("crashed", &self.crashed);
+        builder.field("recoveries", &self.recoveries);
+        builder.field("partition", &self.partition);
+        builder.field("partitions", &self.partitions);
+        builder.field("byzantine", &self.byzantine);
+        builder.field("now", &self.now);
+        builder.field("clock_skews", &self.clock_skews);
+        builder.field("membership", &self.membership);
         builder.finish()
     }
 }
@@ -77,6 +162,7 @@ impl<A, H> Eq for ActorModelState<A, H>
 where
     A: Actor,
     A::State: Eq,
+    A::Storage: Eq,
     H: Eq,
 {
 }
@@ -90,9 +176,18 @@ where
 {
     fn hash<Hash: Hasher>(&self, state: &mut Hash) {
         self.actor_states.hash(state);
+        self.persisted.hash(state);
         self.history.hash(state);
         self.timers_set.hash(state);
         self.network.hash(state);
+        self.crashed.hash(state);
+        self.recoveries.hash(state);
+        self.partition.hash(state);
+        self.partitions.hash(state);
+        self.byzantine.hash(state);
+        self.now.hash(state);
+        self.clock_skews.hash(state);
+        self.membership.hash(state);
     }
 }
 
@@ -106,9 +201,18 @@ where
 {
     fn eq(&self, other: &Self) -> bool {
         self.actor_states.eq(&other.actor_states)
+            && self.persisted.eq(&other.persisted)
             && self.history.eq(&other.history)
             && self.timers_set.eq(&other.timers_set)
             && self.network.eq(&other.network)
+            && self.crashed.eq(&other.crashed)
+            && self.recoveries.eq(&other.recoveries)
+            && self.partition.eq(&other.partition)
+            && self.partitions.eq(&other.partitions)
+            && self.byzantine.eq(&other.byzantine)
+            && self.now.eq(&other.now)
+            && self.clock_skews.eq(&other.clock_skews)
+            && self.membership.eq(&other.membership)
     }
 }
 
@@ -117,16 +221,30 @@ where
     A: Actor,
     A::Msg: Rewrite<Id>,
     A::State: Ord + Rewrite<Id>,
+    A::Storage: Rewrite<Id>,
     H: Rewrite<Id>,
 {
     fn representative(&self) -> Self {
         let plan = RewritePlan::from_values_to_sort(&self.actor_states);
         Self {
             actor_states: plan.reindex(&self.actor_states),
+            persisted: plan.reindex(&self.persisted),
             network: self.network.rewrite(&plan),
             timers_set: plan.reindex(&self.timers_set),
             crashed: plan.reindex(&self.crashed),
+            recoveries: self.recoveries,
+            partition: self.partition.as_ref().map(|group| {
+                let mut group = group.rewrite(&plan);
+                group.sort();
+                group
+            }),
+            partitions: self.partitions,
+            byzantine: plan.reindex(&self.byzantine),
+            now: self.now,
+            clock_skews: plan.reindex(&self.clock_skews),
+            membership: plan.reindex(&self.membership),
             history: self.history.rewrite(&plan),
+            sleep: Vec::new(),
         }
     }
 }
@@ -134,9 +252,10 @@ where
 #[cfg(test)]
 mod test {
     use crate::actor::timers::Timers;
-    use crate::actor::{Actor, ActorModelState, Envelope, Id, Network, Out};
+    use crate::actor::{Actor, ActorModelState, ClockSkew, Envelope, Id, Membership, Network, Out};
     use crate::{Representative, Rewrite, RewritePlan};
     use std::sync::Arc;
+    use std::time::Duration;
 
     #[test]
     fn can_find_representative_from_equivalence_class() {
@@ -150,6 +269,7 @@ mod test {
                 Arc::new(ActorState { acks: vec![]}),
                 Arc::new(ActorState { acks: vec![Id::from(1)]}),
             ],
+            persisted: vec![None; 3],
             network: Network::new_unordered_duplicating([
                 // Id(0) sends peers "Write(X)" and receives two acks.
                 Envelope { src: 0.into(), dst: 1.into(), msg: "Write(X)" },
@@ -164,6 +284,14 @@ mod test {
             ]),
             timers_set: vec![non_empty_timers.clone(), empty_timers.clone(), non_empty_timers.clone()],
             crashed: vec![false; 3],
+            recoveries: 0,
+            partition: None,
+            partitions: 0,
+            byzantine: vec![false; 3],
+            now: Duration::ZERO,
+            clock_skews: vec![ClockSkew::Synchronized; 3],
+            membership: vec![Membership::Member; 3],
+            sleep: Vec::new(),
             history: History {
                 send_sequence: vec![
                     // Id(0) sends two writes
@@ -188,6 +316,7 @@ mod test {
                 Arc::new(ActorState { acks: vec![Id::from(0)]}),
                 Arc::new(ActorState { acks: vec![Id::from(0), Id::from(1)]}),
             ],
+            persisted: vec![None; 3],
             network: Network::new_unordered_duplicating([
                 // Id(2) sends peers "Write(X)" and receives two acks.
                 Envelope { src: 2.into(), dst: 0.into(), msg: "Write(X)" },
@@ -202,6 +331,14 @@ mod test {
             ]),
             timers_set: vec![empty_timers, non_empty_timers.clone(), non_empty_timers.clone()],
             crashed: vec![false; 3],
+            recoveries: 0,
+            partition: None,
+            partitions: 0,
+            byzantine: vec![false; 3],
+            now: Duration::ZERO,
+            clock_skews: vec![ClockSkew::Synchronized; 3],
+            membership: vec![Membership::Member; 3],
+            sleep: Vec::new(),
             history: History {
                 send_sequence: vec![
                     // Id(2) sends two writes
@@ -217,11 +354,41 @@ mod test {
         });
     }
 
+    #[test]
+    fn serializes_and_formats_every_field_of_its_identity() {
+        use crate::actor::ActorModel;
+        use crate::Model;
+        let state = &ActorModel::new((), ()).actor(()).init_states()[0];
+        let serialized = serde_json::to_value(state).unwrap();
+        let keys: Vec<_> = serialized.as_object().unwrap().keys().cloned().collect();
+        let expected = [
+            "actor_states",
+            "persisted",
+            "network",
+            "is_timer_set",
+            "crashed",
+            "recoveries",
+            "partition",
+            "partitions",
+            "byzantine",
+            "now",
+            "clock_skews",
+            "membership",
+            "history",
+        ];
+        assert_eq!(keys.len(), expected.len());
+        for key in expected {
+            assert!(keys.contains(&key.to_owned()), "missing {}", key);
+            assert!(format!("{:?}", state).contains(key), "missing {}", key);
+        }
+    }
+
     struct A;
     impl Actor for A {
         type Msg = &'static str;
         type State = ActorState;
         type Timer = ();
+        type Storage = ();
         fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State {
             unimplemented!();
         }
@@ -65,6 +65,17 @@ where
     ///
     /// [`ordered_reliable_link`]: crate::actor::ordered_reliable_link
     Ordered(BTreeMap<(Id, Id), VecDeque<Msg>>),
+
+    /// Indicates that directed message flows between pairs of actors are ordered except that a
+    /// message can overtake up to the specified number of earlier messages in the same flow, as
+    /// can happen when UDP datagrams race or a TCP connection is reestablished. A bound of zero
+    /// is equivalent to [`Network::Ordered`].
+    BoundedReordering(BTreeMap<(Id, Id), VecDeque<Msg>>, usize),
+
+    /// Indicates that messages have no ordering (racing one another), and each message sent can
+    /// be redelivered up to the specified number of times, as with an at-least-once message
+    /// queue. Tracks the number of deliveries remaining for each message.
+    UnorderedBoundedDuplicating(HashableHashMap<Envelope<Msg>, usize>, usize),
 }
 
 impl<Msg> Network<Msg>
@@ -89,6 +100,21 @@ where
         this
     }
 
+    /// Indicates that directed message flows between pairs of actors are ordered except that a
+    /// message can overtake up to `max_overtaken` earlier messages in the same flow.
+    ///
+    /// See also: [`Self::new_ordered`]
+    pub fn new_bounded_reordering(
+        max_overtaken: usize,
+        envelopes: impl IntoIterator<Item = Envelope<Msg>>,
+    ) -> Self {
+        let mut this = Self::BoundedReordering(BTreeMap::new(), max_overtaken);
+        for env in envelopes {
+            this.send(env);
+        }
+        this
+    }
+
     /// Indicates that messages have no ordering (racing one another), and can be redelivered.
     /// It also sets the last message delivered.
     ///
@@ -121,6 +147,24 @@ where
         this
     }
 
+    /// Indicates that messages have no ordering (racing one another), and each message sent can
+    /// be redelivered up to `max_duplicates` times.
+    ///
+    /// See also: [`Self::new_unordered_duplicating`]
+    pub fn new_unordered_bounded_duplicating(
+        max_duplicates: usize,
+        envelopes: impl IntoIterator<Item = Envelope<Msg>>,
+    ) -> Self {
+        let mut this = Self::UnorderedBoundedDuplicating(
+            HashableHashMap::with_hasher(crate::stable::build_hasher()),
+            max_duplicates,
+        );
+        for env in envelopes {
+            this.send(env);
+        }
+        this
+    }
+
     /// Indicates that messages have no ordering (racing one another), and will not be redelivered.
     ///
     /// See also: [`Self::new_unordered_duplicating`]
@@ -136,7 +180,10 @@ where
         this
     }
 
-    /// Returns a vector of names that can be parsed using [`FromStr`].
+    /// Returns a vector of names that can be parsed using [`FromStr`]. `bounded_reordering`
+    /// allows a message to overtake one earlier message, and `unordered_bounded_duplicating`
+    /// allows a message to be redelivered once. The bounds can be specified as
+    /// `bounded_reordering:N` and `unordered_bounded_duplicating:N` respectively.
     pub fn names() -> Vec<&'static str> {
         struct IterStr<Msg: Eq + Hash>(Option<Network<Msg>>);
         impl<Msg: Eq + Hash> Iterator for IterStr<Msg> {
@@ -153,9 +200,18 @@ where
                             Some("unordered_duplicating")
                         }
                         Network::UnorderedNonDuplicating(_) => {
-                            self.0 = None;
+                            self.0 = Some(Network::BoundedReordering(Default::default(), 1));
                             Some("unordered_nonduplicating")
                         }
+                        Network::BoundedReordering(_, _) => {
+                            self.0 =
+                                Some(Network::UnorderedBoundedDuplicating(Default::default(), 1));
+                            Some("bounded_reordering")
+                        }
+                        Network::UnorderedBoundedDuplicating(_, _) => {
+                            self.0 = None;
+                            Some("unordered_bounded_duplicating")
+                        }
                     }
                 } else {
                     None
@@ -165,19 +221,25 @@ where
         IterStr::<Msg>(Some(Network::Ordered(Default::default()))).collect()
     }
 
-    /// Returns an iterator over all envelopes in the network.
-    pub fn iter_all(&self) -> NetworkIter<Msg> {
+    /// Returns an iterator over all envelopes in the network. Envelopes that can be redelivered
+    /// are only included once.
+    pub fn iter_all(&self) -> NetworkIter<'_, Msg> {
         match self {
             Network::UnorderedDuplicating(set, _) => NetworkIter::UnorderedDuplicating(set.iter()),
             Network::UnorderedNonDuplicating(multiset) => {
                 NetworkIter::UnorderedNonDuplicating(None, multiset.iter())
             }
-            Network::Ordered(map) => NetworkIter::Ordered(None, map.iter()),
+            Network::Ordered(map) | Network::BoundedReordering(map, _) => {
+                NetworkIter::Ordered(None, map.iter())
+            }
+            Network::UnorderedBoundedDuplicating(map, _) => {
+                NetworkIter::UnorderedBoundedDuplicating(map.keys())
+            }
         }
     }
 
     /// Returns an iterator over all distinct deliverable envelopes in the network.
-    pub fn iter_deliverable(&self) -> NetworkDeliverableIter<Msg> {
+    pub fn iter_deliverable(&self) -> NetworkDeliverableIter<'_, Msg> {
         match self {
             Network::UnorderedDuplicating(set, _) => {
                 NetworkDeliverableIter::UnorderedDuplicating(set.iter())
@@ -186,16 +248,26 @@ where
                 NetworkDeliverableIter::UnorderedNonDuplicating(multiset.keys())
             }
             Network::Ordered(map) => NetworkDeliverableIter::Ordered(map.iter()),
+            Network::BoundedReordering(map, max_overtaken) => {
+                NetworkDeliverableIter::BoundedReordering(None, map.iter(), *max_overtaken)
+            }
+            Network::UnorderedBoundedDuplicating(map, _) => {
+                NetworkDeliverableIter::UnorderedNonDuplicating(map.keys())
+            }
         }
     }
 
-    /// Returns the number of messages in the network.
+    /// Returns the number of messages in the network. Messages that can be redelivered are only
+    /// counted once.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         match self {
             Network::UnorderedDuplicating(set, _) => set.len(),
             Network::UnorderedNonDuplicating(multiset) => multiset.values().sum(),
-            Network::Ordered(map) => map.values().map(VecDeque::len).sum(),
+            Network::Ordered(map) | Network::BoundedReordering(map, _) => {
+                map.values().map(VecDeque::len).sum()
+            }
+            Network::UnorderedBoundedDuplicating(map, _) => map.len(),
         }
     }
 
@@ -208,7 +280,10 @@ where
             Network::UnorderedNonDuplicating(multiset) => {
                 *multiset.entry(envelope).or_insert(0) += 1;
             }
-            Network::Ordered(map) => {
+            Network::UnorderedBoundedDuplicating(map, max_duplicates) => {
+                *map.entry(envelope).or_insert(0) += 1 + *max_duplicates;
+            }
+            Network::Ordered(map) | Network::BoundedReordering(map, _) => {
                 map.entry((envelope.src, envelope.dst))
                     .or_insert_with(|| VecDeque::with_capacity(1))
                     .push_back(envelope.msg);
@@ -226,7 +301,8 @@ where
                 // state can also produce a different fingerprint
                 last_msg.replace(envelope);
             }
-            Network::UnorderedNonDuplicating(multiset) => match multiset.entry(envelope) {
+            Network::UnorderedNonDuplicating(multiset)
+            | Network::UnorderedBoundedDuplicating(multiset, _) => match multiset.entry(envelope) {
                 hash_map::Entry::Occupied(mut entry) => {
                     let value = *entry.get();
                     assert!(value > 0);
@@ -240,7 +316,7 @@ where
                     panic!("envelope not found");
                 }
             },
-            Network::Ordered(map) => {
+            Network::Ordered(map) | Network::BoundedReordering(map, _) => {
                 // Find the flow, then find the message in the flow, and finally remove the message
                 // from the flow. Flows must be non-empty (to ensure removing a message is the
                 // inverse of adding it), so also canonicalize by deleting the entire flow if it
@@ -274,7 +350,8 @@ where
             Network::UnorderedDuplicating(set, _) => {
                 set.remove(&envelope);
             }
-            Network::UnorderedNonDuplicating(multiset) => match multiset.entry(envelope) {
+            Network::UnorderedNonDuplicating(multiset)
+            | Network::UnorderedBoundedDuplicating(multiset, _) => match multiset.entry(envelope) {
                 hash_map::Entry::Occupied(mut entry) => {
                     let value = *entry.get();
                     assert!(value > 0);
@@ -288,7 +365,7 @@ where
                     panic!("envelope not found");
                 }
             },
-            Network::Ordered(map) => {
+            Network::Ordered(map) | Network::BoundedReordering(map, _) => {
                 // Find the flow, then find the message in the flow, and finally remove the message
                 // from the flow. Flows must be non-empty (to ensure removing a message is the
                 // inverse of adding it), so also canonicalize by deleting the entire flow if it
@@ -325,7 +402,20 @@ where
             "ordered" => Ok(Self::new_ordered([])),
             "unordered_duplicating" => Ok(Self::new_unordered_duplicating([])),
             "unordered_nonduplicating" => Ok(Self::new_unordered_nonduplicating([])),
-            _ => Err(format!("unable to parse network name: {}", s)),
+            "bounded_reordering" => Ok(Self::new_bounded_reordering(1, [])),
+            "unordered_bounded_duplicating" => Ok(Self::new_unordered_bounded_duplicating(1, [])),
+            _ => match s.strip_prefix("bounded_reordering:").map(str::parse) {
+                Some(Ok(max_overtaken)) => Ok(Self::new_bounded_reordering(max_overtaken, [])),
+                _ => match s
+                    .strip_prefix("unordered_bounded_duplicating:")
+                    .map(str::parse)
+                {
+                    Some(Ok(max_duplicates)) => {
+                        Ok(Self::new_unordered_bounded_duplicating(max_duplicates, []))
+                    }
+                    _ => Err(format!("unable to parse network name: {}", s)),
+                },
+            },
         }
     }
 }
@@ -343,6 +433,12 @@ where
                 Network::UnorderedNonDuplicating(multiset.rewrite(plan))
             }
             Network::Ordered(map) => Network::Ordered(map.rewrite(plan)),
+            Network::BoundedReordering(map, max_overtaken) => {
+                Network::BoundedReordering(map.rewrite(plan), *max_overtaken)
+            }
+            Network::UnorderedBoundedDuplicating(map, max_duplicates) => {
+                Network::UnorderedBoundedDuplicating(map.rewrite(plan), *max_duplicates)
+            }
         }
     }
 }
@@ -359,6 +455,7 @@ pub enum NetworkIter<'a, Msg> {
         Option<(Id, Id, &'a VecDeque<Msg>, usize)>,
         btree_map::Iter<'a, (Id, Id), VecDeque<Msg>>,
     ),
+    UnorderedBoundedDuplicating(hash_map::Keys<'a, Envelope<Msg>, usize>),
 }
 
 impl<'a, Msg> Iterator for NetworkIter<'a, Msg> {
@@ -372,7 +469,7 @@ impl<'a, Msg> Iterator for NetworkIter<'a, Msg> {
             }),
             NetworkIter::UnorderedNonDuplicating(active, it) => {
                 if let Some((env, count)) = active {
-                    // invariant: count > 1
+                    // invariant: count > 0
                     let env = *env; // to avoid holding a reference inside active
                     *count -= 1;
                     if *count == 0 {
@@ -387,26 +484,33 @@ impl<'a, Msg> Iterator for NetworkIter<'a, Msg> {
                         msg: &env.msg,
                     };
                     if *count > 1 {
-                        *active = Some((env, *count));
+                        *active = Some((env, *count - 1));
                     }
                     env
                 })
             }
             NetworkIter::Ordered(active, it) => {
                 if let Some((src, dst, messages, index)) = active {
-                    let msg = messages.get(*index).unwrap(); // messages.len() > 1
-                    return Some(Envelope {
-                        src: *src,
-                        dst: *dst,
-                        msg,
-                    });
+                    if let Some(msg) = messages.get(*index) {
+                        *index += 1;
+                        return Some(Envelope {
+                            src: *src,
+                            dst: *dst,
+                            msg,
+                        });
+                    }
                 }
                 it.next().map(|(&(src, dst), messages)| {
-                    let msg = messages.front().unwrap(); // messages.len() > 1
-                    *active = Some((src, dst, messages, 0));
+                    let msg = messages.front().unwrap(); // messages.len() > 0
+                    *active = Some((src, dst, messages, 1));
                     Envelope { src, dst, msg }
                 })
             }
+            NetworkIter::UnorderedBoundedDuplicating(it) => it.next().map(|env| Envelope {
+                src: env.src,
+                dst: env.dst,
+                msg: &env.msg,
+            }),
         }
     }
 }
@@ -415,9 +519,15 @@ pub enum NetworkDeliverableIter<'a, Msg> {
     UnorderedDuplicating(hash_set::Iter<'a, Envelope<Msg>>),
     UnorderedNonDuplicating(hash_map::Keys<'a, Envelope<Msg>, usize>),
     Ordered(btree_map::Iter<'a, (Id, Id), VecDeque<Msg>>),
+    BoundedReordering(
+        // active channel/cursor to iterate over the messages that can overtake others
+        Option<(Id, Id, &'a VecDeque<Msg>, usize)>,
+        btree_map::Iter<'a, (Id, Id), VecDeque<Msg>>,
+        usize,
+    ),
 }
 
-impl<'a, Msg> Iterator for NetworkDeliverableIter<'a, Msg> {
+impl<'a, Msg: PartialEq> Iterator for NetworkDeliverableIter<'a, Msg> {
     type Item = Envelope<&'a Msg>;
     fn next(&mut self) -> Option<Self::Item> {
         match self {
@@ -435,6 +545,24 @@ impl<'a, Msg> Iterator for NetworkDeliverableIter<'a, Msg> {
                 let msg = messages.front().expect("empty channel");
                 Envelope { src, dst, msg }
             }),
+            NetworkDeliverableIter::BoundedReordering(active, it, max_overtaken) => loop {
+                if let Some((src, dst, messages, index)) = active {
+                    // Only the first of identical messages is distinct.
+                    while *index <= *max_overtaken && *index < messages.len() {
+                        let msg = &messages[*index];
+                        *index += 1;
+                        if !messages.iter().take(*index - 1).any(|m| m == msg) {
+                            return Some(Envelope {
+                                src: *src,
+                                dst: *dst,
+                                msg,
+                            });
+                        }
+                    }
+                }
+                let (&(src, dst), messages) = it.next()?;
+                *active = Some((src, dst, messages, 0));
+            },
         }
     }
 }
@@ -455,9 +583,116 @@ mod test {
                 Network::new_ordered([]),
                 Network::new_unordered_duplicating([]),
                 Network::new_unordered_nonduplicating([]),
+                Network::new_bounded_reordering(1, []),
+                Network::new_unordered_bounded_duplicating(1, []),
             ]
             .into_iter()
             .collect()
         );
+        assert_eq!(
+            Network::<()>::from_str("bounded_reordering:3"),
+            Ok(Network::new_bounded_reordering(3, []))
+        );
+        assert!(Network::<()>::from_str("bounded_reordering:x").is_err());
+        assert_eq!(
+            Network::<()>::from_str("unordered_bounded_duplicating:0"),
+            Ok(Network::new_unordered_bounded_duplicating(0, []))
+        );
+    }
+
+    #[test]
+    fn bounded_duplicating_tracks_remaining_deliveries() {
+        let env = Envelope {
+            src: Id(0),
+            dst: Id(1),
+            msg: 'a',
+        };
+        let mut network = Network::new_unordered_bounded_duplicating(1, [env]);
+        network.on_deliver(env);
+        assert_eq!(network.iter_deliverable().count(), 1);
+        network.send(env);
+        network.on_drop(env);
+        network.on_deliver(env);
+        assert_eq!(network.iter_deliverable().count(), 1);
+        network.on_deliver(env);
+        assert_eq!(network.iter_deliverable().count(), 0);
+        assert_eq!(network.len(), 0);
+    }
+
+    #[test]
+    fn bounded_reordering_can_deliver_within_window() {
+        let network = Network::new_bounded_reordering(
+            2,
+            ['a', 'a', 'b', 'c'].map(|msg| Envelope {
+                src: Id(0),
+                dst: Id(1),
+                msg,
+            }),
+        );
+        assert_eq!(
+            network
+                .iter_deliverable()
+                .map(|e| *e.msg)
+                .collect::<Vec<_>>(),
+            vec!['a', 'b']
+        );
+
+        let mut network = network;
+        network.on_deliver(Envelope {
+            src: Id(0),
+            dst: Id(1),
+            msg: 'b',
+        });
+        assert_eq!(
+            network
+                .iter_deliverable()
+                .map(|e| *e.msg)
+                .collect::<Vec<_>>(),
+            vec!['a', 'c']
+        );
+        assert_eq!(network.len(), 3);
+    }
+
+    #[test]
+    fn can_iterate_all_envelopes() {
+        let envelopes = vec![
+            Envelope {
+                src: Id(0),
+                dst: Id(1),
+                msg: 'a',
+            },
+            Envelope {
+                src: Id(0),
+                dst: Id(1),
+                msg: 'b',
+            },
+            Envelope {
+                src: Id(0),
+                dst: Id(1),
+                msg: 'a',
+            },
+            Envelope {
+                src: Id(1),
+                dst: Id(0),
+                msg: 'c',
+            },
+        ];
+        let sorted = |network: Network<char>| {
+            let mut all: Vec<_> = network.iter_all().map(|e| e.to_cloned_msg()).collect();
+            all.sort();
+            all
+        };
+        let mut expected = envelopes.clone();
+        expected.sort();
+        assert_eq!(sorted(Network::new_ordered(envelopes.clone())), expected);
+        assert_eq!(
+            sorted(Network::new_unordered_nonduplicating(envelopes.clone())),
+            expected
+        );
+        expected.dedup();
+        assert_eq!(
+            sorted(Network::new_unordered_duplicating(envelopes)),
+            expected
+        );
     }
 }
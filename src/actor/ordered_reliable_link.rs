@@ -82,11 +82,12 @@ where
     type Msg = MsgWrapper<A::Msg>;
     type State = StateWrapper<A::Msg, A::State>;
     type Timer = TimerWrapper<A::Timer>;
+    type Storage = A::Storage;
 
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
         o.set_timer(TimerWrapper::Network, self.resend_interval.clone());
 
-        let mut wrapped_out = Out::new();
+        let mut wrapped_out = Out::with_clock(o.clock());
         let mut state = StateWrapper {
             next_send_seq: 1,
             msgs_pending_ack: Default::default(),
@@ -97,6 +98,26 @@ where
         state
     }
 
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        // Sequencing is not durable, so the link restarts as well.
+        o.set_timer(TimerWrapper::Network, self.resend_interval.clone());
+
+        let mut wrapped_out = Out::with_clock(o.clock());
+        let mut state = StateWrapper {
+            next_send_seq: 1,
+            msgs_pending_ack: Default::default(),
+            last_delivered_seqs: Default::default(),
+            wrapped_state: self.wrapped_actor.on_recover(id, storage, &mut wrapped_out),
+        };
+        process_output(&mut state, wrapped_out, o);
+        state
+    }
+
     fn on_msg(
         &self,
         id: Id,
@@ -115,7 +136,7 @@ where
 
                 // Process the message, and early exit if ignored.
                 let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
-                let mut wrapped_out = Out::new();
+                let mut wrapped_out = Out::with_clock(o.clock());
                 self.wrapped_actor.on_msg(
                     id,
                     &mut wrapped_state,
@@ -163,7 +184,7 @@ where
             }
             TimerWrapper::User(timer) => {
                 let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
-                let mut wrapped_out = Out::new();
+                let mut wrapped_out = Out::with_clock(o.clock());
                 self.wrapped_actor
                     .on_timeout(id, &mut wrapped_state, timer, &mut wrapped_out);
                 if is_no_op(&wrapped_state, &wrapped_out) {
@@ -204,6 +225,9 @@ fn process_output<A: Actor>(
                     .insert(state.next_send_seq, (dst, inner_msg));
                 state.next_send_seq += 1;
             }
+            Command::Persist(storage) => {
+                o.persist(storage);
+            }
         }
     }
 }
@@ -229,6 +253,7 @@ mod test {
         type Msg = TestMsg;
         type State = Received;
         type Timer = ();
+        type Storage = ();
 
         fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
             if let TestActor::Sender { receiver_id } = self {
@@ -126,6 +126,7 @@ where
     type Msg = RegisterMsg<u64, char, InternalMsg>;
     type State = RegisterActorState<ServerActor::State, u64>;
     type Timer = ServerActor::Timer;
+    type Storage = ServerActor::Storage;
 
     fn name(&self) -> String {
         match self {
@@ -174,7 +175,7 @@ where
                 }
             }
             RegisterActor::Server(server_actor) => {
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 let state = RegisterActorState::Server(server_actor.on_start(id, &mut server_out));
                 o.append(&mut server_out);
                 state
@@ -237,7 +238,7 @@ where
             }
             (A::Server(server_actor), S::Server(server_state)) => {
                 let mut server_state = Cow::Borrowed(server_state);
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 server_actor.on_msg(id, &mut server_state, src, msg, &mut server_out);
                 if let Cow::Owned(server_state) = server_state {
                     *state = Cow::Owned(RegisterActorState::Server(server_state))
@@ -248,6 +249,27 @@ where
         }
     }
 
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        match self {
+            RegisterActor::Client { .. } => self.on_start(id, o),
+            RegisterActor::Server(server_actor) => {
+                let mut server_out = Out::with_clock(o.clock());
+                let state = RegisterActorState::Server(server_actor.on_recover(
+                    id,
+                    storage,
+                    &mut server_out,
+                ));
+                o.append(&mut server_out);
+                state
+            }
+        }
+    }
+
     fn on_timeout(
         &self,
         id: Id,
@@ -261,7 +283,7 @@ where
             (A::Client { .. }, S::Client { .. }) => {}
             (A::Server(server_actor), S::Server(server_state)) => {
                 let mut server_state = Cow::Borrowed(server_state);
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 server_actor.on_timeout(id, &mut server_state, timer, &mut server_out);
                 if let Cow::Owned(server_state) = server_state {
                     *state = Cow::Owned(RegisterActorState::Server(server_state))
@@ -0,0 +1,493 @@
+//! Private module for selective re-export.
+
+use crate::actor::*;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::time::Duration;
+
+/// Runs [`Actor`]s in a single thread against a virtual clock, injecting message loss, message
+/// delays, and network partitions that are derived from a seed. This complements model checking
+/// with long randomized executions that honor timer durations, and a failure can be replayed by
+/// running a simulator with the same seed, as [`Simulation::trace`] will be identical.
+///
+/// Actors are identified by their index, as with [`ActorModel`], so the same actors can be model
+/// checked, simulated, and spawned.
+///
+/// # Example
+///
+/// ```
+/// use stateright::actor::{Actor, Id, Out, Simulator};
+/// use std::borrow::Cow;
+/// use std::time::Duration;
+///
+/// struct Heartbeat;
+/// impl Actor for Heartbeat {
+///     type Msg = ();
+///     type State = u32;
+///     type Timer = ();
+///     type Storage = ();
+///     fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+///         o.set_timer((), Duration::from_secs(1)..Duration::from_secs(2));
+///         0
+///     }
+///     fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, _: &(), o: &mut Out<Self>) {
+///         *state.to_mut() += 1;
+///         o.send(Id::from(1 - usize::from(id)), ());
+///         o.set_timer((), Duration::from_secs(1)..Duration::from_secs(2));
+///     }
+/// }
+///
+/// let simulation = Simulator::new(42)
+///     .actors([Heartbeat, Heartbeat])
+///     .message_loss(0.1)
+///     .invariant("few heartbeats", |sim| sim.actor_states.iter().all(|s| *s < 100))
+///     .run(Duration::from_secs(60));
+/// assert_eq!(simulation.failure, None);
+/// ```
+pub struct Simulator<A: Actor> {
+    /// The seed from which all nondeterminism is derived.
+    pub seed: u64,
+    /// The actors to run.
+    pub actors: Vec<A>,
+    /// The probability that a message is lost. See [`Simulator::message_loss`].
+    pub message_loss: f64,
+    /// How long a message takes to be delivered. See [`Simulator::message_delay`].
+    pub message_delay: Range<Duration>,
+    /// How often the network is partitioned, and for how long. See [`Simulator::partitions`].
+    pub partitions: Option<(Range<Duration>, Range<Duration>)>,
+    /// Conditions that must hold after every event. See [`Simulator::invariant`].
+    #[allow(clippy::type_complexity)]
+    pub invariants: Vec<(&'static str, fn(&Simulation<A>) -> bool)>,
+}
+
+/// An execution of a [`Simulator`].
+pub struct Simulation<A: Actor> {
+    /// The seed from which the execution was derived.
+    pub seed: u64,
+    /// The virtual time of the latest event.
+    pub now: Duration,
+    /// The state of each actor.
+    pub actor_states: Vec<A::State>,
+    /// The group of actors cut off from the rest, if the network is partitioned.
+    pub partition: Option<Vec<Id>>,
+    /// Every event, along with the virtual time at which it occurred.
+    #[allow(clippy::type_complexity)]
+    pub trace: Vec<(Duration, SimulationEvent<A::Msg, A::Timer>)>,
+    /// The name of the first invariant that did not hold, if any, in which case the simulation
+    /// stopped after the event that violated it.
+    pub failure: Option<&'static str>,
+}
+
+/// An event in a [`Simulation`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SimulationEvent<Msg, Timer> {
+    /// A message was delivered.
+    Deliver { src: Id, dst: Id, msg: Msg },
+    /// A message was lost, either at random or because of a partition.
+    Drop { src: Id, dst: Id, msg: Msg },
+    /// A timer fired.
+    Timeout(Id, Timer),
+    /// The network was partitioned, cutting off a group of actors from the rest.
+    Partition(Vec<Id>),
+    /// A network partition ended.
+    Heal,
+}
+
+/// An event that has yet to occur.
+enum Pending<Msg, Timer> {
+    Deliver { src: Id, dst: Id, msg: Msg },
+    Timeout(Id, Timer),
+    Partition,
+    Heal,
+}
+
+impl<A: Actor> Simulator<A> {
+    /// Constructs a simulator from a seed. Messages take 1 to 10 milliseconds to be delivered
+    /// and are never lost unless otherwise configured.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            actors: Vec::new(),
+            message_loss: 0.0,
+            message_delay: Duration::from_millis(1)..Duration::from_millis(10),
+            partitions: None,
+            invariants: Vec::new(),
+        }
+    }
+
+    /// Adds another [`Actor`] to this simulator.
+    pub fn actor(mut self, actor: A) -> Self {
+        self.actors.push(actor);
+        self
+    }
+
+    /// Adds multiple [`Actor`]s to this simulator.
+    pub fn actors(mut self, actors: impl IntoIterator<Item = A>) -> Self {
+        for actor in actors {
+            self.actors.push(actor);
+        }
+        self
+    }
+
+    /// Specifies the probability that a message is lost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn message_loss(mut self, probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "message_loss must be between 0 and 1, not {}",
+            probability
+        );
+        self.message_loss = probability;
+        self
+    }
+
+    /// Specifies how long a message takes to be delivered. Each message is delayed independently,
+    /// so messages can be reordered.
+    pub fn message_delay(mut self, delay: Range<Duration>) -> Self {
+        self.message_delay = delay;
+        self
+    }
+
+    /// Periodically partitions the network. After a duration drawn from `interval`, a random group
+    /// of actors is cut off from the rest for a duration drawn from `duration`. A message across
+    /// the cut is lost if it is sent or due to be delivered while the partition is in place, so a
+    /// message in flight when the partition starts is only lost if it arrives before the
+    /// partition heals.
+    pub fn partitions(mut self, interval: Range<Duration>, duration: Range<Duration>) -> Self {
+        self.partitions = Some((interval, duration));
+        self
+    }
+
+    /// Adds a condition that must hold after every event.
+    pub fn invariant(mut self, name: &'static str, condition: fn(&Simulation<A>) -> bool) -> Self {
+        self.invariants.push((name, condition));
+        self
+    }
+
+    /// Runs the actors until the virtual clock would pass `until`, no events remain, or an
+    /// invariant does not hold.
+    pub fn run(&self, until: Duration) -> Simulation<A> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut simulation = Simulation {
+            seed: self.seed,
+            now: Duration::ZERO,
+            actor_states: Vec::with_capacity(self.actors.len()),
+            partition: None,
+            trace: Vec::new(),
+            failure: None,
+        };
+        // Events are ordered by time, then by the order in which they were scheduled.
+        let mut pending = BTreeMap::new();
+        let mut next_seq = 0_u64;
+        let mut schedule = |pending: &mut BTreeMap<_, _>, at: Duration, event| {
+            pending.insert((at, next_seq), event);
+            next_seq += 1;
+            (at, next_seq - 1)
+        };
+        let mut timers = vec![HashMap::new(); self.actors.len()];
+
+        for (index, actor) in self.actors.iter().enumerate() {
+            let id = Id::from(index);
+            let mut out = Out::with_clock(Some(Duration::ZERO));
+            simulation.actor_states.push(actor.on_start(id, &mut out));
+            for command in out {
+                self.on_command(
+                    id,
+                    command,
+                    &mut simulation,
+                    &mut rng,
+                    &mut |at, event| schedule(&mut pending, at, event),
+                    &mut timers[index],
+                );
+            }
+        }
+        if let Some((interval, _)) = &self.partitions {
+            let at = sample(&mut rng, interval);
+            schedule(&mut pending, at, Pending::Partition);
+        }
+
+        while let Some(entry) = pending.first_entry() {
+            let (at, _) = *entry.key();
+            if at > until {
+                break;
+            }
+            let key = *entry.key();
+            let event = entry.remove();
+            simulation.now = at;
+            let acted = match event {
+                Pending::Deliver { src, dst, msg } => {
+                    let index = usize::from(dst);
+                    if index >= self.actors.len() {
+                        continue; // ignored if recipient DNE
+                    }
+                    if simulation.is_partitioned(src, dst) {
+                        let event = SimulationEvent::Drop { src, dst, msg };
+                        simulation.trace.push((at, event));
+                        None
+                    } else {
+                        let mut out = Out::with_clock(Some(at));
+                        let mut state = Cow::Borrowed(&simulation.actor_states[index]);
+                        self.actors[index].on_msg(dst, &mut state, src, msg.clone(), &mut out);
+                        if let Cow::Owned(state) = state {
+                            simulation.actor_states[index] = state;
+                        }
+                        let event = SimulationEvent::Deliver { src, dst, msg };
+                        simulation.trace.push((at, event));
+                        Some((dst, out))
+                    }
+                }
+                Pending::Timeout(id, timer) => {
+                    let index = usize::from(id);
+                    if timers[index].get(&timer) != Some(&key) {
+                        continue; // canceled or reset
+                    }
+                    timers[index].remove(&timer);
+                    let mut out = Out::with_clock(Some(at));
+                    let mut state = Cow::Borrowed(&simulation.actor_states[index]);
+                    self.actors[index].on_timeout(id, &mut state, &timer, &mut out);
+                    if let Cow::Owned(state) = state {
+                        simulation.actor_states[index] = state;
+                    }
+                    let event = SimulationEvent::Timeout(id, timer);
+                    simulation.trace.push((at, event));
+                    Some((id, out))
+                }
+                Pending::Partition => {
+                    let (_, duration) = self.partitions.as_ref().unwrap();
+                    let n = self.actors.len();
+                    if n > 1 {
+                        let size = rng.gen_range(1..n);
+                        let mut group = (0..n).map(Id::from).choose_multiple(&mut rng, size);
+                        group.sort();
+                        simulation.partition = Some(group.clone());
+                        simulation
+                            .trace
+                            .push((at, SimulationEvent::Partition(group)));
+                    }
+                    schedule(&mut pending, at + sample(&mut rng, duration), Pending::Heal);
+                    None
+                }
+                Pending::Heal => {
+                    let (interval, _) = self.partitions.as_ref().unwrap();
+                    if simulation.partition.take().is_some() {
+                        simulation.trace.push((at, SimulationEvent::Heal));
+                    }
+                    let next_at = at + sample(&mut rng, interval);
+                    schedule(&mut pending, next_at, Pending::Partition);
+                    None
+                }
+            };
+            if let Some((id, out)) = acted {
+                for command in out {
+                    self.on_command(
+                        id,
+                        command,
+                        &mut simulation,
+                        &mut rng,
+                        &mut |at, event| schedule(&mut pending, at, event),
+                        &mut timers[usize::from(id)],
+                    );
+                }
+            }
+
+            if let Some((name, _)) = self
+                .invariants
+                .iter()
+                .find(|(_, condition)| !condition(&simulation))
+            {
+                simulation.failure = Some(name);
+                break;
+            }
+        }
+        simulation
+    }
+
+    /// Schedules the effect of an actor output.
+    #[allow(clippy::type_complexity)]
+    fn on_command(
+        &self,
+        id: Id,
+        command: Command<A::Msg, A::Timer, A::Storage>,
+        simulation: &mut Simulation<A>,
+        rng: &mut StdRng,
+        schedule: &mut impl FnMut(Duration, Pending<A::Msg, A::Timer>) -> (Duration, u64),
+        timers: &mut HashMap<A::Timer, (Duration, u64)>,
+    ) {
+        let now = simulation.now;
+        match command {
+            Command::Send(dst, msg) => {
+                if rng.gen_bool(self.message_loss) || simulation.is_partitioned(id, dst) {
+                    let event = SimulationEvent::Drop { src: id, dst, msg };
+                    simulation.trace.push((now, event));
+                } else {
+                    let at = now + sample(rng, &self.message_delay);
+                    schedule(at, Pending::Deliver { src: id, dst, msg });
+                }
+            }
+            Command::SetTimer(timer, duration) => {
+                let at = now + sample(rng, &duration);
+                let key = schedule(at, Pending::Timeout(id, timer.clone()));
+                timers.insert(timer, key);
+            }
+            Command::CancelTimer(timer) => {
+                timers.remove(&timer);
+            }
+            Command::Persist(_) => {} // actors do not crash
+        }
+    }
+}
+
+impl<A: Actor> Simulation<A> {
+    /// Indicates whether a partition prevents messages from `src` reaching `dst`.
+    pub fn is_partitioned(&self, src: Id, dst: Id) -> bool {
+        match &self.partition {
+            None => false,
+            Some(group) => group.contains(&src) != group.contains(&dst),
+        }
+    }
+}
+
+/// Draws a duration from a range, which can be empty.
+fn sample(rng: &mut StdRng, range: &Range<Duration>) -> Duration {
+    if range.start < range.end {
+        rng.gen_range(range.start..range.end)
+    } else {
+        range.start
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::actor::actor_test_util::ping_pong::{PingPongActor, PingPongCfg, PingPongMsg};
+    use crate::actor::*;
+    use std::borrow::Cow;
+    use std::time::Duration;
+
+    fn ping_pong(seed: u64) -> Simulator<PingPongActor> {
+        let model = PingPongCfg {
+            maintains_history: false,
+            max_nat: 0,
+        }
+        .into_model();
+        Simulator::new(seed).actors(model.actors)
+    }
+
+    #[test]
+    fn same_seed_replays_same_trace() {
+        // Each actor periodically messages the others.
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = ();
+            type State = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer((), Duration::from_millis(100)..Duration::from_millis(200));
+            }
+            fn on_timeout(&self, id: Id, _: &mut Cow<Self::State>, _: &(), o: &mut Out<Self>) {
+                o.broadcast(&model_peers(id.into(), 3), &());
+                o.set_timer((), Duration::from_millis(100)..Duration::from_millis(200));
+            }
+        }
+        let simulator = |seed| {
+            Simulator::new(seed)
+                .actors([TestActor, TestActor, TestActor])
+                .message_loss(0.1)
+                .message_delay(Duration::from_millis(1)..Duration::from_millis(100))
+                .partitions(
+                    Duration::from_secs(1)..Duration::from_secs(2),
+                    Duration::from_millis(100)..Duration::from_millis(500),
+                )
+        };
+        let simulation = simulator(7).run(Duration::from_secs(10));
+        let count = |f: fn(&SimulationEvent<(), ()>) -> bool| {
+            simulation.trace.iter().filter(|(_, e)| f(e)).count()
+        };
+        assert!(count(|e| matches!(e, SimulationEvent::Deliver { .. })) > 100);
+        assert!(count(|e| matches!(e, SimulationEvent::Drop { .. })) > 10);
+        assert!(count(|e| matches!(e, SimulationEvent::Partition(_))) > 2);
+
+        assert_eq!(
+            simulation.trace,
+            simulator(7).run(Duration::from_secs(10)).trace
+        );
+        assert_ne!(
+            simulation.trace,
+            simulator(8).run(Duration::from_secs(10)).trace
+        );
+    }
+
+    #[test]
+    fn timers_fire_after_their_durations() {
+        // Sets a long timer, then a short timer that cancels the long one and is reset once.
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = ();
+            type State = u8;
+            type Timer = u64;
+            type Storage = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(5, Duration::from_secs(5)..Duration::from_secs(5));
+                o.set_timer(2, Duration::from_secs(2)..Duration::from_secs(3));
+                0
+            }
+            fn on_timeout(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: &Self::Timer,
+                o: &mut Out<Self>,
+            ) {
+                if **state == 0 {
+                    o.cancel_timer(5);
+                    o.set_timer(2, Duration::from_secs(2)..Duration::from_secs(3));
+                }
+                *state.to_mut() += 1;
+            }
+        }
+        let simulation = Simulator::new(0)
+            .actor(TestActor)
+            .run(Duration::from_secs(60));
+        let timeouts = simulation
+            .trace
+            .iter()
+            .map(|(at, event)| {
+                assert_eq!(event, &SimulationEvent::Timeout(Id::from(0), 2));
+                *at
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(timeouts.len(), 2);
+        assert!(Duration::from_secs(2) <= timeouts[0] && timeouts[0] < Duration::from_secs(3));
+        assert!(Duration::from_secs(4) <= timeouts[1] && timeouts[1] < Duration::from_secs(6));
+        assert_eq!(simulation.now, timeouts[1]);
+    }
+
+    #[test]
+    fn stops_when_invariant_does_not_hold() {
+        let simulation = ping_pong(0)
+            .invariant("below 5", |sim| sim.actor_states.iter().all(|s| *s < 5))
+            .run(Duration::from_secs(60));
+        assert_eq!(simulation.failure, Some("below 5"));
+        assert_eq!(simulation.actor_states, vec![4, 5]);
+        assert_eq!(
+            simulation.trace.last().unwrap().1,
+            SimulationEvent::Deliver {
+                src: Id::from(0),
+                dst: Id::from(1),
+                msg: PingPongMsg::Ping(4),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "message_loss must be between 0 and 1")]
+    fn rejects_invalid_message_loss() {
+        let _ = ping_pong(0).message_loss(1.5);
+    }
+}
@@ -1,11 +1,18 @@
 //! Private module for selective re-export.
 
 use crate::actor::*;
-use crossbeam_utils::thread;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
-use std::time::{Duration, Instant};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{
+    Ipv4Addr, Shutdown, SocketAddr, SocketAddrV4, TcpListener, TcpStream, ToSocketAddrs, UdpSocket,
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 impl From<Id> for SocketAddrV4 {
     fn from(id: Id) -> Self {
@@ -33,12 +40,237 @@ impl From<SocketAddrV4> for Id {
     }
 }
 
+/// Maps actor [`Id`]s to the socket addresses of spawned actors, which allows actors to be
+/// reached via IPv6 or host names. An [`Id`] without an entry falls back to the IPv4 address and
+/// port that it encodes, so an address book is only needed for actors whose [`Id`]s do not encode
+/// their addresses, such as the `Id::from(0)`, `Id::from(1)`, etc. used when model checking.
+///
+/// # Example
+///
+/// ```
+/// use stateright::actor::{AddressBook, Id};
+/// use std::net::{Ipv6Addr, SocketAddr};
+/// let addresses = AddressBook::new()
+///     .address(Id::from(0), (Ipv6Addr::LOCALHOST, 3000))
+///     .address(Id::from(1), (Ipv6Addr::LOCALHOST, 3001));
+/// assert_eq!(addresses.addr(Id::from(1)), SocketAddr::from((Ipv6Addr::LOCALHOST, 3001)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AddressBook {
+    addrs: HashMap<Id, SocketAddr>,
+    ids: HashMap<SocketAddr, Id>,
+}
+
+impl AddressBook {
+    /// Constructs an empty address book.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Assigns an address to an actor. Peers must be able to reach the actor at this address, and
+    /// the actor sends from it, so it should not be a wildcard address.
+    pub fn address(mut self, id: impl Into<Id>, addr: impl Into<SocketAddr>) -> Self {
+        let id = id.into();
+        let addr = addr.into();
+        if let Some(prev_addr) = self.addrs.insert(id, addr) {
+            self.ids.remove(&prev_addr);
+        }
+        self.ids.insert(addr, id);
+        self
+    }
+
+    /// Assigns an address to an actor by resolving a host name and port such as
+    /// `"node1.example.com:3000"`. Uses the first address to which the host name resolves.
+    pub fn resolve(self, id: impl Into<Id>, host: impl ToSocketAddrs) -> std::io::Result<Self> {
+        match host.to_socket_addrs()?.next() {
+            Some(addr) => Ok(self.address(id, addr)),
+            None => Err(std::io::Error::new(
+                ErrorKind::NotFound,
+                "host name resolved to no addresses",
+            )),
+        }
+    }
+
+    /// The address of an actor.
+    pub fn addr(&self, id: Id) -> SocketAddr {
+        match self.addrs.get(&id) {
+            Some(addr) => *addr,
+            None => SocketAddr::V4(SocketAddrV4::from(id)),
+        }
+    }
+
+    /// The actor at an address, if known. IPv4-mapped IPv6 addresses, which dual-stack sockets
+    /// report for IPv4 peers, are treated as the corresponding IPv4 addresses.
+    pub fn id(&self, addr: SocketAddr) -> Option<Id> {
+        let addr = match addr {
+            SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+                Some(ip) => SocketAddr::from((ip, v6.port())),
+                None => addr,
+            },
+            SocketAddr::V4(_) => addr,
+        };
+        match (self.ids.get(&addr), addr) {
+            (Some(id), _) => Some(*id),
+            (None, SocketAddr::V4(v4)) => Some(Id::from(v4)),
+            (None, SocketAddr::V6(_)) => None,
+        }
+    }
+}
+
 /// 500 years in the future.
-fn practically_never() -> Instant {
+pub(super) fn practically_never() -> Instant {
     Instant::now() + Duration::from_secs(3600 * 24 * 365 * 500)
 }
 
-/// Runs an actor, sending messages over UDP. Blocks the current thread.
+/// The time since the Unix epoch. See [`Out::clock`].
+pub(super) fn clock() -> Option<Duration> {
+    SystemTime::now().duration_since(UNIX_EPOCH).ok()
+}
+
+/// The file in a storage directory to which a spawned actor's durable state is written.
+pub(super) fn storage_path(dir: &Path, addr: SocketAddr) -> PathBuf {
+    // Colons in IPv6 addresses are not valid in file names on all platforms.
+    let ip = addr.ip().to_string().replace(':', "-");
+    dir.join(format!("{}-{}.storage", ip, addr.port()))
+}
+
+/// Reads durable state previously written by [`write_storage`], if any. Durable state is encoded
+/// as JSON.
+pub(super) fn read_storage(path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces durable state, syncing it to disk before returning.
+pub(super) fn write_storage(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    // Write to a temporary file first so that a crash cannot leave a partial write.
+    let tmp_path = path.with_extension("storage.tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    // The rename is only durable once the directory is synced, which Windows does not support.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Configures how actors are spawned. [`spawn`], [`spawn_tcp`], and `spawn_async` use the
+/// default configuration.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{AddressBook, Id, Spawner};
+/// use std::net::Ipv6Addr;
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor1 = ();
+/// # let actor2 = ();
+/// let addresses = AddressBook::new()
+///     .address(Id::from(0), (Ipv6Addr::LOCALHOST, 3001))
+///     .address(Id::from(1), (Ipv6Addr::LOCALHOST, 3002));
+/// Spawner::new()
+///     .addresses(addresses)
+///     .storage_dir("/var/lib/my-service")
+///     .spawn(
+///         serde_json::to_vec,
+///         |bytes| serde_json::from_slice(bytes),
+///         vec![
+///             (Id::from(0), actor1),
+///             (Id::from(1), actor2),
+///         ]).unwrap().join().unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use = "This code configures how actors are spawned but does not spawn them. \
+              Consider calling spawn() or spawn_tcp()."]
+pub struct Spawner {
+    pub(super) addresses: AddressBook,
+    pub(super) storage_dir: Option<PathBuf>,
+}
+
+impl Spawner {
+    /// Constructs the default configuration.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sends messages to the addresses in an [`AddressBook`] rather than to the IPv4 addresses
+    /// that [`Id`]s encode. Messages from addresses that are not in the address book are ignored
+    /// unless they are IPv4 addresses, in which case they are from the [`Id`] that encodes the
+    /// address.
+    pub fn addresses(mut self, addresses: AddressBook) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Writes the durable state that each actor records via [`Out::persist`] to a file in `dir`
+    /// named after the actor's address, creating the directory if needed. Each write is synced
+    /// to disk before the actor handles its next event. If an actor's file exists when the actor
+    /// is spawned, then the actor starts via [`Actor::on_recover`] with the [`Actor::Storage`]
+    /// that the file encodes (as JSON) rather than via [`Actor::on_start`], so the directory must
+    /// not be shared by unrelated deployments, and spawning fails if the file cannot be decoded.
+    ///
+    /// Without a storage directory, which is the default, nothing is read from or written to
+    /// disk: actors always start via [`Actor::on_start`] and [`Out::persist`] has no effect.
+    pub fn storage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.storage_dir = Some(dir.into());
+        self
+    }
+
+    /// Runs actors on background threads, sending messages over UDP. See [`spawn`].
+    #[must_use = "Actors stop when the handle is dropped. \
+                  Consider calling join(), for example."]
+    pub fn spawn<A, E: Debug + 'static>(
+        self,
+        serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+        deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+        actors: Vec<(impl Into<Id>, A)>,
+    ) -> std::io::Result<SpawnHandle<A>>
+    where
+        A: 'static + Send + Actor,
+        A::Msg: Debug + Send,
+        A::State: Debug + Send,
+        A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        spawn_with_transport(serialize, deserialize, self, actors, UdpTransport::bind)
+    }
+
+    /// Runs actors on background threads, sending messages over TCP. See [`spawn_tcp`].
+    #[must_use = "Actors stop when the handle is dropped. \
+                  Consider calling join(), for example."]
+    pub fn spawn_tcp<A, E: Debug + 'static>(
+        self,
+        serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+        deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+        actors: Vec<(impl Into<Id>, A)>,
+    ) -> std::io::Result<SpawnHandle<A>>
+    where
+        A: 'static + Send + Actor,
+        A::Msg: Debug + Send,
+        A::State: Debug + Send,
+        A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        spawn_with_transport(serialize, deserialize, self, actors, TcpTransport::bind)
+    }
+}
+
+/// Runs actors on background threads, sending messages over UDP. Returns a [`SpawnHandle`] with
+/// which the actors can be inspected and stopped, or an error if unable to bind a socket.
+///
+/// Durable state recorded via [`Out::persist`] is discarded unless a storage directory is
+/// configured via [`Spawner::storage_dir`].
+///
+/// Each message must fit in a single datagram. See [`spawn_tcp`] for an alternative. Each [`Id`]
+/// encodes the IPv4 address and port of its actor. See [`Spawner::addresses`] for other
+/// addresses.
 ///
 /// # Example
 ///
@@ -59,108 +291,839 @@ fn practically_never() -> Instant {
 ///     vec![
 ///         (id1, actor1),
 ///         (id2, actor2),
-///     ]);
+///     ]).unwrap().join().unwrap();
 /// ```
+#[must_use = "Actors stop when the handle is dropped. \
+              Consider calling join(), for example."]
 pub fn spawn<A, E: Debug + 'static>(
     serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
     deserialize: fn(&[u8]) -> Result<A::Msg, E>,
     actors: Vec<(impl Into<Id>, A)>,
-) -> Result<(), Box<dyn std::any::Any + Send + 'static>>
+) -> std::io::Result<SpawnHandle<A>>
 where
     A: 'static + Send + Actor,
-    A::Msg: Debug,
-    A::State: Debug,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
 {
-    thread::scope(|s| {
-        for (id, actor) in actors {
-            let id = id.into();
-            let addr = SocketAddrV4::from(id);
-
-            // note that panics are returned as `Err` when `join`ing
-            s.spawn(move |_| {
-                let socket = UdpSocket::bind(addr).unwrap(); // panic if unable to bind
-                let mut in_buf = [0; 65_535];
-                let mut next_interrupts = HashMap::new();
-
-                let mut out = Out::new();
-                let mut state = Cow::Owned(actor.on_start(id, &mut out));
-                log::info!("Actor started. id={}, state={:?}, out={:?}", addr, state, out);
-                for c in out {
-                    on_command::<A, E>(addr, c, serialize, &socket, &mut next_interrupts);
+    Spawner::new().spawn(serialize, deserialize, actors)
+}
+
+/// Runs actors on background threads, sending messages over TCP.
+///
+/// Behaves like [`spawn`], so the same actor code can be model checked and then deployed over
+/// either transport, but messages are not limited to the size of a datagram. Each message is
+/// framed by a 4-byte big-endian length prefix, and frames longer than 16 MiB are rejected. An
+/// actor keeps one outbound connection per peer, which a background thread establishes on the
+/// first send and reestablishes if the peer closes it, so that the actor is not blocked while
+/// connecting. As with UDP, delivery is best-effort: messages can be lost while a peer is
+/// unreachable, and are dropped for a second after a failed attempt to connect, which actors
+/// must already tolerate when model checked with a lossy network.
+///
+/// A connection identifies its sender in its first frame. Connections from an IP address other
+/// than the claimed sender's are rejected, but the transport is not authenticated, so a host that
+/// is trusted to run one actor can impersonate other actors at the same IP address.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, spawn_tcp};
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor1 = ();
+/// # let actor2 = ();
+/// let id1 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let id2 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+/// spawn_tcp(
+///     serde_json::to_vec,
+///     |bytes| serde_json::from_slice(bytes),
+///     vec![
+///         (id1, actor1),
+///         (id2, actor2),
+///     ]).unwrap().join().unwrap();
+/// ```
+#[must_use = "Actors stop when the handle is dropped. \
+              Consider calling join(), for example."]
+pub fn spawn_tcp<A, E: Debug + 'static>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>,
+) -> std::io::Result<SpawnHandle<A>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+{
+    Spawner::new().spawn_tcp(serialize, deserialize, actors)
+}
+
+/// Binds every actor and reads its durable state before starting any, so that errors can be
+/// returned.
+#[allow(clippy::type_complexity)]
+fn spawn_with_transport<A, E, T>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    spawner: Spawner,
+    actors: Vec<(impl Into<Id>, A)>,
+    bind: fn(Id, AddressBook, mpsc::Sender<Input<A>>) -> std::io::Result<T>,
+) -> std::io::Result<SpawnHandle<A>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+    E: Debug + 'static,
+    T: 'static + Send + Transport,
+{
+    let Spawner {
+        addresses,
+        storage_dir,
+    } = spawner;
+    if let Some(dir) = &storage_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut bound = Vec::with_capacity(actors.len());
+    for (id, actor) in actors {
+        let id = id.into();
+        let storage_path = storage_dir
+            .as_ref()
+            .map(|dir| storage_path(dir, addresses.addr(id)));
+        let storage = match &storage_path {
+            Some(path) => read_storage(path)?,
+            None => None,
+        };
+        let storage: Option<A::Storage> = match storage {
+            Some(bytes) => Some(serde_json::from_slice(&bytes)?),
+            None => None,
+        };
+        let (inbox_sender, inbox) = mpsc::channel();
+        let transport = bind(id, addresses.clone(), inbox_sender.clone())?;
+        bound.push((
+            id,
+            actor,
+            storage_path,
+            storage,
+            transport,
+            inbox_sender,
+            inbox,
+        ));
+    }
+
+    let actors = bound
+        .into_iter()
+        .map(
+            |(id, actor, storage_path, storage, transport, inbox_sender, inbox)| {
+                let addresses = addresses.clone();
+                SpawnedActor {
+                    id,
+                    inbox: inbox_sender,
+                    thread: Some(std::thread::spawn(move || {
+                        run(
+                            id,
+                            actor,
+                            serialize,
+                            deserialize,
+                            &addresses,
+                            storage_path,
+                            storage,
+                            transport,
+                            inbox,
+                        )
+                    })),
                 }
+            },
+        )
+        .collect();
+    Ok(SpawnHandle { actors })
+}
+
+/// A handle to actors started by [`spawn`] or [`spawn_tcp`], which can inspect them, inject
+/// messages, and stop them. Dropping the handle stops the actors that are still running, so a
+/// process that only runs actors should call [`SpawnHandle::join`] to keep them running.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, spawn};
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// use std::time::Duration;
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor = ();
+/// let id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let client_id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+/// let mut handle = spawn(
+///     serde_json::to_vec,
+///     |bytes| serde_json::from_slice(bytes),
+///     vec![(id, actor)]).unwrap();
+/// handle.send(client_id, id, ());
+/// let state = handle.wait_for_state(id, Duration::from_secs(1), |_| true);
+/// assert_eq!(state, Some(()));
+/// handle.stop_all().unwrap();
+/// ```
+#[must_use = "Actors stop when the handle is dropped. \
+              Consider calling join(), for example."]
+pub struct SpawnHandle<A: Actor> {
+    actors: Vec<SpawnedActor<A>>,
+}
+
+struct SpawnedActor<A: Actor> {
+    id: Id,
+    inbox: mpsc::Sender<Input<A>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// An input to a spawned actor's event loop.
+enum Input<A: Actor> {
+    /// A serialized message from a peer.
+    Recv(Id, Vec<u8>),
+    /// A message injected via [`SpawnHandle::send`].
+    Inject(Id, A::Msg),
+    /// A request for the actor's state.
+    State(mpsc::Sender<A::State>),
+    /// A request to stop.
+    Stop,
+}
+
+impl<A: Actor> SpawnHandle<A> {
+    /// The actors that have not been stopped.
+    pub fn ids(&self) -> Vec<Id> {
+        self.actors
+            .iter()
+            .filter(|actor| actor.thread.is_some())
+            .map(|actor| actor.id)
+            .collect()
+    }
+
+    /// The current state of an actor, or `None` if the actor is not running.
+    pub fn state(&self, id: Id) -> Option<A::State> {
+        let (reply_sender, reply) = mpsc::channel();
+        if !self.input(id, Input::State(reply_sender)) {
+            return None;
+        }
+        reply.recv().ok()
+    }
+
+    /// Polls an actor's state until it satisfies a condition, which can be used to wait for the
+    /// actors to reach quiescence. Returns `None` if the actor is not running or if the timeout
+    /// elapses first.
+    pub fn wait_for_state(
+        &self,
+        id: Id,
+        timeout: Duration,
+        condition: impl Fn(&A::State) -> bool,
+    ) -> Option<A::State> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let state = self.state(id)?;
+            if condition(&state) {
+                return Some(state);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Delivers a message to `dst` as if it were sent by `src`, bypassing the network. Returns
+    /// `false` if `dst` is not running.
+    pub fn send(&self, src: Id, dst: Id, msg: A::Msg) -> bool {
+        self.input(dst, Input::Inject(src, msg))
+    }
+
+    /// Stops an actor and waits for its thread to finish. Returns an error if the actor panicked.
+    pub fn stop(&mut self, id: Id) -> std::thread::Result<()> {
+        self.input(id, Input::Stop);
+        match self.actors.iter_mut().find(|actor| actor.id == id) {
+            Some(actor) => actor.join(),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops all actors and waits for their threads to finish. Returns an error if an actor
+    /// panicked.
+    pub fn stop_all(mut self) -> std::thread::Result<()> {
+        for actor in &self.actors {
+            let _ = actor.inbox.send(Input::Stop);
+        }
+        self.join_all()
+    }
+
+    /// Waits for all actors to stop, which only happens if they panic, so this blocks the current
+    /// thread indefinitely unless an actor fails. Returns an error if an actor panicked.
+    pub fn join(mut self) -> std::thread::Result<()> {
+        self.join_all()
+    }
+
+    /// Sends an input to a running actor, indicating whether the actor is running.
+    fn input(&self, id: Id, input: Input<A>) -> bool {
+        self.actors
+            .iter()
+            .find(|actor| actor.id == id && actor.thread.is_some())
+            .is_some_and(|actor| actor.inbox.send(input).is_ok())
+    }
+
+    fn join_all(&mut self) -> std::thread::Result<()> {
+        let mut result = Ok(());
+        for actor in &mut self.actors {
+            let actor_result = actor.join();
+            if result.is_ok() {
+                result = actor_result;
+            }
+        }
+        result
+    }
+}
+
+impl<A: Actor> SpawnedActor<A> {
+    fn join(&mut self) -> std::thread::Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread.join(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A: Actor> Drop for SpawnHandle<A> {
+    fn drop(&mut self) {
+        for actor in &self.actors {
+            let _ = actor.inbox.send(Input::Stop);
+        }
+        let _ = self.join_all();
+    }
+}
 
-                loop {
-                    // Apply an interrupt if present, otherwise wait for a message.
-                    let mut out = Out::new();
-                    let (min_timer, min_instant) = next_interrupts
-                        .iter()
-                        .min_by_key(|(_, instant)| *instant)
-                        .map(|(t, i)| (Some(t.clone()), *i))
-                        .unwrap_or_else(|| (None, practically_never()));
-                    if let Some(max_wait) = min_instant.checked_duration_since(Instant::now()) {
-                        socket.set_read_timeout(Some(max_wait)).expect("set_read_timeout failed");
-                        match socket.recv_from(&mut in_buf) {
-                            Err(e) => {
-                                // Timeout (`WouldBlock`) ignored since next iteration will apply interrupt.
-                                if e.kind() != std::io::ErrorKind::WouldBlock {
-                                    log::warn!("Unable to read socket. Ignoring. id={}, err={:?}", addr, e);
-                                }
-                                continue;
-                            },
-                            Ok((count, src_addr)) => {
-                                match deserialize(&in_buf[..count]) {
-                                    Ok(msg) => {
-                                        if let SocketAddr::V4(src_addr) = src_addr {
-                                            log::info!("Received message. id={}, src={}, msg={}",
-                                                        addr, src_addr, format!("{:?}", msg));
-                                            actor.on_msg(id, &mut state, Id::from(src_addr), msg, &mut out);
-                                        } else {
-                                            log::debug!("Received non-IPv4 message. Ignoring. id={}, src={}, msg={}",
-                                                       addr, src_addr, format!("{:?}", msg));
-                                            continue;
-                                        }
-                                    },
-                                    Err(e) => {
-                                        log::debug!("Unable to parse message. Ignoring. id={}, src={}, buf={:?}, err={:?}",
-                                                   addr, src_addr, &in_buf[..count], e);
-                                        continue;
-                                    }
-                                }
-                            },
+/// A means for a spawned actor to send serialized messages to its peers. Received messages are
+/// forwarded to the actor's inbox by background threads, which stop when the transport is
+/// dropped.
+trait Transport {
+    /// Sends a serialized message to `dst`.
+    fn send(&mut self, dst: Id, buf: &[u8]) -> std::io::Result<()>;
+}
+
+/// How often background threads check whether their transport has been dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct UdpTransport {
+    socket: UdpSocket,
+    addresses: AddressBook,
+    stopped: Arc<AtomicBool>,
+    reader: Option<std::thread::JoinHandle<()>>,
+}
+
+impl UdpTransport {
+    /// Reads datagrams on a background thread.
+    fn bind<A>(
+        id: Id,
+        addresses: AddressBook,
+        inbox: mpsc::Sender<Input<A>>,
+    ) -> std::io::Result<Self>
+    where
+        A: 'static + Actor,
+        A::Msg: Send,
+        A::State: Send,
+    {
+        let addr = addresses.addr(id);
+        let socket = UdpSocket::bind(addr)?;
+        let reader_socket = socket.try_clone()?;
+        reader_socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let reader_addresses = addresses.clone();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let reader_stopped = Arc::clone(&stopped);
+        let reader = std::thread::spawn(move || {
+            let mut in_buf = [0; 65_535];
+            while !reader_stopped.load(Ordering::Relaxed) {
+                let (count, src_addr) = match reader_socket.recv_from(&mut in_buf) {
+                    Ok(received) => received,
+                    Err(e) => {
+                        // Timeouts ignored since they only serve to check whether to stop.
+                        if !matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+                            log::warn!("Unable to read socket. Ignoring. id={}, err={:?}", addr, e);
                         }
-                    } else {
-                        let min_timer = min_timer.unwrap();
-                        next_interrupts.remove(&min_timer); // timer is no longer valid
-                        actor.on_timeout(id, &mut state, &min_timer, &mut out);
+                        continue;
+                    }
+                };
+                match reader_addresses.id(src_addr) {
+                    Some(src) => {
+                        if inbox
+                            .send(Input::Recv(src, in_buf[..count].to_vec()))
+                            .is_err()
+                        {
+                            return; // actor stopped
+                        }
+                    }
+                    None => {
+                        log::debug!(
+                            "Received message from unknown source. Ignoring. id={}, src={}, buf={:?}",
+                            addr,
+                            src_addr,
+                            &in_buf[..count]
+                        );
                     }
+                }
+            }
+        });
+        Ok(UdpTransport {
+            socket,
+            addresses,
+            stopped,
+            reader: Some(reader),
+        })
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, dst: Id, buf: &[u8]) -> std::io::Result<()> {
+        self.socket
+            .send_to(buf, self.addresses.addr(dst))
+            .map(|_| ())
+    }
+}
+
+impl Drop for UdpTransport {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+struct TcpTransport {
+    id: Id,
+    addresses: AddressBook,
+    outboxes: HashMap<Id, mpsc::Sender<Vec<u8>>>,
+    writers: Vec<std::thread::JoinHandle<()>>,
+    stopped: Arc<AtomicBool>,
+    accepted: Arc<Mutex<HashMap<u64, TcpStream>>>,
+    acceptor: Option<std::thread::JoinHandle<()>>,
+}
+
+impl TcpTransport {
+    /// Listens for inbound connections on a background thread.
+    fn bind<A>(
+        id: Id,
+        addresses: AddressBook,
+        inbox: mpsc::Sender<Input<A>>,
+    ) -> std::io::Result<Self>
+    where
+        A: 'static + Actor,
+        A::Msg: Send,
+        A::State: Send,
+    {
+        let addr = addresses.addr(id);
+        let listener = TcpListener::bind(addr)?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let accepted = Arc::new(Mutex::new(HashMap::new()));
+        let acceptor = {
+            let addresses = addresses.clone();
+            let stopped = Arc::clone(&stopped);
+            let accepted = Arc::clone(&accepted);
+            std::thread::spawn(move || {
+                accept_tcp(addr, addresses, listener, inbox, stopped, accepted)
+            })
+        };
+        Ok(TcpTransport {
+            id,
+            addresses,
+            outboxes: HashMap::new(),
+            writers: Vec::new(),
+            stopped,
+            accepted,
+            acceptor: Some(acceptor),
+        })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, dst: Id, buf: &[u8]) -> std::io::Result<()> {
+        // Each peer has a writer thread, so that the event loop never waits to connect.
+        let outbox = match self.outboxes.entry(dst) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (outbox, queue) = mpsc::channel();
+                let (id, dst_addr) = (self.id, self.addresses.addr(dst));
+                self.writers
+                    .push(std::thread::spawn(move || write_tcp(id, dst_addr, queue)));
+                entry.insert(outbox)
+            }
+        };
+        outbox
+            .send(buf.to_vec())
+            .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, "writer stopped"))
+    }
+}
+
+impl Drop for TcpTransport {
+    fn drop(&mut self) {
+        // Wake the acceptor so that it notices the transport was dropped, and close inbound
+        // connections so that their readers stop. Writers stop once their outboxes are closed.
+        self.stopped.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect_timeout(&self.addresses.addr(self.id), TCP_TIMEOUT);
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+        for (_, stream) in self.accepted.lock().unwrap().drain() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        self.outboxes.clear();
+        for writer in self.writers.drain(..) {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// How long to wait when connecting to or writing to a peer over TCP.
+const TCP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long to drop messages to a peer after failing to connect to it, rather than queueing them
+/// behind further connection attempts.
+const TCP_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The largest message that can be sent or received over TCP. Longer frames are rejected before
+/// they are buffered, so a peer cannot exhaust the receiver's memory.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Writes messages to a peer, keeping one connection that is established on demand and
+/// reestablished if the peer closes it.
+fn write_tcp(id: Id, dst_addr: SocketAddr, queue: mpsc::Receiver<Vec<u8>>) {
+    let mut stream: Option<TcpStream> = None;
+    let mut retry_at = Instant::now();
+    for buf in queue {
+        // Reuse the connection, reconnecting once if it has been closed.
+        if let Some(connection) = &mut stream {
+            match write_frame(connection, &buf) {
+                Ok(()) => continue,
+                Err(e) => {
+                    log::debug!(
+                        "Connection lost. Reconnecting. dst={}, err={:?}",
+                        dst_addr,
+                        e
+                    );
+                    stream = None;
+                }
+            }
+        }
+        if Instant::now() < retry_at {
+            log::debug!(
+                "Peer unreachable. Ignoring. dst={}, buf={:?}",
+                dst_addr,
+                buf
+            );
+            continue;
+        }
+        let connection = TcpStream::connect_timeout(&dst_addr, TCP_TIMEOUT).and_then(|mut s| {
+            s.set_write_timeout(Some(TCP_TIMEOUT))?;
+            s.set_nodelay(true)?;
+            write_frame(&mut s, &id.0.to_be_bytes())?; // identifies the sender
+            write_frame(&mut s, &buf)?;
+            Ok(s)
+        });
+        match connection {
+            Ok(connection) => stream = Some(connection),
+            Err(e) => {
+                log::warn!(
+                    "Unable to send. Ignoring. dst={}, buf={:?}, err={:?}",
+                    dst_addr,
+                    buf,
+                    e
+                );
+                retry_at = Instant::now() + TCP_RECONNECT_BACKOFF;
+            }
+        }
+    }
+}
+
+/// Accepts inbound TCP connections, forwarding their messages to the actor's inbox.
+fn accept_tcp<A>(
+    addr: SocketAddr,
+    addresses: AddressBook,
+    listener: TcpListener,
+    inbox: mpsc::Sender<Input<A>>,
+    stopped: Arc<AtomicBool>,
+    accepted: Arc<Mutex<HashMap<u64, TcpStream>>>,
+) where
+    A: 'static + Actor,
+    A::Msg: Send,
+    A::State: Send,
+{
+    // Connections are tracked until their readers stop, so that they can be closed on drop.
+    let mut next_connection_id = 0;
+    for stream in listener.incoming() {
+        if stopped.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!(
+                    "Unable to accept connection. Ignoring. id={}, err={:?}",
+                    addr,
+                    e
+                );
+                continue;
+            }
+        };
+        match stream.try_clone() {
+            Ok(clone) => {
+                accepted.lock().unwrap().insert(next_connection_id, clone);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Unable to track connection. Ignoring. id={}, err={:?}",
+                    addr,
+                    e
+                );
+                continue;
+            }
+        }
+        let connection_id = next_connection_id;
+        next_connection_id += 1;
+        let inbox = inbox.clone();
+        let addresses = addresses.clone();
+        let accepted = Arc::clone(&accepted);
+        std::thread::spawn(move || {
+            read_tcp(addr, &addresses, &mut stream, &inbox);
+            accepted.lock().unwrap().remove(&connection_id);
+        });
+    }
+}
+
+/// Forwards messages from an inbound TCP connection to the actor's inbox until either closes.
+fn read_tcp<A>(
+    addr: SocketAddr,
+    addresses: &AddressBook,
+    stream: &mut TcpStream,
+    inbox: &mpsc::Sender<Input<A>>,
+) where
+    A: Actor,
+{
+    // The first frame identifies the sender, as its port is ephemeral.
+    let src = match read_frame(stream).map(|buf| <[u8; 8]>::try_from(buf.as_slice())) {
+        Ok(Ok(bytes)) => Id(u64::from_be_bytes(bytes)),
+        result => {
+            log::debug!(
+                "Unable to identify peer. Ignoring. id={}, result={:?}",
+                addr,
+                result
+            );
+            return;
+        }
+    };
+    // The sender identifies itself, so at least check that it connected from the host
+    // at which the claimed actor resides.
+    let peer_addr = match stream.peer_addr() {
+        Ok(peer_addr) => peer_addr,
+        Err(e) => {
+            log::debug!(
+                "Unable to identify peer. Ignoring. id={}, err={:?}",
+                addr,
+                e
+            );
+            return;
+        }
+    };
+    if peer_addr.ip().to_canonical() != addresses.addr(src).ip().to_canonical() {
+        log::warn!(
+            "Peer address does not match claimed actor. Ignoring. id={}, src={}, peer={}",
+            addr,
+            addresses.addr(src),
+            peer_addr
+        );
+        let _ = stream.shutdown(Shutdown::Both);
+        return;
+    }
+    loop {
+        match read_frame(stream) {
+            Ok(buf) => {
+                if inbox.send(Input::Recv(src, buf)).is_err() {
+                    return; // actor stopped
+                }
+            }
+            Err(e) => {
+                log::debug!("Connection closed. id={}, src={:?}, err={:?}", addr, src, e);
+                return;
+            }
+        }
+    }
+}
+
+/// Writes a message prefixed by its length.
+fn write_frame(stream: &mut impl Write, buf: &[u8]) -> std::io::Result<()> {
+    if buf.len() > MAX_FRAME_LEN {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "message too large",
+        ));
+    }
+    stream.write_all(&(buf.len() as u32).to_be_bytes())?;
+    stream.write_all(buf)?;
+    stream.flush()
+}
 
-                    // Handle commands and update state.
-                    if !is_no_op(&state, &out) {
-                        log::debug!("Acted. id={}, state={:?}, out={:?}",
-                                    addr, state, out);
+/// Reads a message written by [`write_frame`], rejecting frames longer than [`MAX_FRAME_LEN`].
+fn read_frame(stream: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("frame too large: {} bytes", len),
+        ));
+    }
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Runs an actor's event loop until it is stopped, recovering from durable state if any.
+#[allow(clippy::too_many_arguments)]
+fn run<A, E, T>(
+    id: Id,
+    actor: A,
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    addresses: &AddressBook,
+    storage_path: Option<PathBuf>,
+    storage: Option<A::Storage>,
+    mut transport: T,
+    inbox: mpsc::Receiver<Input<A>>,
+) where
+    A: Actor,
+    A::Msg: Debug,
+    A::State: Debug,
+    A::Storage: serde::Serialize,
+    E: Debug,
+    T: Transport,
+{
+    let addr = addresses.addr(id);
+    let mut next_interrupts = HashMap::new();
+
+    let mut out = Out::with_clock(clock());
+    let mut state: Cow<A::State> = match storage {
+        Some(storage) => {
+            let state = Cow::Owned(actor.on_recover(id, Some(&storage), &mut out));
+            log::info!(
+                "Actor recovered. id={}, state={:?}, out={:?}",
+                addr,
+                state,
+                out
+            );
+            state
+        }
+        None => {
+            let state = Cow::Owned(actor.on_start(id, &mut out));
+            log::info!(
+                "Actor started. id={}, state={:?}, out={:?}",
+                addr,
+                state,
+                out
+            );
+            state
+        }
+    };
+    for c in out {
+        on_command::<A, E>(
+            addresses,
+            id,
+            c,
+            serialize,
+            &mut |dst, buf| transport.send(dst, buf),
+            storage_path.as_deref(),
+            &mut next_interrupts,
+        );
+    }
+
+    loop {
+        // Apply an interrupt if present, otherwise wait for an input.
+        let mut out = Out::with_clock(clock());
+        let (min_timer, min_instant) = next_interrupts
+            .iter()
+            .min_by_key(|(_, instant)| *instant)
+            .map(|(t, i)| (Some(t.clone()), *i))
+            .unwrap_or_else(|| (None, practically_never()));
+        if let Some(max_wait) = min_instant.checked_duration_since(Instant::now()) {
+            let (src, msg) = match inbox.recv_timeout(max_wait) {
+                // Timeout ignored since next iteration will apply interrupt.
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) | Ok(Input::Stop) => {
+                    log::info!("Actor stopped. id={}, state={:?}", addr, state);
+                    return;
+                }
+                Ok(Input::State(reply)) => {
+                    let _ = reply.send(state.clone().into_owned());
+                    continue;
+                }
+                Ok(Input::Inject(src, msg)) => (src, msg),
+                Ok(Input::Recv(src, buf)) => {
+                    match deserialize(&buf) {
+                        Ok(msg) => (src, msg),
+                        Err(e) => {
+                            log::debug!("Unable to parse message. Ignoring. id={}, src={}, buf={:?}, err={:?}",
+                                   addr, addresses.addr(src), buf, e);
+                            continue;
+                        }
                     }
-                    for c in out { on_command::<A, E>(addr, c, serialize, &socket, &mut next_interrupts); }
                 }
-            });
+            };
+            log::info!(
+                "Received message. id={}, src={}, msg={:?}",
+                addr,
+                addresses.addr(src),
+                msg
+            );
+            actor.on_msg(id, &mut state, src, msg, &mut out);
+        } else {
+            let min_timer = min_timer.unwrap();
+            next_interrupts.remove(&min_timer); // timer is no longer valid
+            actor.on_timeout(id, &mut state, &min_timer, &mut out);
         }
-    })
+
+        // Handle commands and update state.
+        if !is_no_op(&state, &out) {
+            log::debug!("Acted. id={}, state={:?}, out={:?}", addr, state, out);
+        }
+        for c in out {
+            on_command::<A, E>(
+                addresses,
+                id,
+                c,
+                serialize,
+                &mut |dst, buf| transport.send(dst, buf),
+                storage_path.as_deref(),
+                &mut next_interrupts,
+            );
+        }
+    }
 }
 
 /// The effect to perform in response to spawned actor outputs.
-fn on_command<A, E>(
-    addr: SocketAddrV4,
-    command: Command<A::Msg, A::Timer>,
+pub(super) fn on_command<A, E>(
+    addresses: &AddressBook,
+    id: Id,
+    command: Command<A::Msg, A::Timer, A::Storage>,
     serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
-    socket: &UdpSocket,
+    send: &mut impl FnMut(Id, &[u8]) -> std::io::Result<()>,
+    storage_path: Option<&Path>,
     next_interrupts: &mut HashMap<A::Timer, Instant>,
 ) where
     A: Actor,
     A::Msg: Debug,
+    A::Storage: serde::Serialize,
     E: Debug,
 {
+    let addr = addresses.addr(id);
     match command {
         Command::Send(dst, msg) => {
-            let dst_addr = SocketAddrV4::from(dst);
+            let dst_addr = addresses.addr(dst);
             match serialize(&msg) {
                 Err(e) => {
                     log::warn!(
@@ -172,7 +1135,7 @@ fn on_command<A, E>(
                     );
                 }
                 Ok(out_buf) => {
-                    if let Err(e) = socket.send_to(&out_buf, dst_addr) {
+                    if let Err(e) = send(dst, &out_buf) {
                         log::warn!(
                             "Unable to send. Ignoring. src={}, dst={}, msg={:?}, err={:?}",
                             addr,
@@ -202,13 +1165,43 @@ fn on_command<A, E>(
                 .entry(timer)
                 .and_modify(|d| *d = practically_never());
         }
+        Command::Persist(storage) => {
+            let path = match storage_path {
+                Some(path) => path,
+                None => {
+                    log::debug!(
+                        "No storage directory. Ignoring persisted state. id={}, storage={:?}",
+                        addr,
+                        storage
+                    );
+                    return;
+                }
+            };
+            let result = serde_json::to_vec(&storage)
+                .map_err(std::io::Error::from)
+                .and_then(|bytes| write_storage(path, &bytes));
+            if let Err(e) = result {
+                log::warn!(
+                    "Unable to persist. Ignoring. id={}, storage={:?}, err={:?}",
+                    addr,
+                    storage,
+                    e
+                );
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::{read_frame, write_frame, Input, TcpTransport, Transport, MAX_FRAME_LEN};
     use crate::actor::*;
-    use std::net::{Ipv4Addr, SocketAddrV4};
+    use std::borrow::Cow;
+    use std::net::{
+        Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream, UdpSocket,
+    };
+    use std::sync::mpsc;
+    use std::time::Duration;
 
     #[test]
     fn can_encode_id() {
@@ -221,4 +1214,278 @@ mod test {
         let addr = SocketAddrV4::new(Ipv4Addr::new(1, 2, 3, 4), 5);
         assert_eq!(SocketAddrV4::from(Id::from(addr)), addr);
     }
+
+    #[test]
+    fn can_look_up_addresses() {
+        let ipv6 = SocketAddr::from((Ipv6Addr::LOCALHOST, 3000));
+        let ipv4 = SocketAddr::from((Ipv4Addr::LOCALHOST, 3001));
+        let addresses = AddressBook::new()
+            .address(Id::from(0), ipv6)
+            .resolve(Id::from(1), "127.0.0.1:3001")
+            .unwrap();
+        assert_eq!(addresses.addr(Id::from(0)), ipv6);
+        assert_eq!(addresses.addr(Id::from(1)), ipv4);
+        assert_eq!(addresses.id(ipv6), Some(Id::from(0)));
+        assert_eq!(addresses.id(ipv4), Some(Id::from(1)));
+
+        // Dual-stack sockets report IPv4 peers via IPv4-mapped IPv6 addresses.
+        let mapped = SocketAddr::from((Ipv4Addr::LOCALHOST.to_ipv6_mapped(), 3001));
+        assert_eq!(addresses.id(mapped), Some(Id::from(1)));
+
+        // Otherwise IPv4 addresses are encoded by `Id`s.
+        let encoded = SocketAddrV4::new(Ipv4Addr::new(1, 2, 3, 4), 5);
+        assert_eq!(addresses.addr(Id::from(encoded)), SocketAddr::V4(encoded));
+        assert_eq!(
+            addresses.id(SocketAddr::V4(encoded)),
+            Some(Id::from(encoded))
+        );
+        assert_eq!(
+            addresses.id(SocketAddr::from((Ipv6Addr::LOCALHOST, 3001))),
+            None
+        );
+
+        // Reassigning an actor's address forgets the previous one.
+        let addresses = addresses.address(Id::from(0), (Ipv6Addr::LOCALHOST, 3002));
+        assert_eq!(addresses.id(ipv6), None);
+    }
+
+    #[test]
+    fn can_frame_messages() {
+        let mut stream = Vec::new();
+        write_frame(&mut stream, b"hello").unwrap();
+        write_frame(&mut stream, b"").unwrap();
+        assert_eq!(&stream[..9], [0, 0, 0, 5, b'h', b'e', b'l', b'l', b'o']);
+
+        let mut stream = stream.as_slice();
+        assert_eq!(read_frame(&mut stream).unwrap(), b"hello");
+        assert_eq!(read_frame(&mut stream).unwrap(), b"");
+        assert!(read_frame(&mut stream).is_err());
+
+        // Lengths are checked before buffering.
+        let mut stream: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+        let e = read_frame(&mut stream).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        let mut stream = Vec::new();
+        assert!(write_frame(&mut stream, &vec![0; MAX_FRAME_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn can_exchange_large_messages_over_tcp() {
+        // Reserve ephemeral ports, then release them for the transports.
+        let unused_addr = || {
+            TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .unwrap()
+                .local_addr()
+                .unwrap()
+        };
+        let (id1, id2) = (Id::from(0), Id::from(1));
+        let addresses = AddressBook::new()
+            .address(id1, unused_addr())
+            .address(id2, unused_addr());
+        let (inbox_sender1, inbox1) = mpsc::channel::<Input<()>>();
+        let (inbox_sender2, inbox2) = mpsc::channel::<Input<()>>();
+        let mut transport1 = TcpTransport::bind(id1, addresses.clone(), inbox_sender1).unwrap();
+        let mut transport2 = TcpTransport::bind(id2, addresses, inbox_sender2).unwrap();
+
+        let large = vec![7; 100_000]; // exceeds a UDP datagram
+        transport1.send(id2, &large).unwrap();
+        transport1.send(id2, b"small").unwrap();
+        transport2.send(id1, b"reply").unwrap();
+
+        let recv =
+            |inbox: &mpsc::Receiver<Input<()>>| match inbox.recv_timeout(Duration::from_secs(5)) {
+                Ok(Input::Recv(src, buf)) => (src, buf),
+                _ => panic!("expected a message"),
+            };
+        assert_eq!(recv(&inbox2), (id1, large));
+        assert_eq!(recv(&inbox2), (id1, b"small".to_vec()));
+        assert_eq!(recv(&inbox1), (id2, b"reply".to_vec()));
+    }
+
+    #[test]
+    fn rejects_tcp_peers_that_claim_another_host() {
+        let addr = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let id = Id::from(0);
+        let (inbox_sender, inbox) = mpsc::channel::<Input<()>>();
+        let _transport =
+            TcpTransport::bind(id, AddressBook::new().address(id, addr), inbox_sender).unwrap();
+        let send_as = |src: Id, buf: &[u8]| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write_frame(&mut stream, &src.0.to_be_bytes()).unwrap();
+            write_frame(&mut stream, buf).unwrap();
+        };
+
+        // Connections are from 127.0.0.1, which does not host the first actor.
+        let elsewhere = Id::from(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 2), 3000));
+        let local = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3000));
+        send_as(elsewhere, b"forged");
+        send_as(local, b"genuine");
+        match inbox.recv_timeout(Duration::from_secs(5)) {
+            Ok(Input::Recv(src, buf)) => assert_eq!((src, buf), (local, b"genuine".to_vec())),
+            _ => panic!("expected a message"),
+        }
+        assert!(inbox.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn forgets_tcp_connections_once_closed() {
+        let addr = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let id = Id::from(0);
+        let (inbox_sender, inbox) = mpsc::channel::<Input<()>>();
+        let transport =
+            TcpTransport::bind(id, AddressBook::new().address(id, addr), inbox_sender).unwrap();
+        for _ in 0..3 {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write_frame(&mut stream, &id.0.to_be_bytes()).unwrap();
+            write_frame(&mut stream, b"hello").unwrap();
+            assert!(inbox.recv_timeout(Duration::from_secs(5)).is_ok());
+        }
+
+        // Readers stop once their peers disconnect, after which their connections are dropped.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !transport.accepted.lock().unwrap().is_empty() {
+            assert!(std::time::Instant::now() < deadline, "connections leaked");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn can_inspect_message_and_stop_spawned_actors() {
+        // Counts the messages it receives.
+        struct CountingActor;
+        impl Actor for CountingActor {
+            type State = usize;
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, _: Id, _: &mut Out<Self>) -> Self::State {
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                *state.to_mut() += 1;
+            }
+        }
+        let unused_addr = || {
+            UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+                .unwrap()
+                .local_addr()
+                .unwrap()
+        };
+        let (id1, id2) = (Id::from(0), Id::from(1));
+        let (addr1, addr2) = (unused_addr(), unused_addr());
+        let addresses = AddressBook::new().address(id1, addr1).address(id2, addr2);
+        let mut handle = Spawner::new()
+            .addresses(addresses)
+            .spawn(
+                |_| Ok::<_, ()>(Vec::new()),
+                |_| Ok(()),
+                vec![(id1, CountingActor), (id2, CountingActor)],
+            )
+            .unwrap();
+        assert_eq!(handle.ids(), vec![id1, id2]);
+        assert_eq!(handle.state(id1), Some(0));
+
+        // Injected messages bypass the network.
+        assert!(handle.send(id2, id1, ()));
+        assert!(handle.send(id2, id1, ()));
+        let max_wait = Duration::from_secs(5);
+        assert_eq!(handle.wait_for_state(id1, max_wait, |n| *n == 2), Some(2));
+
+        // Stopped actors release their sockets and ignore further requests.
+        handle.stop(id1).unwrap();
+        assert_eq!(handle.ids(), vec![id2]);
+        assert_eq!(handle.state(id1), None);
+        assert!(!handle.send(id2, id1, ()));
+        UdpSocket::bind(addr1).unwrap();
+
+        assert_eq!(handle.state(id2), Some(0));
+        handle.stop_all().unwrap();
+        UdpSocket::bind(addr2).unwrap();
+    }
+
+    #[test]
+    fn recovers_persisted_state_only_from_storage_dir() {
+        // Persists the number of messages it receives.
+        struct DurableActor;
+        impl Actor for DurableActor {
+            type State = u8;
+            type Msg = ();
+            type Timer = ();
+            type Storage = u8;
+            fn on_start(&self, _: Id, _: &mut Out<Self>) -> Self::State {
+                0
+            }
+            fn on_msg(
+                &self,
+                _: Id,
+                state: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                o: &mut Out<Self>,
+            ) {
+                *state.to_mut() += 1;
+                o.persist(**state);
+            }
+            fn on_recover(
+                &self,
+                _: Id,
+                storage: Option<&Self::Storage>,
+                _: &mut Out<Self>,
+            ) -> Self::State {
+                storage.copied().unwrap_or(0)
+            }
+        }
+        let dir = std::env::temp_dir().join(format!("stateright-spawn-{}", std::process::id()));
+        let port = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port));
+        let spawn = |spawner: Spawner| {
+            spawner
+                .spawn(
+                    |_| Ok::<_, ()>(Vec::new()),
+                    |_| Ok(()),
+                    vec![(id, DurableActor)],
+                )
+                .unwrap()
+        };
+        let max_wait = Duration::from_secs(5);
+
+        // Nothing is written without a storage directory.
+        let handle = spawn(Spawner::new());
+        assert!(handle.send(id, id, ()));
+        assert_eq!(handle.wait_for_state(id, max_wait, |n| *n == 1), Some(1));
+        handle.stop_all().unwrap();
+        assert!(!dir.exists());
+        let handle = spawn(Spawner::new());
+        assert_eq!(handle.state(id), Some(0));
+        handle.stop_all().unwrap();
+
+        // Otherwise the actor recovers what it last persisted.
+        let handle = spawn(Spawner::new().storage_dir(&dir));
+        assert!(handle.send(id, id, ()));
+        assert!(handle.send(id, id, ()));
+        assert_eq!(handle.wait_for_state(id, max_wait, |n| *n == 2), Some(2));
+        handle.stop_all().unwrap();
+        let handle = spawn(Spawner::new().storage_dir(&dir));
+        assert_eq!(handle.state(id), Some(2));
+        handle.stop_all().unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,393 @@
+//! Private module for selective re-export.
+
+use crate::actor::spawn::{clock, on_command, read_storage, storage_path, write_storage};
+use crate::actor::*;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Runs each actor as a task on the current [Tokio](https://tokio.rs) runtime, sending messages
+/// over UDP. Requires the `tokio` feature.
+///
+/// Behaves like [`spawn`], but rather than dedicating a thread to each actor, the actors share
+/// the runtime's worker threads, so a process can host many actors and can embed them in an
+/// existing service. Must be called from within a runtime, as with [`tokio::spawn`]. A task only
+/// completes if its actor is unable to bind its socket. See [`Spawner::addresses`] for addresses
+/// that are not encoded by [`Id`]s.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, spawn_async};
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # async fn example() {
+/// # let actor1 = ();
+/// # let actor2 = ();
+/// let id1 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let id2 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+/// let tasks = spawn_async(
+///     serde_json::to_vec,
+///     |bytes| serde_json::from_slice(bytes),
+///     vec![
+///         (id1, actor1),
+///         (id2, actor2),
+///     ]);
+/// for task in tasks {
+///     task.await.unwrap().unwrap();
+/// }
+/// # }
+/// ```
+pub fn spawn_async<A, E: Debug + 'static>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>,
+) -> Vec<JoinHandle<std::io::Result<()>>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    A::Timer: Send,
+    A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+{
+    Spawner::new().spawn_async(serialize, deserialize, actors)
+}
+
+impl Spawner {
+    /// Runs each actor as a task on the current [Tokio](https://tokio.rs) runtime, sending
+    /// messages over UDP. Requires the `tokio` feature. See [`spawn_async`].
+    pub fn spawn_async<A, E: Debug + 'static>(
+        self,
+        serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+        deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+        actors: Vec<(impl Into<Id>, A)>,
+    ) -> Vec<JoinHandle<std::io::Result<()>>>
+    where
+        A: 'static + Send + Actor,
+        A::Msg: Debug + Send,
+        A::State: Debug + Send,
+        A::Timer: Send,
+        A::Storage: Send + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        actors
+            .into_iter()
+            .map(|(id, actor)| {
+                tokio::spawn(run_async(
+                    id.into(),
+                    actor,
+                    serialize,
+                    deserialize,
+                    self.addresses.clone(),
+                    self.storage_dir.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Runs an actor's event loop, yielding to the runtime while waiting.
+async fn run_async<A, E>(
+    id: Id,
+    actor: A,
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    addresses: AddressBook,
+    storage_dir: Option<PathBuf>,
+) -> std::io::Result<()>
+where
+    A: Actor,
+    A::Msg: Debug,
+    A::State: Debug,
+    A::Storage: serde::Serialize + serde::de::DeserializeOwned,
+    E: Debug,
+{
+    let addr = addresses.addr(id);
+    let socket = UdpSocket::bind(addr).await?;
+    let mut in_buf = vec![0; 65_535];
+    let mut next_interrupts = HashMap::new();
+
+    // Sends do not wait, so a message is dropped if the socket's buffer is full.
+    let mut send = |dst: Id, buf: &[u8]| socket.try_send_to(buf, addresses.addr(dst)).map(|_| ());
+
+    // File system access blocks, so it is performed on a blocking thread.
+    let storage_path = storage_dir.map(|dir| storage_path(&dir, addr));
+    let storage = match storage_path.clone() {
+        Some(path) => tokio::task::spawn_blocking(move || {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            read_storage(&path)
+        })
+        .await
+        .map_err(std::io::Error::other)??,
+        None => None,
+    };
+    let storage: Option<A::Storage> = match storage {
+        Some(bytes) => Some(serde_json::from_slice(&bytes)?),
+        None => None,
+    };
+
+    let mut out = Out::with_clock(clock());
+    let mut state = match storage {
+        Some(storage) => {
+            let state = actor.on_recover(id, Some(&storage), &mut out);
+            log::info!(
+                "Actor recovered. id={}, state={:?}, out={:?}",
+                addr,
+                state,
+                out
+            );
+            state
+        }
+        None => {
+            let state = actor.on_start(id, &mut out);
+            log::info!(
+                "Actor started. id={}, state={:?}, out={:?}",
+                addr,
+                state,
+                out
+            );
+            state
+        }
+    };
+    on_commands(
+        &addresses,
+        id,
+        out,
+        serialize,
+        &mut send,
+        storage_path.as_deref(),
+        &mut next_interrupts,
+    )
+    .await;
+
+    loop {
+        // Apply an interrupt if one elapses before a message arrives.
+        let min_interrupt = next_interrupts
+            .iter()
+            .min_by_key(|(_, instant)| *instant)
+            .map(|(t, i)| (t.clone(), *i));
+        let event = match min_interrupt {
+            None => Ok(socket.recv_from(&mut in_buf).await),
+            Some((timer, instant)) => {
+                tokio::time::timeout_at(instant.into(), socket.recv_from(&mut in_buf))
+                    .await
+                    .map_err(|_| timer)
+            }
+        };
+
+        // Scoped so that the borrowed state is not held across `await`s.
+        let out = {
+            let mut out = Out::with_clock(clock());
+            let mut next_state = Cow::Borrowed(&state);
+            match event {
+                Ok(Ok((count, src_addr))) => {
+                    let src = match addresses.id(src_addr) {
+                        Some(src) => src,
+                        None => {
+                            log::debug!(
+                                "Received message from unknown source. Ignoring. id={}, src={}, buf={:?}",
+                                addr,
+                                src_addr,
+                                &in_buf[..count]
+                            );
+                            continue;
+                        }
+                    };
+                    match deserialize(&in_buf[..count]) {
+                        Ok(msg) => {
+                            log::info!(
+                                "Received message. id={}, src={}, msg={:?}",
+                                addr,
+                                src_addr,
+                                msg
+                            );
+                            actor.on_msg(id, &mut next_state, src, msg, &mut out);
+                        }
+                        Err(e) => {
+                            log::debug!(
+                                "Unable to parse message. Ignoring. id={}, src={}, buf={:?}, err={:?}",
+                                addr,
+                                src_addr,
+                                &in_buf[..count],
+                                e
+                            );
+                            continue;
+                        }
+                    }
+                }
+                Ok(Err(e)) => {
+                    log::warn!("Unable to read socket. Ignoring. id={}, err={:?}", addr, e);
+                    continue;
+                }
+                Err(timer) => {
+                    next_interrupts.remove(&timer); // timer is no longer valid
+                    actor.on_timeout(id, &mut next_state, &timer, &mut out);
+                }
+            }
+
+            // Handle commands and update state.
+            if !is_no_op(&next_state, &out) {
+                log::debug!("Acted. id={}, state={:?}, out={:?}", addr, next_state, out);
+            }
+            if let Cow::Owned(next_state) = next_state {
+                state = next_state;
+            }
+            out
+        };
+        on_commands(
+            &addresses,
+            id,
+            out,
+            serialize,
+            &mut send,
+            storage_path.as_deref(),
+            &mut next_interrupts,
+        )
+        .await;
+    }
+}
+
+/// The effect to perform in response to actor outputs. Durable state is written on a blocking
+/// thread, so that the runtime's workers are not blocked while the write is synced to disk, but
+/// the commands that follow still wait for it.
+async fn on_commands<A, E>(
+    addresses: &AddressBook,
+    id: Id,
+    out: Out<A>,
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    send: &mut impl FnMut(Id, &[u8]) -> std::io::Result<()>,
+    storage_path: Option<&Path>,
+    next_interrupts: &mut HashMap<A::Timer, Instant>,
+) where
+    A: Actor,
+    A::Msg: Debug,
+    A::Storage: serde::Serialize,
+    E: Debug,
+{
+    for c in out {
+        match (c, storage_path) {
+            (Command::Persist(storage), Some(path)) => {
+                let path = path.to_path_buf();
+                let result = match serde_json::to_vec(&storage) {
+                    Ok(bytes) => tokio::task::spawn_blocking(move || write_storage(&path, &bytes))
+                        .await
+                        .map_err(std::io::Error::other)
+                        .and_then(|r| r),
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(e) = result {
+                    log::warn!(
+                        "Unable to persist. Ignoring. id={}, err={:?}",
+                        addresses.addr(id),
+                        e
+                    );
+                }
+            }
+            (c, _) => on_command::<A, E>(
+                addresses,
+                id,
+                c,
+                serialize,
+                send,
+                storage_path,
+                next_interrupts,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::actor::*;
+    use std::borrow::Cow;
+    use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn can_fire_timers_and_exchange_messages() {
+        // Each actor reports its events. The first actor messages the second once its timer fires.
+        struct TestActor {
+            peer: Option<Id>,
+            events: mpsc::Sender<(Id, &'static str)>,
+        }
+        impl Actor for TestActor {
+            type State = ();
+            type Msg = ();
+            type Timer = ();
+            type Storage = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer((), Duration::from_millis(10)..Duration::from_millis(10));
+            }
+            fn on_msg(
+                &self,
+                id: Id,
+                _: &mut Cow<Self::State>,
+                _: Id,
+                _: Self::Msg,
+                _: &mut Out<Self>,
+            ) {
+                self.events.send((id, "msg")).unwrap();
+            }
+            fn on_timeout(
+                &self,
+                id: Id,
+                _: &mut Cow<Self::State>,
+                _: &Self::Timer,
+                o: &mut Out<Self>,
+            ) {
+                self.events.send((id, "timeout")).unwrap();
+                if let Some(peer) = self.peer {
+                    o.send(peer, ());
+                }
+            }
+        }
+        let unused_id = || {
+            let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            let port = socket.local_addr().unwrap().port();
+            Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port))
+        };
+        let (id1, id2) = (unused_id(), unused_id());
+        let (events, receiver) = mpsc::channel();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let _guard = runtime.enter();
+        spawn_async(
+            |_| Ok::<_, ()>(Vec::new()),
+            |_| Ok(()),
+            vec![
+                (
+                    id1,
+                    TestActor {
+                        peer: Some(id2),
+                        events: events.clone(),
+                    },
+                ),
+                (id2, TestActor { peer: None, events }),
+            ],
+        );
+        // Waits on a blocking thread while the runtime drives the actors.
+        let mut events = runtime
+            .block_on(tokio::task::spawn_blocking(move || {
+                let max_wait = Duration::from_secs(5);
+                (0..3)
+                    .map(|_| receiver.recv_timeout(max_wait).expect("expected an event"))
+                    .collect::<Vec<_>>()
+            }))
+            .unwrap();
+        events.sort();
+        let mut expected = vec![(id1, "timeout"), (id2, "timeout"), (id2, "msg")];
+        expected.sort();
+        assert_eq!(events, expected);
+    }
+}
@@ -1,11 +1,23 @@
-use crate::{util::HashableHashSet, Rewrite, RewritePlan};
+use crate::{util::HashableHashMap, Rewrite, RewritePlan};
+use std::fmt::Debug;
 use std::hash::Hash;
+use std::time::Duration;
 
 use super::Id;
 
 /// A collection of timers that have been set for a given actor.
-#[derive(Clone, Debug, Hash, PartialEq, Eq, serde::Serialize)]
-pub struct Timers<T: Hash + Eq>(HashableHashSet<T>);
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct Timers<T: Hash + Eq>(HashableHashMap<T, Deadline>);
+
+/// Indicates when a timer can fire, relative to the start of the system. Only relevant if
+/// [`ActorModel::discrete_time`] is enabled.
+///
+/// [`ActorModel::discrete_time`]: crate::actor::ActorModel::discrete_time
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize)]
+pub struct Deadline {
+    pub earliest: Duration,
+    pub latest: Duration,
+}
 
 impl<T: Hash + Eq> Default for Timers<T> {
     fn default() -> Self {
@@ -19,17 +31,22 @@ where
 {
     /// Create a new timer set.
     pub fn new() -> Self {
-        Self(HashableHashSet::new())
+        Self(HashableHashMap::new())
     }
 
     /// Set a timer.
     pub fn set(&mut self, timer: T) -> bool {
-        self.0.insert(timer)
+        self.0.insert(timer, Deadline::default()).is_none()
+    }
+
+    /// Set a timer that can only fire within a particular interval.
+    pub fn set_with_deadline(&mut self, timer: T, deadline: Deadline) -> bool {
+        self.0.insert(timer, deadline).is_none()
     }
 
     /// Cancel a timer.
     pub fn cancel(&mut self, timer: &T) -> bool {
-        self.0.remove(timer)
+        self.0.remove(timer).is_some()
     }
 
     /// Cancels all timers.
@@ -39,10 +56,34 @@ where
 
     /// Iterate through the currently set timers.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.keys()
+    }
+
+    /// Indicates when a timer can fire, if it is set.
+    pub fn deadline(&self, timer: &T) -> Option<Deadline> {
+        self.0.get(timer).copied()
+    }
+
+    /// Iterate through the deadlines of the currently set timers.
+    pub fn deadlines(&self) -> impl Iterator<Item = (&T, &Deadline)> {
         self.0.iter()
     }
 }
 
+// Deadlines are omitted for consistency with the output prior to their introduction.
+impl<T: Debug + Hash + Eq> Debug for Timers<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let timers: std::collections::HashSet<_> = self.0.keys().collect();
+        f.debug_tuple("Timers").field(&timers).finish()
+    }
+}
+
+impl<T: serde::Serialize + Hash + Eq> serde::Serialize for Timers<T> {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_seq(self.0.keys())
+    }
+}
+
 impl<T> Rewrite<Id> for Timers<T>
 where
     T: Eq + Hash + Clone,
@@ -132,6 +132,7 @@ where
     type Msg = WORegisterMsg<u64, char, InternalMsg>;
     type State = WORegisterActorState<ServerActor::State, u64>;
     type Timer = ServerActor::Timer;
+    type Storage = ServerActor::Storage;
 
     #[allow(clippy::identity_op)]
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
@@ -166,7 +167,7 @@ where
                 }
             }
             WORegisterActor::Server(server_actor) => {
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 let state =
                     WORegisterActorState::Server(server_actor.on_start(id, &mut server_out));
                 o.append(&mut server_out);
@@ -188,7 +189,7 @@ where
             (A::Client { .. }, S::Client { .. }) => {}
             (A::Server(server_actor), S::Server(server_state)) => {
                 let mut server_state = Cow::Borrowed(server_state);
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 server_actor.on_timeout(id, &mut server_state, timer, &mut server_out);
                 if let Cow::Owned(server_state) = server_state {
                     *state = Cow::Owned(WORegisterActorState::Server(server_state))
@@ -274,7 +275,7 @@ where
             }
             (A::Server(server_actor), S::Server(server_state)) => {
                 let mut server_state = Cow::Borrowed(server_state);
-                let mut server_out = Out::new();
+                let mut server_out = Out::with_clock(o.clock());
                 server_actor.on_msg(id, &mut server_state, src, msg, &mut server_out);
                 if let Cow::Owned(server_state) = server_state {
                     *state = Cow::Owned(WORegisterActorState::Server(server_state))
@@ -285,6 +286,27 @@ where
         }
     }
 
+    fn on_recover(
+        &self,
+        id: Id,
+        storage: Option<&Self::Storage>,
+        o: &mut Out<Self>,
+    ) -> Self::State {
+        match self {
+            WORegisterActor::Client { .. } => self.on_start(id, o),
+            WORegisterActor::Server(server_actor) => {
+                let mut server_out = Out::with_clock(o.clock());
+                let state = WORegisterActorState::Server(server_actor.on_recover(
+                    id,
+                    storage,
+                    &mut server_out,
+                ));
+                o.append(&mut server_out);
+                state
+            }
+        }
+    }
+
     fn name(&self) -> String {
         match self {
             WORegisterActor::Client {
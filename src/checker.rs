@@ -1,14 +1,21 @@
 //! Private module for selective re-export.
 
 mod bfs;
+mod bitstate;
+mod checkpoint;
 mod dfs;
+mod distributed;
 mod explorer;
+mod iddfs;
+mod liveness;
+mod ltl;
 mod on_demand;
 mod path;
 mod representative;
 mod rewrite;
 mod rewrite_plan;
 mod simulation;
+mod storage;
 mod visitor;
 
 use crate::has_discoveries::HasDiscoveries;
@@ -18,15 +25,18 @@ use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
+pub use ltl::Ltl;
 pub use path::*;
 pub use representative::*;
 pub use rewrite::*;
 pub use rewrite_plan::*;
 pub use simulation::{Chooser, UniformChooser};
+pub use storage::{DiskStorage, InMemoryStorage, StateStorage};
 pub use visitor::*;
 
 #[derive(Clone, Copy)]
@@ -72,6 +82,12 @@ pub struct CheckerBuilder<M: Model> {
     visitor: Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
     finish_when: HasDiscoveries,
     timeout: Option<Duration>,
+    liveness: bool,
+    sound_eventually: bool,
+    checkpoint: Option<(PathBuf, Duration)>,
+    resume: Option<PathBuf>,
+    storage: Option<Box<dyn StateStorage>>,
+    bitstate: Option<usize>,
 }
 impl<M: Model> CheckerBuilder<M> {
     pub(crate) fn new(model: M) -> Self {
@@ -84,6 +100,12 @@ impl<M: Model> CheckerBuilder<M> {
             visitor: None,
             finish_when: HasDiscoveries::All,
             timeout: None,
+            liveness: false,
+            sound_eventually: false,
+            checkpoint: None,
+            resume: None,
+            storage: None,
+            bitstate: None,
         }
     }
 
@@ -138,15 +160,24 @@ impl<M: Model> CheckerBuilder<M> {
     /// - `GET /.status` returns information about the model checker status.
     /// - `GET /.states` returns available initial states and fingerprints.
     /// - `GET /.states/{fingerprint1}/{fingerprint2}/...` follows the specified
-    ///    path of fingerprints and returns available actions with resulting
-    ///    states and fingerprints.
+    ///   path of fingerprints and returns available actions with resulting
+    ///   states and fingerprints.
     /// - `GET /.states/.../{invalid-fingerprint}` returns 404.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model has [`Property::ltl`] properties, which this checker does not check.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
     pub fn serve(self, addresses: impl std::net::ToSocketAddrs) -> std::sync::Arc<impl Checker<M>>
     where
         M: 'static + Model + Send + Sync,
         M::Action: Debug + Send + Sync,
         M::State: Debug + Hash + Send + Sync,
     {
+        self.assert_no_bitstate("serve");
+        self.assert_bfs_only_options("serve");
+        self.assert_no_ltl("serve");
         explorer::serve(self, addresses)
     }
 
@@ -164,6 +195,7 @@ impl<M: Model> CheckerBuilder<M> {
         M: Model + Send + Sync + 'static,
         M::State: Hash + Send + Sync + 'static,
     {
+        self.assert_no_bitstate("spawn_bfs");
         bfs::BfsChecker::spawn(self)
     }
 
@@ -173,6 +205,12 @@ impl<M: Model> CheckerBuilder<M> {
     ///
     /// This call does not block the current thread. Call [`Checker::join`] to block until checking
     /// completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model has [`Property::ltl`] properties, which this checker does not check.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
     #[must_use = "Checkers run on background threads. \
                   Consider calling join() or report(...), for example."]
     pub fn spawn_on_demand(self) -> impl Checker<M>
@@ -180,6 +218,9 @@ impl<M: Model> CheckerBuilder<M> {
         M: Model + Send + Sync + 'static,
         M::State: Hash + Send + Sync + 'static,
     {
+        self.assert_no_bitstate("spawn_on_demand");
+        self.assert_bfs_only_options("spawn_on_demand");
+        self.assert_no_ltl("spawn_on_demand");
         on_demand::OnDemandChecker::spawn(self)
     }
 
@@ -196,15 +237,122 @@ impl<M: Model> CheckerBuilder<M> {
         M: Model + Send + Sync + 'static,
         M::State: Hash + Send + Sync + 'static,
     {
+        self.assert_bfs_only_options("spawn_dfs");
         dfs::DfsChecker::spawn(self)
     }
 
+    /// Spawns an iterative deepening depth-first search model checker, which repeats a
+    /// depth-first search with a depth bound that increases by one each time. Like
+    /// [`CheckerBuilder::spawn_bfs`] the shortest [`Path`] to each discovery is found, while
+    /// memory use is bounded by the depth of the search rather than the number of states, at the
+    /// cost of revisiting shallower states in each iteration. Each iteration only remembers the
+    /// current path and a fixed-size cache of states, so a state reached via multiple paths may
+    /// be checked more than once, and [`Checker::unique_state_count`] may count it more than once.
+    ///
+    /// Checking stops once no path within the bound leads to an unchecked state or the bound
+    /// reaches [`CheckerBuilder::target_max_depth`]. Checking is always single threaded.
+    ///
+    /// This call does not block the current thread. Call [`Checker::join`] to block until
+    /// checking completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`CheckerBuilder::liveness`] is enabled or the model has [`Property::ltl`]
+    /// properties, as checking either requires the state graph.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_iddfs(self) -> impl Checker<M>
+    where
+        M: Model + Send + Sync + 'static,
+        M::State: Hash + Send + Sync + 'static,
+    {
+        self.assert_no_bitstate("spawn_iddfs");
+        self.assert_bfs_only_options("spawn_iddfs");
+        assert!(
+            !self.liveness,
+            "spawn_iddfs does not support liveness checking; use spawn_bfs or spawn_dfs"
+        );
+        self.assert_no_ltl("spawn_iddfs");
+        iddfs::IddfsChecker::spawn(self)
+    }
+
+    /// Spawns a model checker that distributes states across worker processes, each of which
+    /// must be running [`CheckerBuilder::serve_worker`] for the same model at one of the specified
+    /// addresses. States are partitioned among workers by fingerprint, and each worker sends
+    /// newly generated states directly to the worker that owns them, while the current process
+    /// coordinates the workers by detecting when checking is complete. If a worker is lost,
+    /// checking stops and [`Checker::error`] indicates why.
+    ///
+    /// Like [`CheckerBuilder::spawn_bfs`], each worker explores its states in breadth-first order,
+    /// although the resulting paths are not necessarily the shortest. Workers skip the actions
+    /// that [`Model::is_redundant`] indicates, and apply [`CheckerBuilder::symmetry`] if it is
+    /// set for the workers, in which case it must also be set for the coordinator.
+    ///
+    /// This call does not block the current thread. Call [`Checker::join`] to block until
+    /// checking completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model has [`Property::ltl`] properties, which this checker does not check, or
+    /// if [`CheckerBuilder::visitor`], [`CheckerBuilder::liveness`], or
+    /// [`CheckerBuilder::sound_eventually`] is set, as each requires states to be checked in one
+    /// process.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_distributed(self, workers: Vec<std::net::SocketAddr>) -> impl Checker<M>
+    where
+        M: Model + Send + Sync + 'static,
+        M::State: Hash + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        self.assert_no_bitstate("spawn_distributed");
+        self.assert_bfs_only_options("spawn_distributed");
+        self.assert_no_ltl("spawn_distributed");
+        self.assert_distributable("spawn_distributed");
+        distributed::DistributedChecker::spawn(self, workers)
+    }
+
+    /// Listens at the specified address for a coordinator started by
+    /// [`CheckerBuilder::spawn_distributed`], then checks the states assigned to this process
+    /// until the coordinator indicates that checking is complete. Blocks the current thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// // In each worker process:
+    /// model.checker().serve_worker("0.0.0.0:3001").unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`CheckerBuilder::spawn_distributed`].
+    pub fn serve_worker(self, address: impl std::net::ToSocketAddrs) -> std::io::Result<()>
+    where
+        M::State: Hash + Send + serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        self.assert_no_bitstate("serve_worker");
+        self.assert_bfs_only_options("serve_worker");
+        self.assert_no_ltl("serve_worker");
+        self.assert_distributable("serve_worker");
+        distributed::serve_worker(self, address)
+    }
+
     /// Spawns a simulation model checker. This repeatedly traverses the model from initial states
     /// to a terminal state. This aims to provide faster coverage of deep states for models that
     /// cannot practically be checked exhaustively.
     ///
     /// This call does not block the current thread. Call [`Checker::join`] to block until
     /// checking completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model has [`Property::ltl`] properties, which this checker does not check.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
     #[must_use = "Checkers run on background threads. \
                   Consider calling join() or report(...), for example."]
     pub fn spawn_simulation<C>(self, seed: u64, chooser: C) -> impl Checker<M>
@@ -213,9 +361,57 @@ impl<M: Model> CheckerBuilder<M> {
         M::State: Hash + Send + Sync + 'static,
         C: Chooser<M>,
     {
+        self.assert_no_bitstate("spawn_simulation");
+        self.assert_bfs_only_options("spawn_simulation");
+        self.assert_no_ltl("spawn_simulation");
         simulation::SimulationChecker::spawn::<C>(self, seed, chooser)
     }
 
+    /// Panics if [`CheckerBuilder::bitstate`] is set, as only [`CheckerBuilder::spawn_dfs`]
+    /// supports it.
+    fn assert_no_bitstate(&self, spawn: &str) {
+        assert!(
+            self.bitstate.is_none(),
+            "{} does not support bitstate hashing; use spawn_dfs",
+            spawn
+        );
+    }
+
+    /// Panics if [`CheckerBuilder::checkpoint_to`], [`CheckerBuilder::resume_from`], or
+    /// [`CheckerBuilder::storage`] is set, as only [`CheckerBuilder::spawn_bfs`] supports them.
+    fn assert_bfs_only_options(&self, spawn: &str) {
+        assert!(
+            self.checkpoint.is_none() && self.resume.is_none() && self.storage.is_none(),
+            "{} does not support checkpoint_to, resume_from, or storage; use spawn_bfs",
+            spawn
+        );
+    }
+
+    /// Panics if an option that requires states to be checked in one process is set.
+    fn assert_distributable(&self, spawn: &str) {
+        assert!(
+            self.visitor.is_none() && !self.liveness && !self.sound_eventually,
+            "{} does not support visitor, liveness, or sound_eventually",
+            spawn
+        );
+    }
+
+    /// Panics if the model has [`Property::ltl`] properties, as only
+    /// [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`] check them.
+    ///
+    /// [`Property::ltl`]: crate::Property::ltl
+    fn assert_no_ltl(&self, spawn: &str) {
+        assert!(
+            !self
+                .model
+                .properties()
+                .iter()
+                .any(|p| matches!(p.expectation, Expectation::Ltl)),
+            "{} does not support Property::ltl; use spawn_bfs or spawn_dfs",
+            spawn
+        );
+    }
+
     /// Enables symmetry reduction. Requires the [model state] to implement [`Representative`].
     ///
     /// [model state]: crate::Model::State
@@ -285,6 +481,123 @@ impl<M: Model> CheckerBuilder<M> {
             ..self
         }
     }
+
+    /// Ensures that [`Property::eventually`] counterexamples are not missed when a state is
+    /// reachable via multiple paths or a cycle, at the cost of visiting more states. States are
+    /// considered visited only if reached with the same set of unsatisfied `eventually`
+    /// properties, and a path that returns to an earlier state without satisfying a property is
+    /// reported as a counterexample (the last state of which repeats an earlier one).
+    ///
+    /// Such cycles are found among the transitions between visited states, which are recorded in
+    /// memory and searched once the checker threads exit. [`CheckerBuilder::spawn_dfs`] ignores
+    /// [`CheckerBuilder::symmetry`] in this mode, as cycles among representatives need not be
+    /// cycles of the model, and a check continued via [`CheckerBuilder::resume_from`] misses
+    /// cycles through states expanded before the checkpoint.
+    ///
+    /// Applies to [`CheckerBuilder::spawn_bfs`], [`CheckerBuilder::spawn_dfs`], and
+    /// [`CheckerBuilder::spawn_on_demand`]. See also [`CheckerBuilder::liveness`], which
+    /// additionally considers fairness but must build the state graph.
+    ///
+    /// [`Property::eventually`]: crate::Property::eventually
+    pub fn sound_eventually(self) -> Self {
+        Self {
+            sound_eventually: true,
+            ..self
+        }
+    }
+
+    /// Replaces the set of visited states of a [`CheckerBuilder::spawn_dfs`] check with a fixed
+    /// array of `bits` bits, as with "bitstate hashing" in SPIN. Memory use is then independent of
+    /// the number of states, but distinct states may collide, in which case part of the state
+    /// space is silently omitted. [`Checker::false_positive_rate`] estimates the chance of this
+    /// for the next newly reached state, [`Checker::estimated_omitted_states`] estimates how many
+    /// states were omitted over the whole check, and the checker reports both.
+    ///
+    /// A state space of `n` states is typically covered with high probability given `bits` of
+    /// at least `32 * n`.
+    ///
+    /// # Panics
+    ///
+    /// Only [`CheckerBuilder::spawn_dfs`] supports bitstate hashing, so spawning any other checker
+    /// with this setting panics.
+    pub fn bitstate(self, bits: usize) -> Self {
+        Self {
+            bitstate: Some(bits),
+            ..self
+        }
+    }
+
+    /// Periodically saves the progress of a [`CheckerBuilder::spawn_bfs`] check to a file at
+    /// `path`, so that it can be continued with [`CheckerBuilder::resume_from`] if the process is
+    /// interrupted. A checkpoint is saved at most once per `interval`, and each one replaces the
+    /// previous.
+    ///
+    /// Checkpoints record the fingerprints of visited states, the pending frontier, and
+    /// discoveries. Pending states are reconstructed on resumption by replaying the model from an
+    /// initial state, so the model must not change between runs.
+    ///
+    /// Each checkpoint rewrites every visited fingerprint, and all checker threads pause while it
+    /// is written, so the cost of a save grows with the size of the state space. Choose an
+    /// `interval` long enough to amortize that pause.
+    ///
+    /// # Panics
+    ///
+    /// Only [`CheckerBuilder::spawn_bfs`] supports checkpoints, so spawning any other checker with
+    /// this setting panics.
+    pub fn checkpoint_to(self, path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            checkpoint: Some((path.into(), interval)),
+            ..self
+        }
+    }
+
+    /// Continues a [`CheckerBuilder::spawn_bfs`] check from a file previously saved via
+    /// [`CheckerBuilder::checkpoint_to`] rather than starting from the initial states.
+    ///
+    /// # Panics
+    ///
+    /// Spawning the checker panics if the checkpoint cannot be read or does not match the model,
+    /// or if the checker is not [`CheckerBuilder::spawn_bfs`].
+    pub fn resume_from(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            resume: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Specifies where [`CheckerBuilder::spawn_bfs`] records visited states. Defaults to
+    /// [`InMemoryStorage`]. [`DiskStorage`] moves the visited set and most of the pending
+    /// frontier out of memory at the cost of speed.
+    ///
+    /// # Panics
+    ///
+    /// Only [`CheckerBuilder::spawn_bfs`] supports this setting, so spawning any other checker
+    /// with it panics.
+    pub fn storage(self, storage: impl StateStorage + 'static) -> Self {
+        Self {
+            storage: Some(Box::new(storage)),
+            ..self
+        }
+    }
+
+    /// Enables cycle detection for [`Property::eventually`] properties, so that a behavior that
+    /// loops forever without satisfying the property is reported as a counterexample, whose
+    /// [`Path`] ends by returning to an earlier state. Without this, such properties are only
+    /// falsified by paths to terminal states.
+    ///
+    /// Only applies to [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`], and like
+    /// [`Property::ltl`] requires exploring the state graph on one thread, within the same bounds
+    /// as the main search. That pass then checks eventually properties in place of the main
+    /// search, so each is reported by one or the other.
+    ///
+    /// [`Property::eventually`]: crate::Property::eventually
+    /// [`Property::ltl`]: crate::Property::ltl
+    pub fn liveness(self) -> Self {
+        Self {
+            liveness: true,
+            ..self
+        }
+    }
 }
 
 /// Implementations perform [`Model`] checking.
@@ -316,6 +629,25 @@ pub trait Checker<M: Model> {
     /// Indicates the maximum depth that has been explored.
     fn max_depth(&self) -> usize;
 
+    /// Estimates the probability that the next newly reached state will be mistaken for a visited
+    /// one and therefore omitted, given the states visited so far. This is not the probability that
+    /// any state was omitted over the whole check, which is larger; see
+    /// [`Checker::estimated_omitted_states`]. Only available when checking with
+    /// [`CheckerBuilder::bitstate`].
+    fn false_positive_rate(&self) -> Option<f64> {
+        None
+    }
+
+    /// Estimates how many newly reached states have been mistaken for visited ones and therefore
+    /// omitted over the whole check. Each visited state contributes the number of new states
+    /// expected to have been mistaken for visited ones before it, given the
+    /// [`Checker::false_positive_rate`] at that time. States reachable only via an omitted state
+    /// are omitted as well, which this does not account for. Only available when checking with
+    /// [`CheckerBuilder::bitstate`].
+    fn estimated_omitted_states(&self) -> Option<f64> {
+        None
+    }
+
     /// Returns a map from property name to corresponding "discovery" (indicated
     /// by a [`Path`]).
     fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>>;
@@ -338,9 +670,16 @@ pub trait Checker<M: Model> {
     fn handles(&mut self) -> Vec<JoinHandle<()>>;
 
     /// Indicates that either all properties have associated discoveries or all reachable states
-    /// have been visited.
+    /// have been visited, or that checking stopped early due to an [`Checker::error`].
     fn is_done(&self) -> bool;
 
+    /// Returns the error that stopped checking early, if any. Only a checker spawned via
+    /// [`CheckerBuilder::spawn_distributed`] can fail this way, for example if it loses its
+    /// connection to a worker.
+    fn error(&self) -> Option<&std::io::Error> {
+        None
+    }
+
     /// Looks up a discovery by property name. Panics if the property does not exist.
     fn discovery(&self, name: &'static str) -> Option<Path<M::State, M::Action>> {
         self.discoveries().remove(name)
@@ -370,6 +709,8 @@ pub trait Checker<M: Model> {
                         total_states: slf.state_count(),
                         unique_states: slf.unique_state_count(),
                         max_depth: slf.max_depth(),
+                        false_positive_rate: slf.false_positive_rate(),
+                        estimated_omitted_states: slf.estimated_omitted_states(),
                         duration: method_start.elapsed(),
                         done: false,
                     });
@@ -387,6 +728,8 @@ pub trait Checker<M: Model> {
                 total_states: self.state_count(),
                 unique_states: self.unique_state_count(),
                 max_depth: self.max_depth(),
+                false_positive_rate: self.false_positive_rate(),
+                estimated_omitted_states: self.estimated_omitted_states(),
                 duration: method_start2.elapsed(),
                 done: true,
             });
@@ -423,6 +766,8 @@ pub trait Checker<M: Model> {
                 total_states: self.state_count(),
                 unique_states: self.unique_state_count(),
                 max_depth: self.max_depth(),
+                false_positive_rate: self.false_positive_rate(),
+                estimated_omitted_states: self.estimated_omitted_states(),
                 duration: method_start.elapsed(),
                 done: false,
             });
@@ -433,6 +778,8 @@ pub trait Checker<M: Model> {
             total_states: self.state_count(),
             unique_states: self.unique_state_count(),
             max_depth: self.max_depth(),
+            false_positive_rate: self.false_positive_rate(),
+            estimated_omitted_states: self.estimated_omitted_states(),
             duration: method_start.elapsed(),
             done: true,
         });
@@ -456,7 +803,7 @@ pub trait Checker<M: Model> {
         let properties = self.model().properties();
         let property = properties.iter().find(|p| p.name == name).unwrap();
         match property.expectation {
-            Expectation::Always | Expectation::Eventually => {
+            Expectation::Always | Expectation::Eventually | Expectation::Ltl => {
                 DiscoveryClassification::Counterexample
             }
             Expectation::Sometimes => DiscoveryClassification::Example,
@@ -464,7 +811,7 @@ pub trait Checker<M: Model> {
     }
 
     /// A helper that verifies examples exist for all `sometimes` properties and no counterexamples
-    /// exist for any `always`/`eventually` properties.
+    /// exist for any `always`/`eventually`/`ltl` properties.
     fn assert_properties(&self)
     where
         M::Action: Debug,
@@ -474,6 +821,7 @@ pub trait Checker<M: Model> {
             match p.expectation {
                 Expectation::Always => self.assert_no_discovery(p.name),
                 Expectation::Eventually => self.assert_no_discovery(p.name),
+                Expectation::Ltl => self.assert_no_discovery(p.name),
                 Expectation::Sometimes => {
                     self.assert_any_discovery(p.name);
                 }
@@ -491,6 +839,12 @@ pub trait Checker<M: Model> {
             "Discovery for \"{}\" not found, but model checking is incomplete.",
             name
         );
+        if let Some(error) = self.error() {
+            panic!(
+                "Discovery for \"{}\" not found, but model checking failed: {}",
+                name, error
+            );
+        }
         panic!("Discovery for \"{}\" not found.", name);
     }
 
@@ -514,6 +868,12 @@ pub trait Checker<M: Model> {
             "Discovery for \"{}\" not found, but model checking is incomplete.",
             name
         );
+        if let Some(error) = self.error() {
+            panic!(
+                "Discovery for \"{}\" not found, but model checking failed: {}",
+                name, error
+            );
+        }
     }
 
     /// Panics if the specified actions do not result in a discovery for the specified property
@@ -544,14 +904,16 @@ pub trait Checker<M: Model> {
                             self.model().actions(states.last().unwrap(), &mut actions);
                             actions.is_empty()
                         };
-                        if !is_liveness_satisfied && is_path_terminal {
+                        let is_path_cyclic =
+                            states[..states.len() - 1].contains(states.last().unwrap());
+                        if !is_liveness_satisfied && (is_path_terminal || is_path_cyclic) {
                             return;
                         }
                         if is_liveness_satisfied {
                             additional_info
                                 .push("incorrect counterexample satisfies eventually property");
                         }
-                        if !is_path_terminal {
+                        if !is_path_terminal && !is_path_cyclic {
                             additional_info.push("incorrect counterexample is nonterminal");
                         }
                     }
@@ -560,6 +922,36 @@ pub trait Checker<M: Model> {
                             return;
                         }
                     }
+                    Expectation::Ltl => {
+                        // The path either returns to an earlier state or stutters at the end.
+                        let states = path.into_states();
+                        let states: Vec<_> = states.iter().collect();
+                        let (last, prefix) = states.split_last().unwrap();
+                        let mut lassos: Vec<_> = (0..prefix.len())
+                            .filter(|&i| prefix[i] == *last)
+                            .map(|i| (prefix, i))
+                            .collect();
+                        let is_path_terminal = !self
+                            .model()
+                            .next_states(last)
+                            .iter()
+                            .any(|s| self.model().within_boundary(s));
+                        if is_path_terminal {
+                            lassos.push((&states[..], prefix.len()));
+                        }
+                        let formula = property.formula.as_ref().unwrap();
+                        for (states, loop_start) in &lassos {
+                            if !formula.holds_on_lasso(self.model(), states, *loop_start) {
+                                return;
+                            }
+                        }
+                        if lassos.is_empty() {
+                            additional_info
+                                .push("incorrect counterexample neither cycles nor terminates");
+                        } else {
+                            additional_info.push("incorrect counterexample satisfies ltl property");
+                        }
+                    }
                 }
             }
         }
@@ -586,10 +978,40 @@ pub trait Checker<M: Model> {
 // a counterexample to the property.
 type EventuallyBits = id_set::IdSet;
 
+/// Derives the key under which a state is recorded as visited. See
+/// [`CheckerBuilder::sound_eventually`], which causes the key to also reflect the unsatisfied
+/// `eventually` properties so that the paths leading to a state are not conflated.
+fn visited_key(fp: Fingerprint, ebits: &EventuallyBits, sound_eventually: bool) -> Fingerprint {
+    if !sound_eventually || ebits.is_empty() {
+        fp
+    } else {
+        crate::fingerprint(&(fp, ebits.iter().collect::<Vec<_>>()))
+    }
+}
+
+/// Collects the actions to take from a state, omitting those that [`Model::is_redundant`]
+/// indicates need not be taken unless `every` transition is required (as with
+/// [`CheckerBuilder::sound_eventually`]). Returns whether any were omitted, in which case the
+/// state is not terminal.
+fn collect_actions<M: Model>(
+    model: &M,
+    state: &M::State,
+    actions: &mut Vec<M::Action>,
+    every: bool,
+) -> bool {
+    model.actions(state, actions);
+    if every {
+        return false;
+    }
+    let count = actions.len();
+    actions.retain(|action| !model.is_redundant(state, action));
+    actions.len() < count
+}
+
 #[cfg(test)]
 mod test_eventually_property_checker {
     use crate::test_util::dgraph::DGraph;
-    use crate::{Checker, Property};
+    use crate::{Checker, Model, Property};
 
     fn eventually_odd() -> Property<DGraph> {
         Property::eventually("odd", |_, s| s % 2 == 1)
@@ -661,7 +1083,7 @@ mod test_eventually_property_checker {
 
     #[test]
     fn fixme_can_miss_counterexample_when_revisiting_a_state() {
-        // i.e. incorrectly verify
+        // i.e. incorrectly verify, unless liveness checking is enabled (see below)
         assert_eq!(
             DGraph::with_property(eventually_odd())
                 .with_path(vec![0, 2, 4, 2]) // cycle
@@ -678,6 +1100,239 @@ mod test_eventually_property_checker {
             None
         ); // FIXME: `unwrap().into_states()` should be [0, 2, 4, 6]
     }
+
+    #[test]
+    fn liveness_checking_discovers_counterexample_when_revisiting_a_state() {
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4, 2]) // cycle
+            .checker()
+            .liveness()
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 2]
+        );
+        checker.assert_discovery("odd", vec![2, 4, 2]);
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4, 2])
+            .checker()
+            .liveness()
+            .spawn_dfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 2]
+        );
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4])
+            .with_path(vec![1, 4, 6]) // revisiting 4
+            .checker()
+            .liveness()
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn sound_eventually_discovers_counterexample_when_revisiting_a_state() {
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4, 2]) // cycle
+            .checker()
+            .sound_eventually()
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 2]
+        );
+        checker.assert_discovery("odd", vec![2, 4, 2]);
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4, 2])
+            .checker()
+            .sound_eventually()
+            .spawn_dfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 2]
+        );
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4, 2])
+            .checker()
+            .sound_eventually()
+            .spawn_on_demand();
+        checker.run_to_completion();
+        while !checker.is_done() {
+            std::thread::yield_now();
+        }
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 2]
+        );
+
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4])
+            .with_path(vec![1, 4, 6]) // revisiting 4
+            .checker()
+            .sound_eventually()
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 6]
+        );
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 4])
+            .with_path(vec![1, 4, 6])
+            .checker()
+            .sound_eventually()
+            .spawn_dfs()
+            .join();
+        assert_eq!(
+            checker.discovery("odd").unwrap().into_states(),
+            vec![0, 2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn sound_eventually_discovers_cycles_entered_via_a_join() {
+        // 4 is first reached directly from 0, so no path visits the 2-4 cycle in an order where
+        // the revisited state is an ancestor.
+        let assert_cycle = |states: Vec<u8>| {
+            let (last, prefix) = states.split_last().unwrap();
+            assert!(prefix.contains(last), "not a cycle: {:?}", states);
+            assert!(states.iter().all(|s| s % 2 == 0), "satisfies: {:?}", states);
+        };
+        let graph = || {
+            DGraph::with_property(eventually_odd())
+                .with_path(vec![0, 2, 4, 2])
+                .with_path(vec![0, 4])
+        };
+        let checker = graph().checker().sound_eventually().spawn_bfs().join();
+        assert_cycle(checker.discovery("odd").unwrap().into_states());
+        let checker = graph().checker().sound_eventually().spawn_dfs().join();
+        assert_cycle(checker.discovery("odd").unwrap().into_states());
+        let checker = graph().checker().sound_eventually().spawn_on_demand();
+        checker.run_to_completion();
+        while !checker.is_done() {
+            std::thread::yield_now();
+        }
+        assert_cycle(checker.discovery("odd").unwrap().into_states());
+    }
+
+    #[test]
+    fn sound_eventually_validates_joins_that_satisfy_property() {
+        DGraph::with_property(eventually_odd())
+            .with_path(vec![1, 4, 6])
+            .with_path(vec![0, 3, 4, 6]) // revisiting 4 after satisfying the property
+            .checker()
+            .sound_eventually()
+            .spawn_dfs()
+            .join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn liveness_checking_validates_cycles_that_satisfy_property() {
+        DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 3, 2])
+            .with_path(vec![1, 1])
+            .checker()
+            .liveness()
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+}
+
+#[cfg(test)]
+mod test_ltl_property_checker {
+    use crate::test_util::dgraph::DGraph;
+    use crate::{Checker, Ltl, Model, Property};
+
+    fn is_odd() -> Ltl<DGraph> {
+        Ltl::atom(|_, s| s % 2 == 1)
+    }
+
+    fn infinitely_often_odd() -> Property<DGraph> {
+        Property::ltl("infinitely often odd", Ltl::infinitely_often(is_odd()))
+    }
+
+    #[test]
+    fn can_validate() {
+        DGraph::with_property(infinitely_often_odd())
+            .with_path(vec![1]) // terminal stutters forever
+            .with_path(vec![2, 3, 4, 5, 2])
+            .with_path(vec![6, 7, 6])
+            .check()
+            .assert_properties();
+        DGraph::with_property(Property::ltl("even until odd", (!is_odd()).until(is_odd())))
+            .with_path(vec![0, 2, 3, 4, 4])
+            .with_path(vec![0, 5])
+            .check()
+            .assert_properties();
+    }
+
+    #[test]
+    fn can_discover_lasso_counterexample() {
+        let checker = DGraph::with_property(infinitely_often_odd())
+            .with_path(vec![1, 3, 1])
+            .with_path(vec![1, 2, 4, 6, 4])
+            .check();
+        assert_eq!(
+            checker
+                .discovery("infinitely often odd")
+                .unwrap()
+                .into_states(),
+            vec![1, 2, 4, 6, 4]
+        );
+        checker.assert_discovery("infinitely often odd", vec![2, 4, 6, 4]);
+
+        let checker = DGraph::with_property(infinitely_often_odd())
+            .with_path(vec![1, 3, 1])
+            .with_path(vec![1, 2, 4, 6, 4])
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_discovery("infinitely often odd", vec![2, 4, 6, 4]);
+    }
+
+    #[test]
+    fn can_discover_terminal_counterexample() {
+        let checker =
+            DGraph::with_property(Property::ltl("even until odd", (!is_odd()).until(is_odd())))
+                .with_path(vec![0, 2, 3])
+                .with_path(vec![0, 4])
+                .check();
+        assert_eq!(
+            checker.discovery("even until odd").unwrap().into_states(),
+            vec![0, 4]
+        );
+        checker.assert_discovery("even until odd", vec![4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "incorrect counterexample satisfies ltl property")]
+    fn rejects_invalid_discovery() {
+        DGraph::with_property(infinitely_often_odd())
+            .with_path(vec![1, 3, 1])
+            .with_path(vec![1, 2, 4, 6, 4])
+            .check()
+            .assert_discovery("infinitely often odd", vec![3, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "spawn_iddfs does not support Property::ltl")]
+    fn rejects_checkers_that_cannot_check_ltl() {
+        let _ = DGraph::with_property(infinitely_often_odd())
+            .with_path(vec![1, 3, 1])
+            .checker()
+            .spawn_iddfs();
+    }
 }
 
 #[cfg(test)]
@@ -711,6 +1366,25 @@ mod test_report {
     use super::*;
     use crate::{report::WriteReporter, test_util::linear_equation_solver::LinearEquation};
 
+    #[test]
+    fn report_includes_false_positive_rate_for_bitstate_hashing() {
+        let mut written: Vec<u8> = Vec::new();
+        LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .bitstate(1 << 20)
+            .spawn_dfs()
+            .report(&mut WriteReporter::new(&mut written));
+        let output = String::from_utf8(written).unwrap();
+        let done = output.lines().find(|l| l.starts_with("Done.")).unwrap();
+        assert!(
+            done.starts_with("Done. states=55, unique=55, depth=28, sec=")
+                && done.contains(", false_positive_rate=")
+                && done.contains(", estimated_omitted_states="),
+            "Output did not include the false positive rate. output={:?}`",
+            output
+        );
+    }
+
     #[test]
     fn report_includes_property_names_and_paths() {
         // The assertions use `starts_with` to omit timing since it varies.
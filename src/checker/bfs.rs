@@ -1,10 +1,14 @@
 //! Private module for selective re-export.
 
-use crate::checker::{Checker, EventuallyBits, Expectation, Path};
+use crate::checker::checkpoint::{read_checkpoint, CheckpointEntry, Checkpointer};
+use crate::checker::liveness::{check_liveness, Bounds, EventuallyCycles, OnExit};
+use crate::checker::{
+    collect_actions, visited_key, Checker, EventuallyBits, Expectation, InMemoryStorage, Path,
+    StateStorage,
+};
 use crate::job_market::JobBroker;
 use crate::{fingerprint, CheckerBuilder, CheckerVisitor, Fingerprint, Model, Property};
-use dashmap::mapref::entry::Entry;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use nohash_hasher::NoHashHasher;
 use std::collections::{HashMap, VecDeque};
 use std::hash::{BuildHasherDefault, Hash};
@@ -26,9 +30,9 @@ pub(crate) struct BfsChecker<M: Model> {
     job_broker: JobBroker<Job<M::State>>,
     state_count: Arc<AtomicUsize>,
     max_depth: Arc<AtomicUsize>,
-    generated:
-        Arc<DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>>,
-    discoveries: Arc<DashMap<&'static str, Fingerprint>>,
+    generated: Arc<dyn StateStorage>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+    cycles: Option<Arc<EventuallyCycles>>,
 }
 type Job<State> = (State, Fingerprint, EventuallyBits, NonZeroUsize);
 
@@ -42,24 +46,21 @@ where
         let target_state_count = options.target_state_count;
         let target_max_depth = options.target_max_depth;
         let thread_count = options.thread_count;
+        let liveness = options.liveness;
+        let close_at = options.timeout.map(|t| SystemTime::now() + t);
+        let bounds = Arc::new(Bounds {
+            symmetry: options.symmetry,
+            target_state_count,
+            target_max_depth,
+            close_at,
+        });
+        let sound_eventually = options.sound_eventually;
         let visitor = Arc::new(options.visitor);
         let finish_when = Arc::new(options.finish_when);
         let properties = Arc::new(model.properties());
 
-        let init_states: Vec<_> = model
-            .init_states()
-            .into_iter()
-            .filter(|s| model.within_boundary(s))
-            .collect();
-        let state_count = Arc::new(AtomicUsize::new(init_states.len()));
+        let state_count = Arc::new(AtomicUsize::new(0));
         let max_depth = Arc::new(AtomicUsize::new(0));
-        let generated = Arc::new({
-            let generated = DashMap::default();
-            for s in &init_states {
-                generated.insert(fingerprint(s), None);
-            }
-            generated
-        });
         let ebits = {
             let mut ebits = EventuallyBits::new();
             for (i, p) in model.properties().iter().enumerate() {
@@ -68,22 +69,90 @@ where
                     ..
                 } = p
                 {
+                    if liveness {
+                        // Owned by `check_liveness`.
+                        continue;
+                    }
                     ebits.insert(i);
                 }
             }
             ebits
         };
-        let pending: VecDeque<_> = init_states
-            .into_iter()
-            .map(|s| {
-                let fp = fingerprint(&s);
-                (s, fp, ebits.clone(), NonZeroUsize::new(1).unwrap())
-            })
-            .collect();
+        let generated: Arc<dyn StateStorage> = match options.storage {
+            Some(storage) => Arc::from(storage),
+            None => Arc::new(InMemoryStorage::default()),
+        };
+        let aliases = Arc::new(DashMap::default());
         let discoveries = Arc::new(DashMap::default());
+        let cycles = sound_eventually.then(|| Arc::new(EventuallyCycles::new(thread_count)));
+        let pending: VecDeque<_> = if let Some(path) = &options.resume {
+            let entries = read_checkpoint(path).unwrap_or_else(|err| {
+                panic!("Unable to resume from checkpoint {:?}: {}", path, err)
+            });
+            let mut pending = VecDeque::new();
+            for entry in entries {
+                let entry = entry.unwrap_or_else(|err| {
+                    panic!("Unable to resume from checkpoint {:?}: {}", path, err)
+                });
+                match entry {
+                    CheckpointEntry::Counts {
+                        state_count: count,
+                        max_depth: depth,
+                    } => {
+                        state_count.store(count, Ordering::Relaxed);
+                        max_depth.store(depth, Ordering::Relaxed);
+                    }
+                    CheckpointEntry::Generated(key, parent) => {
+                        generated.insert(key, parent);
+                    }
+                    CheckpointEntry::Alias(key, fp) => {
+                        aliases.insert(key, fp);
+                    }
+                    CheckpointEntry::Pending(key) => {
+                        pending.push_back(resume_job(&*model, &*generated, &aliases, &ebits, key));
+                    }
+                    CheckpointEntry::Discovery(name, fingerprints) => {
+                        if let Some(property) = properties.iter().find(|p| p.name == name) {
+                            discoveries.insert(property.name, fingerprints);
+                        }
+                    }
+                }
+            }
+            pending
+        } else {
+            let init_states: Vec<_> = model
+                .init_states()
+                .into_iter()
+                .filter(|s| model.within_boundary(s))
+                .collect();
+            state_count.store(init_states.len(), Ordering::Relaxed);
+            for s in &init_states {
+                let fp = fingerprint(s);
+                let key = visited_key(fp, &ebits, sound_eventually);
+                generated.insert(key, None);
+                if key != fp {
+                    aliases.insert(key, fp);
+                }
+            }
+            init_states
+                .into_iter()
+                .map(|s| {
+                    let fp = fingerprint(&s);
+                    (s, fp, ebits.clone(), NonZeroUsize::new(1).unwrap())
+                })
+                .collect()
+        };
+        let checkpointer = options.checkpoint.map(|(path, interval)| {
+            let checkpointer = Checkpointer::new(path, interval);
+            for (_, fp, ebits, _) in &pending {
+                checkpointer
+                    .unexpanded
+                    .insert(visited_key(*fp, ebits, sound_eventually));
+            }
+            Arc::new(checkpointer)
+        });
         let mut handles = Vec::new();
 
-        let close_at = options.timeout.map(|t| SystemTime::now() + t);
         let mut job_broker = JobBroker::new(thread_count, close_at);
         job_broker.push(pending);
 
@@ -96,15 +165,42 @@ where
             let state_count = Arc::clone(&state_count);
             let max_depth = Arc::clone(&max_depth);
             let generated = Arc::clone(&generated);
+            let aliases = Arc::clone(&aliases);
             let discoveries = Arc::clone(&discoveries);
+            let bounds = Arc::clone(&bounds);
+            let cycles = cycles.clone();
+            let checkpointer = checkpointer.clone();
+            let ebits = ebits.clone();
             handles.push(
                 std::thread::Builder::new()
                     .name(format!("checker-{}", t))
                     .spawn(move || {
                         log::debug!("{}: Thread started.", t);
+                        let _exit = cycles.as_deref().map(|cycles| {
+                            OnExit(|| {
+                                cycles.exit(&properties, &discoveries, |key| {
+                                    reconstruct_fingerprints(&*generated, &aliases, key)
+                                })
+                            })
+                        });
+                        if t == 0 {
+                            check_liveness(&*model, &properties, liveness, &bounds, &discoveries);
+                        }
                         let mut pending = VecDeque::new();
                         loop {
-                            // Step 1: Do work.
+                            // Step 1: Do work, preferring states that storage holds on disk.
+                            while pending.len() < 1500 {
+                                match generated.pop_pending() {
+                                    None => break,
+                                    Some(key) => pending.push_front(resume_job(
+                                        &*model,
+                                        &*generated,
+                                        &aliases,
+                                        &ebits,
+                                        key,
+                                    )),
+                                }
+                            }
                             if pending.is_empty() {
                                 pending = {
                                     let jobs = job_broker.pop();
@@ -120,17 +216,32 @@ where
                                     jobs
                                 };
                             }
+                            let running = checkpointer.as_ref().map(|c| c.running());
                             Self::check_block(
                                 &model,
                                 &state_count,
-                                &generated,
+                                &*generated,
+                                &aliases,
                                 &mut pending,
                                 &discoveries,
                                 &visitor,
                                 1500,
                                 target_max_depth,
                                 &max_depth,
+                                sound_eventually,
+                                cycles.as_deref(),
+                                checkpointer.as_ref().map(|c| &c.unexpanded),
                             );
+                            drop(running);
+                            if let Some(checkpointer) = &checkpointer {
+                                checkpointer.save_if_due(
+                                    &*generated,
+                                    &aliases,
+                                    &discoveries,
+                                    &state_count,
+                                    &max_depth,
+                                );
+                            }
                             if finish_when.matches(
                                 &discoveries.iter().map(|r| *r.key()).collect(),
                                 &properties,
@@ -170,6 +281,7 @@ where
             max_depth,
             generated,
             discoveries,
+            cycles,
         }
     }
 
@@ -177,17 +289,17 @@ where
     fn check_block(
         model: &M,
         state_count: &AtomicUsize,
-        generated: &DashMap<
-            Fingerprint,
-            Option<Fingerprint>,
-            BuildHasherDefault<NoHashHasher<u64>>,
-        >,
+        generated: &dyn StateStorage,
+        aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
         pending: &mut VecDeque<Job<M::State>>,
-        discoveries: &DashMap<&'static str, Fingerprint>,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
         visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
         mut max_count: usize,
         target_max_depth: Option<NonZeroUsize>,
         global_max_depth: &AtomicUsize,
+        sound_eventually: bool,
+        cycles: Option<&EventuallyCycles>,
+        unexpanded: Option<&DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>>,
     ) {
         let properties = model.properties();
 
@@ -205,6 +317,7 @@ where
                 None => return,
                 Some(pair) => pair,
             };
+            let state_key = visited_key(state_fp, &ebits, sound_eventually);
 
             if max_depth.get() > current_max_depth {
                 let _ = global_max_depth.compare_exchange(
@@ -219,12 +332,18 @@ where
             if let Some(target_max_depth) = target_max_depth {
                 if max_depth >= target_max_depth {
                     log::trace!("Skipping state as past max depth {}", max_depth);
+                    if let Some(unexpanded) = unexpanded {
+                        unexpanded.remove(&state_key);
+                    }
                     continue;
                 }
             }
 
             if let Some(visitor) = visitor {
-                visitor.visit(model, reconstruct_path(model, generated, state_fp));
+                visitor.visit(
+                    model,
+                    reconstruct_path(model, generated, aliases, state_key),
+                );
             }
 
             // Done if discoveries found for all properties.
@@ -241,7 +360,10 @@ where
                     } => {
                         if !always(model, &state) {
                             // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
+                            discoveries.insert(
+                                property.name,
+                                reconstruct_fingerprints(generated, aliases, state_key),
+                            );
                         } else {
                             is_awaiting_discoveries = true;
                         }
@@ -253,7 +375,10 @@ where
                     } => {
                         if sometimes(model, &state) {
                             // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
+                            discoveries.insert(
+                                property.name,
+                                reconstruct_fingerprints(generated, aliases, state_key),
+                            );
                         } else {
                             is_awaiting_discoveries = true;
                         }
@@ -273,6 +398,12 @@ where
                             ebits.remove(i);
                         }
                     }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => {
+                        // Checked against the state graph by `check_liveness`.
+                    }
                 }
             }
             if !is_awaiting_discoveries {
@@ -280,9 +411,11 @@ where
             }
 
             // Otherwise enqueue newly generated states (with related metadata).
-            let mut is_terminal = true;
-            model.actions(&state, &mut actions);
-            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
+            let mut is_terminal = !collect_actions(model, &state, &mut actions, sound_eventually);
+            let next_states = model
+                .next_states_for(&state, &mut actions)
+                .into_iter()
+                .flatten();
             for next_state in next_states {
                 // Skip if outside boundary.
                 if !model.within_boundary(&next_state) {
@@ -290,32 +423,37 @@ where
                 }
                 state_count.fetch_add(1, Ordering::Relaxed);
 
-                // Skip if already generated.
-                //
-                // FIXME: we should really include ebits in the fingerprint here --
-                // it is possible to arrive at a DAG join with two different ebits
-                // values, and subsequently treat the fact that some eventually
-                // property held on the path leading to the first visit as meaning
-                // that it holds in the path leading to the second visit -- another
-                // possible false-negative.
+                // Skip if already generated, which is not terminal as it may be a join or a
+                // cycle. With `sound_eventually`, cycles are found once the search ends.
                 let next_fingerprint = fingerprint(&next_state);
-                if let Entry::Vacant(next_entry) = generated.entry(next_fingerprint) {
-                    next_entry.insert(Some(state_fp));
+                let next_key = visited_key(next_fingerprint, &ebits, sound_eventually);
+                if let Some(cycles) = cycles {
+                    if !ebits.is_empty() {
+                        cycles.insert(
+                            (state_key, state_fp),
+                            max_depth.get(),
+                            &ebits,
+                            (next_key, next_fingerprint),
+                        );
+                    }
+                }
+                if generated.insert(next_key, Some(state_key)) {
+                    if next_key != next_fingerprint {
+                        aliases.insert(next_key, next_fingerprint);
+                    }
+                    if let Some(unexpanded) = unexpanded {
+                        unexpanded.insert(next_key);
+                    }
                 } else {
-                    // FIXME: arriving at an already-known state may be a loop (in which case it
-                    // could, in a fancier implementation, be considered a terminal state for
-                    // purposes of eventually-property checking) but it might also be a join in
-                    // a DAG, which makes it non-terminal. These cases can be disambiguated (at
-                    // some cost), but for now we just _don't_ treat them as terminal, and tell
-                    // users they need to explicitly ensure model path-acyclicality when they're
-                    // using eventually properties (using a boundary or empty actions or
-                    // whatever).
                     is_terminal = false;
                     continue;
                 }
 
                 // Otherwise further checking is applicable.
                 is_terminal = false;
+                if generated.push_pending(next_key, pending.len()) {
+                    continue;
+                }
                 pending.push_front((
                     next_state,
                     next_fingerprint,
@@ -323,11 +461,17 @@ where
                     NonZeroUsize::new(max_depth.get() + 1).unwrap(),
                 ));
             }
+            if let Some(unexpanded) = unexpanded {
+                unexpanded.remove(&state_key);
+            }
             if is_terminal {
                 for (i, property) in properties.iter().enumerate() {
                     if ebits.contains(i) {
                         // Races other threads, but that's fine.
-                        discoveries.insert(property.name, state_fp);
+                        discoveries.insert(
+                            property.name,
+                            reconstruct_fingerprints(generated, aliases, state_key),
+                        );
                     }
                 }
             }
@@ -362,7 +506,7 @@ where
             .map(|mapref| {
                 (
                     <&'static str>::clone(mapref.key()),
-                    reconstruct_path(self.model(), &self.generated, *mapref.value()),
+                    Path::from_fingerprints(self.model(), VecDeque::from(mapref.value().clone())),
                 )
             })
             .collect()
@@ -373,31 +517,46 @@ where
     }
 
     fn is_done(&self) -> bool {
-        self.job_broker.is_closed() || self.discoveries.len() == self.model.properties().len()
+        let is_searched = self.cycles.as_ref().is_none_or(|c| c.is_searched());
+        (self.job_broker.is_closed() && is_searched)
+            || self.discoveries.len() == self.model.properties().len()
     }
 }
 
 fn reconstruct_path<M>(
     model: &M,
-    generated: &DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
-    fp: Fingerprint,
+    generated: &dyn StateStorage,
+    aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+    key: Fingerprint,
 ) -> Path<M::State, M::Action>
 where
     M: Model,
     M::State: Hash,
 {
+    Path::from_fingerprints(
+        model,
+        reconstruct_fingerprints(generated, aliases, key).into(),
+    )
+}
+
+fn reconstruct_fingerprints(
+    generated: &dyn StateStorage,
+    aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+    key: Fingerprint,
+) -> Vec<Fingerprint> {
     // First build a stack of digests representing the path (with the init digest at top of
     // stack). Then unwind the stack of digests into a vector of states. The TLC model checker
     // uses a similar technique, which is documented in the paper "Model Checking TLA+
     // Specifications" by Yu, Manolios, and Lamport.
 
     let mut fingerprints = VecDeque::new();
-    let mut next_fp = fp;
-    while let Some(source) = generated.get(&next_fp) {
-        match *source {
-            Some(prev_fingerprint) => {
+    let mut next_key = key;
+    while let Some(source) = generated.parent(next_key) {
+        let next_fp = aliases.get(&next_key).map_or(next_key, |fp| *fp);
+        match source {
+            Some(prev_key) => {
                 fingerprints.push_front(next_fp);
-                next_fp = prev_fingerprint;
+                next_key = prev_key;
             }
             None => {
                 fingerprints.push_front(next_fp);
@@ -405,7 +564,45 @@ where
             }
         }
     }
-    Path::from_fingerprints(model, fingerprints)
+    fingerprints.into()
+}
+
+/// Rebuilds a job from a checkpoint or [`StateStorage::pop_pending`] by replaying the path to the
+/// state visited under `key`.
+fn resume_job<M>(
+    model: &M,
+    generated: &dyn StateStorage,
+    aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+    ebits: &EventuallyBits,
+    key: Fingerprint,
+) -> Job<M::State>
+where
+    M: Model,
+    M::State: Hash,
+{
+    let fingerprints = reconstruct_fingerprints(generated, aliases, key);
+    let mut states = Path::from_fingerprints(model, fingerprints.into()).into_states();
+    let state = states.pop().unwrap();
+    let mut ebits = ebits.clone();
+    for (i, property) in model.properties().iter().enumerate() {
+        if let Property {
+            expectation: Expectation::Eventually,
+            condition: eventually,
+            ..
+        } = property
+        {
+            if states.iter().any(|s| eventually(model, s)) {
+                ebits.remove(i);
+            }
+        }
+    }
+    let state_fp = fingerprint(&state);
+    (
+        state,
+        state_fp,
+        ebits,
+        NonZeroUsize::new(states.len() + 1).unwrap(),
+    )
 }
 
 #[cfg(test)]
@@ -475,6 +672,96 @@ mod test {
         );
     }
 
+    #[test]
+    fn can_complete_with_disk_storage() {
+        let dir = |name: &str| {
+            std::env::temp_dir().join(format!("stateright-bfs-{}-{}", name, std::process::id()))
+        };
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .storage(DiskStorage::new(dir("unsolvable")).unwrap())
+            .threads(2)
+            .spawn_bfs()
+            .join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.unique_state_count(), 256 * 256);
+        drop(checker);
+        assert!(!dir("unsolvable").exists());
+
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .storage(
+                DiskStorage::new(dir("solvable"))
+                    .unwrap()
+                    .max_pending_in_memory(1),
+            )
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX, Guess::IncreaseX, Guess::IncreaseY]
+        );
+        drop(checker);
+        assert!(!dir("solvable").exists());
+    }
+
+    #[test]
+    fn can_resume_from_checkpoint() {
+        let path =
+            std::env::temp_dir().join(format!("stateright-bfs-resume-{}.json", std::process::id()));
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .checkpoint_to(&path, std::time::Duration::ZERO)
+            .target_state_count(10_000)
+            .spawn_bfs()
+            .join();
+        assert!(checker.unique_state_count() < 256 * 256);
+
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .resume_from(&path)
+            .spawn_bfs()
+            .join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.unique_state_count(), 256 * 256);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn resumes_with_earlier_discoveries() {
+        let path = std::env::temp_dir().join(format!(
+            "stateright-bfs-discoveries-{}.json",
+            std::process::id()
+        ));
+        LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .checkpoint_to(&path, std::time::Duration::ZERO)
+            .spawn_bfs()
+            .join();
+
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .resume_from(&path)
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX, Guess::IncreaseX, Guess::IncreaseY]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "spawn_bfs does not support bitstate hashing")]
+    fn rejects_bitstate_hashing() {
+        let _ = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .bitstate(1 << 20)
+            .spawn_bfs();
+    }
+
     // test that the checker shuts down all threads properly after a checker thread encounters a
     // panic in the model execution.
     #[test]
@@ -0,0 +1,173 @@
+//! Private module for selective re-export.
+
+use crate::Fingerprint;
+use dashmap::DashSet;
+use nohash_hasher::NoHashHasher;
+use std::hash::BuildHasherDefault;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// The set of visited states, which is either exact or a fixed size bit array in the style of
+/// the SPIN model checker's "bitstate hashing" (also known as a Bloom filter).
+pub(crate) enum Visited {
+    Exact(DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>),
+    Bitstate(BitState),
+}
+
+impl Visited {
+    pub(crate) fn new(bitstate: Option<usize>) -> Self {
+        match bitstate {
+            None => Visited::Exact(DashSet::default()),
+            Some(bits) => Visited::Bitstate(BitState::new(bits)),
+        }
+    }
+
+    /// Returns `true` if the state was not previously visited. May incorrectly return `false`
+    /// in bitstate mode.
+    pub(crate) fn insert(&self, fp: Fingerprint) -> bool {
+        match self {
+            Visited::Exact(set) => set.insert(fp),
+            Visited::Bitstate(bits) => bits.insert(fp),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Visited::Exact(set) => set.len(),
+            Visited::Bitstate(bits) => bits.inserted.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn false_positive_rate(&self) -> Option<f64> {
+        match self {
+            Visited::Exact(_) => None,
+            Visited::Bitstate(bits) => Some(bits.false_positive_rate()),
+        }
+    }
+
+    pub(crate) fn estimated_omitted_states(&self) -> Option<f64> {
+        match self {
+            Visited::Exact(_) => None,
+            Visited::Bitstate(bits) => Some(bits.estimated_omitted_states()),
+        }
+    }
+}
+
+pub(crate) struct BitState {
+    words: Box<[AtomicU64]>,
+    bit_count: u64,
+    inserted: AtomicUsize,
+    set_bits: AtomicUsize,
+    /// The bits of an `f64` estimating the number of states omitted so far.
+    omitted: AtomicU64,
+}
+
+impl BitState {
+    /// The number of bits set per state, which is the default for SPIN.
+    const HASH_COUNT: u64 = 3;
+
+    fn new(bits: usize) -> Self {
+        assert!(bits > 0, "Bitstate hashing requires at least one bit");
+        let word_count = bits.div_ceil(64);
+        Self {
+            words: (0..word_count).map(|_| AtomicU64::new(0)).collect(),
+            bit_count: 64 * word_count as u64,
+            inserted: AtomicUsize::new(0),
+            set_bits: AtomicUsize::new(0),
+            omitted: AtomicU64::new(0.0f64.to_bits()),
+        }
+    }
+
+    fn insert(&self, fp: Fingerprint) -> bool {
+        // Derives the bit indices via double hashing.
+        let h1 = fp.get();
+        let h2 = fp.get().rotate_left(32) | 1;
+        let mut set_bits = 0;
+        for i in 0..Self::HASH_COUNT {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.bit_count;
+            let mask = 1 << (bit % 64);
+            let prev = self.words[(bit / 64) as usize].fetch_or(mask, Ordering::Relaxed);
+            if prev & mask == 0 {
+                set_bits += 1;
+            }
+        }
+        if set_bits == 0 {
+            return false;
+        }
+        let p = self.false_positive_rate();
+        self.inserted.fetch_add(1, Ordering::Relaxed);
+        self.set_bits.fetch_add(set_bits, Ordering::Relaxed);
+        // Before reaching this new state, an expected `p / (1 - p)` others were mistaken for
+        // visited ones, which is close to `p` while few bits are set.
+        let _ = self
+            .omitted
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |omitted| {
+                Some((f64::from_bits(omitted) + p / (1.0 - p)).to_bits())
+            });
+        true
+    }
+
+    /// Estimates the probability that a newly reached state would be mistaken for one that was
+    /// already visited, which is the chance that each of its bits is already set.
+    fn false_positive_rate(&self) -> f64 {
+        let fill = self.set_bits.load(Ordering::Relaxed) as f64 / self.bit_count as f64;
+        fill.powi(Self::HASH_COUNT as i32)
+    }
+
+    /// Estimates how many newly reached states have been mistaken for visited ones over the
+    /// whole check.
+    fn estimated_omitted_states(&self) -> f64 {
+        f64::from_bits(self.omitted.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fingerprint;
+
+    #[test]
+    fn false_positive_rate_grows_with_visited_states() {
+        let visited = Visited::new(Some(1 << 16));
+        assert_eq!(visited.false_positive_rate(), Some(0.0));
+        assert!(visited.insert(fingerprint(&0)));
+        assert!(!visited.insert(fingerprint(&0)));
+        for i in 1..1_000 {
+            visited.insert(fingerprint(&i));
+        }
+        let sparse = visited.false_positive_rate().unwrap();
+        assert!(0.0 < sparse && sparse < 0.001, "{}", sparse);
+        for i in 1_000..100_000 {
+            visited.insert(fingerprint(&i));
+        }
+        let dense = visited.false_positive_rate().unwrap();
+        assert!(visited.len() < 100_000);
+        assert!(0.5 < dense && dense <= 1.0, "{}", dense);
+
+        assert_eq!(Visited::new(None).false_positive_rate(), None);
+    }
+
+    #[test]
+    fn estimated_omitted_states_sums_false_positive_rates() {
+        let visited = Visited::new(Some(1 << 16));
+        assert_eq!(visited.estimated_omitted_states(), Some(0.0));
+        for i in 0..1_000 {
+            visited.insert(fingerprint(&i));
+        }
+        let sparse = visited.estimated_omitted_states().unwrap();
+        assert!(0.0 < sparse && sparse < visited.false_positive_rate().unwrap() * 1_000.0);
+        for i in 1_000..100_000 {
+            visited.insert(fingerprint(&i));
+        }
+        // Roughly the difference between the states reached and those inserted.
+        let dense = visited.estimated_omitted_states().unwrap();
+        let missed = (100_000 - visited.len()) as f64;
+        assert!(
+            0.5 * missed < dense && dense < 2.0 * missed,
+            "{} {}",
+            dense,
+            missed
+        );
+
+        assert_eq!(Visited::new(None).estimated_omitted_states(), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Private module for selective re-export.
+
+use crate::checker::StateStorage;
+use crate::Fingerprint;
+use dashmap::{DashMap, DashSet};
+use nohash_hasher::NoHashHasher;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::hash::BuildHasherDefault;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// One record of the on-disk representation of an interrupted check, which is a sequence of
+/// JSON values so that it can be written and read one record at a time rather than as a whole.
+/// States are recorded as fingerprints only, so the pending frontier is restored by replaying each
+/// path from an initial state.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) enum CheckpointEntry {
+    Counts {
+        state_count: usize,
+        max_depth: usize,
+    },
+    /// A visited key and the key of its predecessor.
+    Generated(Fingerprint, Option<Fingerprint>),
+    /// A visited key and the fingerprint of its state, where the two differ.
+    Alias(Fingerprint, Fingerprint),
+    /// The key of a visited state whose successors have not yet been generated. These follow the
+    /// [`CheckpointEntry::Generated`] and [`CheckpointEntry::Alias`] entries, which are needed to
+    /// replay their paths.
+    Pending(Fingerprint),
+    Discovery(String, Vec<Fingerprint>),
+}
+
+/// Reads the entries of a checkpoint as they are needed.
+pub(crate) fn read_checkpoint(
+    path: &Path,
+) -> std::io::Result<impl Iterator<Item = std::io::Result<CheckpointEntry>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::Deserializer::from_reader(reader)
+        .into_iter()
+        .map(|entry| entry.map_err(std::io::Error::from)))
+}
+
+/// Writes checkpoint entries to a temporary file that then replaces the checkpoint, so an
+/// interrupted write leaves any previous checkpoint intact.
+pub(crate) struct CheckpointWriter {
+    path: PathBuf,
+    tmp: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CheckpointWriter {
+    pub(crate) fn create(path: &Path) -> std::io::Result<Self> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(File::create(&tmp)?),
+            tmp,
+        })
+    }
+
+    pub(crate) fn write(&mut self, entry: &CheckpointEntry) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub(crate) fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        std::fs::rename(self.tmp, self.path)
+    }
+}
+
+/// Periodically saves a checkpoint while a checker is running.
+///
+/// Checker threads hold [`Checkpointer::running`] while expanding states and record the keys
+/// they have yet to expand in [`Checkpointer::unexpanded`], so a checkpoint taken while no thread
+/// is running captures a consistent frontier even though the queued states themselves are spread
+/// across threads.
+pub(crate) struct Checkpointer {
+    path: PathBuf,
+    interval: Duration,
+    next_at: Mutex<Instant>,
+    pause: RwLock<()>,
+    pub(crate) unexpanded: DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+}
+
+impl Checkpointer {
+    pub(crate) fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            path,
+            interval,
+            next_at: Mutex::new(Instant::now() + interval),
+            pause: RwLock::new(()),
+            unexpanded: DashSet::default(),
+        }
+    }
+
+    /// Prevents a checkpoint from being taken until the returned guard is dropped.
+    pub(crate) fn running(&self) -> RwLockReadGuard<'_, ()> {
+        self.pause.read()
+    }
+
+    /// Saves a checkpoint if the interval has elapsed and no other thread is already doing so.
+    /// Other threads are paused while the visited set is written, which takes time proportional
+    /// to its size.
+    pub(crate) fn save_if_due(
+        &self,
+        generated: &dyn StateStorage,
+        aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        state_count: &AtomicUsize,
+        max_depth: &AtomicUsize,
+    ) {
+        let mut next_at = match self.next_at.try_lock() {
+            Some(next_at) => next_at,
+            None => return,
+        };
+        if Instant::now() < *next_at {
+            return;
+        }
+        {
+            let _paused = self.pause.write();
+            let result = self.save(
+                generated,
+                aliases,
+                discoveries,
+                state_count.load(Ordering::Relaxed),
+                max_depth.load(Ordering::Relaxed),
+            );
+            match result {
+                Ok(()) => log::debug!(
+                    "Saved checkpoint. path={:?}, gen={}, pending={}",
+                    self.path,
+                    generated.len(),
+                    self.unexpanded.len()
+                ),
+                Err(err) => log::warn!("Unable to save checkpoint. path={:?}: {}", self.path, err),
+            }
+        }
+        *next_at = Instant::now() + self.interval;
+    }
+
+    /// Streams each entry to the checkpoint file, so memory use does not grow with the number of
+    /// visited states.
+    fn save(
+        &self,
+        generated: &dyn StateStorage,
+        aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        state_count: usize,
+        max_depth: usize,
+    ) -> std::io::Result<()> {
+        let mut writer = CheckpointWriter::create(&self.path)?;
+        writer.write(&CheckpointEntry::Counts {
+            state_count,
+            max_depth,
+        })?;
+        let mut result = Ok(());
+        generated.for_each(&mut |key, parent| {
+            if result.is_ok() {
+                result = writer.write(&CheckpointEntry::Generated(key, parent));
+            }
+        });
+        result?;
+        for e in aliases.iter() {
+            writer.write(&CheckpointEntry::Alias(*e.key(), *e.value()))?;
+        }
+        for key in self.unexpanded.iter() {
+            writer.write(&CheckpointEntry::Pending(*key))?;
+        }
+        for e in discoveries.iter() {
+            writer.write(&CheckpointEntry::Discovery(
+                e.key().to_string(),
+                e.value().clone(),
+            ))?;
+        }
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fingerprint;
+
+    #[test]
+    fn can_save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "stateright-checkpoint-test-{}.json",
+            std::process::id()
+        ));
+        let entries = vec![
+            CheckpointEntry::Counts {
+                state_count: 3,
+                max_depth: 2,
+            },
+            CheckpointEntry::Generated(fingerprint(&1), None),
+            CheckpointEntry::Generated(fingerprint(&2), Some(fingerprint(&1))),
+            CheckpointEntry::Pending(fingerprint(&2)),
+            CheckpointEntry::Discovery("p".to_string(), vec![fingerprint(&1)]),
+        ];
+        let mut writer = CheckpointWriter::create(&path).unwrap();
+        for entry in &entries {
+            writer.write(entry).unwrap();
+        }
+        writer.finish().unwrap();
+        let loaded: Vec<_> = read_checkpoint(&path)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(loaded, entries);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,12 +1,13 @@
 //! Private module for selective re-export.
 
-use crate::checker::{Checker, EventuallyBits, Expectation, Path};
+use crate::checker::bitstate::Visited;
+use crate::checker::liveness::{check_liveness, Bounds, EventuallyCycles, OnExit};
+use crate::checker::{collect_actions, visited_key, Checker, EventuallyBits, Expectation, Path};
 use crate::job_market::JobBroker;
 use crate::{fingerprint, CheckerBuilder, CheckerVisitor, Fingerprint, Model, Property};
-use dashmap::{DashMap, DashSet};
-use nohash_hasher::NoHashHasher;
+use dashmap::DashMap;
 use std::collections::{HashMap, VecDeque};
-use std::hash::{BuildHasherDefault, Hash};
+use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -25,8 +26,9 @@ pub(crate) struct DfsChecker<M: Model> {
     job_broker: JobBroker<Job<M::State>>,
     state_count: Arc<AtomicUsize>,
     max_depth: Arc<AtomicUsize>,
-    generated: Arc<DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>>,
+    generated: Arc<Visited>,
     discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+    cycles: Option<Arc<EventuallyCycles>>,
 }
 type Job<State> = (State, Vec<Fingerprint>, EventuallyBits, NonZeroUsize);
 
@@ -37,10 +39,24 @@ where
 {
     pub(crate) fn spawn(options: CheckerBuilder<M>) -> Self {
         let model = Arc::new(options.model);
-        let symmetry = options.symmetry;
+        // Cycles in the reduced state space need not be cycles of the model.
+        let symmetry = if options.sound_eventually {
+            None
+        } else {
+            options.symmetry
+        };
         let target_state_count = options.target_state_count;
         let target_max_depth = options.target_max_depth;
         let thread_count = options.thread_count;
+        let liveness = options.liveness;
+        let close_at = options.timeout.map(|t| SystemTime::now() + t);
+        let bounds = Arc::new(Bounds {
+            symmetry: options.symmetry,
+            target_state_count,
+            target_max_depth,
+            close_at,
+        });
+        let sound_eventually = options.sound_eventually;
         let visitor = Arc::new(options.visitor);
         let finish_when = Arc::new(options.finish_when);
         let properties = Arc::new(model.properties());
@@ -52,17 +68,6 @@ where
             .collect();
         let state_count = Arc::new(AtomicUsize::new(init_states.len()));
         let max_depth = Arc::new(AtomicUsize::new(0));
-        let generated = Arc::new({
-            let generated = DashSet::default();
-            for s in &init_states {
-                if let Some(representative) = symmetry {
-                    generated.insert(fingerprint(&representative(s)));
-                } else {
-                    generated.insert(fingerprint(s));
-                }
-            }
-            generated
-        });
         let ebits = {
             let mut ebits = EventuallyBits::new();
             for (i, p) in properties.iter().enumerate() {
@@ -71,11 +76,30 @@ where
                     ..
                 } = p
                 {
+                    if liveness {
+                        // Owned by `check_liveness`.
+                        continue;
+                    }
                     ebits.insert(i);
                 }
             }
             ebits
         };
+        let generated = Arc::new({
+            let generated = Visited::new(options.bitstate);
+            for s in &init_states {
+                if let Some(representative) = symmetry {
+                    generated.insert(visited_key(
+                        fingerprint(&representative(s)),
+                        &ebits,
+                        sound_eventually,
+                    ));
+                } else {
+                    generated.insert(visited_key(fingerprint(s), &ebits, sound_eventually));
+                }
+            }
+            generated
+        });
         let pending: VecDeque<_> = init_states
             .into_iter()
             .map(|s| {
@@ -84,9 +108,9 @@ where
             })
             .collect();
         let discoveries = Arc::new(DashMap::default());
+        let cycles = sound_eventually.then(|| Arc::new(EventuallyCycles::new(thread_count)));
         let mut handles = Vec::new();
 
-        let close_at = options.timeout.map(|t| SystemTime::now() + t);
         let mut job_broker = JobBroker::new(thread_count, close_at);
         job_broker.push(pending);
 
@@ -100,11 +124,21 @@ where
             let max_depth = Arc::clone(&max_depth);
             let generated = Arc::clone(&generated);
             let discoveries = Arc::clone(&discoveries);
+            let bounds = Arc::clone(&bounds);
+            let cycles = cycles.clone();
             handles.push(
                 std::thread::Builder::new()
                     .name(format!("checker-{}", t))
                     .spawn(move || {
                         log::debug!("{}: Thread started.", t);
+                        let _exit = cycles.as_deref().map(|cycles| {
+                            OnExit(|| {
+                                cycles.exit(&properties, &discoveries, |key| cycles.stem(key))
+                            })
+                        });
+                        if t == 0 {
+                            check_liveness(&*model, &properties, liveness, &bounds, &discoveries);
+                        }
                         let mut pending = VecDeque::new();
                         loop {
                             // Step 1: Do work.
@@ -134,6 +168,8 @@ where
                                 target_max_depth,
                                 &max_depth,
                                 symmetry,
+                                sound_eventually,
+                                cycles.as_deref(),
                             );
                             if finish_when.matches(
                                 &discoveries.iter().map(|r| *r.key()).collect(),
@@ -174,6 +210,7 @@ where
             max_depth,
             generated,
             discoveries,
+            cycles,
         }
     }
 
@@ -182,7 +219,7 @@ where
     fn check_block(
         model: &M,
         state_count: &AtomicUsize,
-        generated: &DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+        generated: &Visited,
         pending: &mut VecDeque<Job<M::State>>,
         discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
         visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
@@ -190,6 +227,8 @@ where
         target_max_depth: Option<NonZeroUsize>,
         global_max_depth: &AtomicUsize,
         symmetry: Option<fn(&M::State) -> M::State>,
+        sound_eventually: bool,
+        cycles: Option<&EventuallyCycles>,
     ) {
         let properties = model.properties();
 
@@ -207,6 +246,8 @@ where
                 None => return,
                 Some(pair) => pair,
             };
+            let state_fp = *fingerprints.last().unwrap();
+            let state_key = visited_key(state_fp, &ebits, sound_eventually);
 
             if max_depth.get() > current_max_depth {
                 let _ = global_max_depth.compare_exchange(
@@ -277,6 +318,12 @@ where
                             ebits.remove(i);
                         }
                     }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => {
+                        // Checked against the state graph by `check_liveness`.
+                    }
                 }
             }
             if !is_awaiting_discoveries {
@@ -284,54 +331,47 @@ where
             }
 
             // Otherwise enqueue newly generated states (with related metadata).
-            let mut is_terminal = true;
-            model.actions(&state, &mut actions);
-            for action in actions.drain(..) {
-                let next_state = match model.next_state(&state, action) {
-                    None => continue,
-                    Some(next_state) => next_state,
-                };
-
+            let mut is_terminal = !collect_actions(model, &state, &mut actions, sound_eventually);
+            for next_state in model
+                .next_states_for(&state, &mut actions)
+                .into_iter()
+                .flatten()
+            {
                 // Skip if outside boundary.
                 if !model.within_boundary(&next_state) {
                     continue;
                 }
                 state_count.fetch_add(1, Ordering::Relaxed);
 
-                // Skip if already generated.
+                // Skip if already generated, which is not terminal as it may be a join or a
+                // cycle. With `sound_eventually`, cycles are found once the search ends.
                 //
-                // FIXME: we should really include ebits in the fingerprint here --
-                // it is possible to arrive at a DAG join with two different ebits
-                // values, and subsequently treat the fact that some eventually
-                // property held on the path leading to the first visit as meaning
-                // that it holds in the path leading to the second visit -- another
-                // possible false-negative.
-                let next_fingerprint = if let Some(representative) = symmetry {
-                    let representative_fingerprint = fingerprint(&representative(&next_state));
-                    if !generated.insert(representative_fingerprint) {
-                        is_terminal = false;
-                        continue;
-                    }
-                    // IMPORTANT: continue the path with the pre-canonicalized state/fingerprint to
-                    // avoid jumping to another part of the state space for which there may not be
-                    // a path extension from the previously collected path.
-                    fingerprint(&next_state)
-                } else {
-                    let next_fingerprint = fingerprint(&next_state);
-                    if !generated.insert(next_fingerprint) {
-                        // FIXME: arriving at an already-known state may be a loop (in which case it
-                        // could, in a fancier implementation, be considered a terminal state for
-                        // purposes of eventually-property checking) but it might also be a join in
-                        // a DAG, which makes it non-terminal. These cases can be disambiguated (at
-                        // some cost), but for now we just _don't_ treat them as terminal, and tell
-                        // users they need to explicitly ensure model path-acyclicality when they're
-                        // using eventually properties (using a boundary or empty actions or
-                        // whatever).
-                        is_terminal = false;
-                        continue;
+                // IMPORTANT: continue the path with the pre-canonicalized state/fingerprint to
+                // avoid jumping to another part of the state space for which there may not be a
+                // path extension from the previously collected path.
+                let next_fingerprint = fingerprint(&next_state);
+                let next_key = visited_key(
+                    match symmetry {
+                        Some(representative) => fingerprint(&representative(&next_state)),
+                        None => next_fingerprint,
+                    },
+                    &ebits,
+                    sound_eventually,
+                );
+                if let Some(cycles) = cycles {
+                    if !ebits.is_empty() {
+                        cycles.insert(
+                            (state_key, state_fp),
+                            max_depth.get(),
+                            &ebits,
+                            (next_key, next_fingerprint),
+                        );
                     }
-                    next_fingerprint
-                };
+                }
+                if !generated.insert(next_key) {
+                    is_terminal = false;
+                    continue;
+                }
 
                 // Otherwise further checking is applicable.
                 is_terminal = false;
@@ -376,6 +416,14 @@ where
         self.generated.len()
     }
 
+    fn false_positive_rate(&self) -> Option<f64> {
+        self.generated.false_positive_rate()
+    }
+
+    fn estimated_omitted_states(&self) -> Option<f64> {
+        self.generated.estimated_omitted_states()
+    }
+
     fn max_depth(&self) -> usize {
         self.max_depth.load(Ordering::Relaxed)
     }
@@ -397,7 +445,9 @@ where
     }
 
     fn is_done(&self) -> bool {
-        self.job_broker.is_closed() || self.discoveries.len() == self.model.properties().len()
+        let is_searched = self.cycles.as_ref().is_none_or(|c| c.is_searched());
+        (self.job_broker.is_closed() && is_searched)
+            || self.discoveries.len() == self.model.properties().len()
     }
 }
 
@@ -483,6 +533,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn can_complete_with_bitstate_hashing() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .bitstate(1 << 20)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+        assert_eq!(checker.unique_state_count(), 55);
+        let p = checker.false_positive_rate().unwrap();
+        assert!(0.0 < p && p < 1e-9, "{}", p);
+        let omitted = checker.estimated_omitted_states().unwrap();
+        assert!(0.0 < omitted && omitted < 1e-8, "{}", omitted);
+
+        // A tiny bit array causes most of the state space to be omitted.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .bitstate(64)
+            .spawn_dfs()
+            .join();
+        assert!(checker.unique_state_count() <= 64);
+        assert!(checker.false_positive_rate().unwrap() > 0.25);
+    }
+
+    #[test]
+    #[should_panic(expected = "spawn_dfs does not support checkpoint_to")]
+    fn rejects_checkpoints() {
+        let _ = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .checkpoint_to("unused.json", std::time::Duration::ZERO)
+            .spawn_dfs();
+    }
+
     #[test]
     fn can_apply_symmetry_reduction() {
         use crate::actor::Id;
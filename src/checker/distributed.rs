@@ -0,0 +1,960 @@
+//! Private module for selective re-export.
+
+use crate::checker::{collect_actions, Checker, EventuallyBits, Expectation, Path};
+use crate::{fingerprint, CheckerBuilder, Fingerprint, Model, Property};
+use dashmap::DashMap;
+use nohash_hasher::NoHashHasher;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasherDefault, Hash};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+// Workers own the states whose fingerprints hash to their partition. Each worker connects to
+// every other, and a worker sends the successors owned by another worker directly to that worker,
+// so states never pass through the coordinator after the initial states.
+//
+// The coordinator instead detects termination via the "four counter" method of Mattern: every
+// worker reports how many batches of jobs it has sent and received. Once every worker has
+// reported being idle and the totals balance, the coordinator probes each worker again, and
+// checking is complete if none has sent or received anything since.
+//
+// States are owned and deduplicated by key, which is the fingerprint of the state's
+// representative with `CheckerBuilder::symmetry`. Each worker records the fingerprint and parent
+// key of every state it owns, so the coordinator reconstructs the path to a discovery by asking
+// the owner of each state along the path for its parent.
+
+/// The number of states a worker explores between checking for messages.
+const CHUNK_SIZE: usize = 1500;
+
+/// The longest message that is sent or received, which bounds the memory used to receive one.
+/// Batches of jobs are split as needed to fit.
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+#[derive(Deserialize, Serialize)]
+struct Job<State> {
+    state: State,
+    fingerprint: Fingerprint,
+    key: Fingerprint,
+    /// The key of the state from which this one was generated.
+    parent: Option<Fingerprint>,
+    depth: usize,
+    /// Indices of `eventually` properties not yet satisfied along the path.
+    ebits: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Progress {
+    discoveries: Vec<(String, Fingerprint)>,
+    state_count: usize,
+    unique_state_count: usize,
+    max_depth: usize,
+    /// Batches of jobs sent to other workers.
+    sent: usize,
+    /// Batches of jobs received from the coordinator or other workers.
+    received: usize,
+    is_idle: bool,
+    /// Whether this answers a [`Message::Probe`].
+    is_probe: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+enum Message<State> {
+    /// Assigns a worker its partition of the fingerprint space, indicating the addresses of all
+    /// workers (including itself) in partition order.
+    Start {
+        index: usize,
+        workers: Vec<SocketAddr>,
+    },
+    /// Opens a connection from the worker with the specified index.
+    Peer(usize),
+    Jobs(Vec<Job<State>>),
+    Progress(Progress),
+    /// Asks a worker to report its progress.
+    Probe,
+    /// Asks for the fingerprint and parent key of the state with the specified key.
+    Parent(Fingerprint),
+    ParentOf {
+        key: Fingerprint,
+        fingerprint: Fingerprint,
+        parent: Option<Fingerprint>,
+    },
+    Stop,
+}
+
+fn encode<State: Serialize>(message: &Message<State>) -> std::io::Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    if line.len() > MAX_MESSAGE_LEN {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Message length {} exceeds {}", line.len(), MAX_MESSAGE_LEN),
+        ));
+    }
+    Ok(line)
+}
+
+fn send<State: Serialize>(
+    writer: &mut impl Write,
+    message: &Message<State>,
+) -> std::io::Result<()> {
+    writer.write_all(&encode(message)?)?;
+    writer.flush()
+}
+
+/// Sends a batch of jobs, split across as many messages as needed to respect
+/// [`MAX_MESSAGE_LEN`]. Returns the number of messages sent.
+fn send_jobs<State: Serialize>(
+    writer: &mut impl Write,
+    jobs: Vec<Job<State>>,
+) -> std::io::Result<usize> {
+    let message = Message::Jobs(jobs);
+    match encode(&message) {
+        Ok(line) => {
+            writer.write_all(&line)?;
+            writer.flush()?;
+            Ok(1)
+        }
+        Err(err) => match message {
+            Message::Jobs(mut jobs) if jobs.len() > 1 => {
+                let rest = jobs.split_off(jobs.len() / 2);
+                Ok(send_jobs(writer, jobs)? + send_jobs(writer, rest)?)
+            }
+            _ => Err(err),
+        },
+    }
+}
+
+/// Returns `None` if the connection was closed.
+fn receive<State: DeserializeOwned>(
+    reader: &mut impl BufRead,
+) -> std::io::Result<Option<Message<State>>> {
+    let mut line = String::new();
+    let len = (&mut *reader)
+        .take(MAX_MESSAGE_LEN as u64)
+        .read_line(&mut line)?;
+    if len == 0 {
+        return Ok(None);
+    }
+    if len == MAX_MESSAGE_LEN && !line.ends_with('\n') {
+        return Err(invalid_data("Message exceeds the maximum length"));
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+/// Forwards the messages received over a connection to a channel, tagged with `source`, ending
+/// with `None` once the connection closes.
+fn forward<State>(
+    name: String,
+    source: usize,
+    mut reader: BufReader<TcpStream>,
+    sender: Sender<(usize, Option<Message<State>>)>,
+) -> std::io::Result<()>
+where
+    State: DeserializeOwned + Send + 'static,
+{
+    std::thread::Builder::new().name(name).spawn(move || loop {
+        let message = receive::<State>(&mut reader).unwrap_or(None);
+        let is_closed = message.is_none();
+        if sender.send((source, message)).is_err() || is_closed {
+            return;
+        }
+    })?;
+    Ok(())
+}
+
+/// Returns the key under which a state is owned and deduplicated.
+#[allow(clippy::type_complexity)]
+fn key<State: Hash>(
+    symmetry: Option<fn(&State) -> State>,
+    state: &State,
+    fingerprint: Fingerprint,
+) -> Fingerprint {
+    match symmetry {
+        Some(representative) => crate::fingerprint(&representative(state)),
+        None => fingerprint,
+    }
+}
+
+fn owner(fp: Fingerprint, count: usize) -> usize {
+    (fp.get() % count as u64) as usize
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn lost_connection(index: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::ConnectionAborted,
+        format!("Lost connection to worker {}", index),
+    )
+}
+
+/// Serves a single coordinator. See [`CheckerBuilder::serve_worker`].
+pub(crate) fn serve_worker<M>(
+    options: CheckerBuilder<M>,
+    address: impl ToSocketAddrs,
+) -> std::io::Result<()>
+where
+    M: Model,
+    M::State: Hash + Serialize + DeserializeOwned + Send + 'static,
+{
+    let model = options.model;
+
+    // Accept the coordinator and every other worker, in whatever order they connect, and connect
+    // to every other worker once the coordinator indicates where they are.
+    const COORDINATOR: usize = usize::MAX;
+    let listener = TcpListener::bind(address)?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut coordinator = None;
+    let mut peers = Vec::new();
+    let mut accepted_peers = 0;
+    while coordinator.is_none() || accepted_peers + 1 < peers.len() {
+        let (stream, remote) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        match receive::<M::State>(&mut reader)? {
+            Some(Message::Start { index, workers }) if coordinator.is_none() => {
+                log::debug!("Accepted coordinator. addr={}", remote);
+                if workers.len() <= index {
+                    return Err(invalid_data("Invalid worker index"));
+                }
+                forward(
+                    "worker-coordinator".to_owned(),
+                    COORDINATOR,
+                    reader,
+                    sender.clone(),
+                )?;
+                for (peer, address) in workers.iter().enumerate() {
+                    peers.push(if peer == index {
+                        None
+                    } else {
+                        let mut writer = BufWriter::new(connect(*address)?);
+                        send::<M::State>(&mut writer, &Message::Peer(index))?;
+                        Some(writer)
+                    });
+                }
+                coordinator = Some((index, BufWriter::new(stream)));
+            }
+            Some(Message::Peer(peer)) => {
+                log::debug!("Accepted worker {}. addr={}", peer, remote);
+                forward(format!("worker-{}", peer), peer, reader, sender.clone())?;
+                accepted_peers += 1;
+            }
+            _ => return Err(invalid_data("Expected a start or peer message")),
+        }
+    }
+    drop(sender);
+    let (index, mut writer) = coordinator.unwrap();
+
+    let mut worker = Worker {
+        properties: model.properties(),
+        model,
+        symmetry: options.symmetry,
+        target_max_depth: options.target_max_depth,
+        index,
+        count: peers.len(),
+        generated: HashMap::default(),
+        discovered: HashSet::new(),
+        discoveries: Vec::new(),
+        pending: VecDeque::new(),
+        state_count: 0,
+        max_depth: 0,
+        sent: 0,
+        received: 0,
+    };
+    let mut is_reported_idle = true;
+    loop {
+        // Explore while there are states to explore, and otherwise wait for messages.
+        let message = if worker.pending.is_empty() {
+            if !is_reported_idle {
+                let progress = worker.progress(false);
+                send::<M::State>(&mut writer, &Message::Progress(progress))?;
+                is_reported_idle = true;
+            }
+            match receiver.recv() {
+                Ok(message) => message,
+                Err(_) => return Ok(()),
+            }
+        } else {
+            match receiver.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+                Err(TryRecvError::Empty) => {
+                    for (peer, batch) in worker.explore().into_iter().enumerate() {
+                        if batch.is_empty() {
+                            continue;
+                        }
+                        worker.sent += send_jobs(peers[peer].as_mut().unwrap(), batch)?;
+                    }
+                    let progress = worker.progress(false);
+                    is_reported_idle = progress.is_idle;
+                    send::<M::State>(&mut writer, &Message::Progress(progress))?;
+                    continue;
+                }
+            }
+        };
+        match message {
+            (_, Some(Message::Jobs(jobs))) => {
+                worker.received += 1;
+                is_reported_idle = false;
+                for job in jobs {
+                    if let Entry::Vacant(entry) = worker.generated.entry(job.key) {
+                        entry.insert((job.fingerprint, job.parent));
+                        worker.pending.push_back(job);
+                    }
+                }
+            }
+            (COORDINATOR, Some(Message::Probe)) => {
+                let progress = worker.progress(true);
+                send::<M::State>(&mut writer, &Message::Progress(progress))?;
+            }
+            (COORDINATOR, Some(Message::Parent(key))) => {
+                let (fingerprint, parent) = *worker
+                    .generated
+                    .get(&key)
+                    .ok_or_else(|| invalid_data("Unknown fingerprint"))?;
+                send::<M::State>(
+                    &mut writer,
+                    &Message::ParentOf {
+                        key,
+                        fingerprint,
+                        parent,
+                    },
+                )?;
+            }
+            (COORDINATOR, Some(Message::Stop)) | (COORDINATOR, None) => return Ok(()),
+            (_, None) => {
+                // Another worker stopped. Any failure is also visible to the coordinator,
+                // which stops the others.
+            }
+            _ => return Err(invalid_data("Unexpected message")),
+        }
+    }
+}
+
+/// The states owned by a worker, along with its progress.
+struct Worker<M: Model> {
+    model: M,
+    properties: Vec<Property<M>>,
+    #[allow(clippy::type_complexity)]
+    symmetry: Option<fn(&M::State) -> M::State>,
+    target_max_depth: Option<std::num::NonZeroUsize>,
+    index: usize,
+    count: usize,
+    /// Maps the key of each owned state to its fingerprint and the key of its parent.
+    #[allow(clippy::type_complexity)]
+    generated: HashMap<
+        Fingerprint,
+        (Fingerprint, Option<Fingerprint>),
+        BuildHasherDefault<NoHashHasher<u64>>,
+    >,
+    discovered: HashSet<&'static str>,
+    /// Discoveries not yet reported to the coordinator, identified by the key of the state.
+    discoveries: Vec<(String, Fingerprint)>,
+    pending: VecDeque<Job<M::State>>,
+    state_count: usize,
+    max_depth: usize,
+    sent: usize,
+    received: usize,
+}
+
+impl<M> Worker<M>
+where
+    M: Model,
+    M::State: Hash,
+{
+    fn progress(&mut self, is_probe: bool) -> Progress {
+        Progress {
+            discoveries: std::mem::take(&mut self.discoveries),
+            state_count: self.state_count,
+            unique_state_count: self.generated.len(),
+            max_depth: self.max_depth,
+            sent: self.sent,
+            received: self.received,
+            is_idle: self.pending.is_empty(),
+            is_probe,
+        }
+    }
+
+    /// Explores up to [`CHUNK_SIZE`] pending states, returning the successors owned by each
+    /// other worker.
+    fn explore(&mut self) -> Vec<Vec<Job<M::State>>> {
+        let model = &self.model;
+        let mut batches: Vec<Vec<Job<M::State>>> = (0..self.count).map(|_| Vec::new()).collect();
+        let mut actions = Vec::new();
+        for _ in 0..CHUNK_SIZE {
+            let Job {
+                state,
+                key: state_key,
+                depth,
+                ebits: ebit_indices,
+                ..
+            } = match self.pending.pop_front() {
+                Some(job) => job,
+                None => break,
+            };
+            self.max_depth = self.max_depth.max(depth);
+            if let Some(target_max_depth) = self.target_max_depth {
+                if depth >= target_max_depth.get() {
+                    continue;
+                }
+            }
+
+            let mut ebits = EventuallyBits::new();
+            for i in ebit_indices {
+                ebits.insert(i);
+            }
+            for (i, property) in self.properties.iter().enumerate() {
+                match property {
+                    Property {
+                        expectation: Expectation::Always,
+                        condition: always,
+                        ..
+                    } => {
+                        if !always(model, &state) && self.discovered.insert(property.name) {
+                            self.discoveries
+                                .push((property.name.to_string(), state_key));
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Sometimes,
+                        condition: sometimes,
+                        ..
+                    } => {
+                        if sometimes(model, &state) && self.discovered.insert(property.name) {
+                            self.discoveries
+                                .push((property.name.to_string(), state_key));
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Eventually,
+                        condition: eventually,
+                        ..
+                    } => {
+                        if eventually(model, &state) {
+                            ebits.remove(i);
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => unreachable!("rejected when spawning the checker"),
+                }
+            }
+
+            let mut is_terminal = !collect_actions(model, &state, &mut actions, false);
+            let next_states = model
+                .next_states_for(&state, &mut actions)
+                .into_iter()
+                .flatten();
+            for next_state in next_states {
+                if !model.within_boundary(&next_state) {
+                    continue;
+                }
+                self.state_count += 1;
+                is_terminal = false;
+
+                let next_fingerprint = fingerprint(&next_state);
+                let next_key = key(self.symmetry, &next_state, next_fingerprint);
+                let job = Job {
+                    state: next_state,
+                    fingerprint: next_fingerprint,
+                    key: next_key,
+                    parent: Some(state_key),
+                    depth: depth + 1,
+                    ebits: ebits.iter().collect(),
+                };
+                let next_owner = owner(next_key, self.count);
+                if next_owner != self.index {
+                    batches[next_owner].push(job);
+                } else if let Entry::Vacant(entry) = self.generated.entry(next_key) {
+                    entry.insert((next_fingerprint, Some(state_key)));
+                    self.pending.push_back(job);
+                }
+            }
+            if is_terminal {
+                for (i, property) in self.properties.iter().enumerate() {
+                    if ebits.contains(i) && self.discovered.insert(property.name) {
+                        self.discoveries
+                            .push((property.name.to_string(), state_key));
+                    }
+                }
+            }
+        }
+        batches
+    }
+}
+
+pub(crate) struct DistributedChecker<M: Model> {
+    model: Arc<M>,
+    handles: Vec<JoinHandle<()>>,
+    state_count: Arc<AtomicUsize>,
+    unique_state_count: Arc<AtomicUsize>,
+    max_depth: Arc<AtomicUsize>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+    error: Arc<OnceLock<std::io::Error>>,
+    is_done: Arc<AtomicBool>,
+}
+
+/// The counters reported by a worker, for detecting termination.
+#[derive(Clone, Copy, PartialEq)]
+struct Counters {
+    sent: usize,
+    received: usize,
+    is_idle: bool,
+}
+
+impl<M> DistributedChecker<M>
+where
+    M: Model + Send + Sync + 'static,
+    M::State: Hash + Send + Serialize + DeserializeOwned + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>, workers: Vec<SocketAddr>) -> Self {
+        assert!(!workers.is_empty(), "At least one worker is required");
+        let model = Arc::new(options.model);
+        let state_count = Arc::new(AtomicUsize::new(0));
+        let unique_state_count = Arc::new(AtomicUsize::new(0));
+        let max_depth = Arc::new(AtomicUsize::new(0));
+        let discoveries = Arc::new(DashMap::default());
+        let error = Arc::new(OnceLock::new());
+        let is_done = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let coordinator = Coordinator {
+                model: Arc::clone(&model),
+                symmetry: options.symmetry,
+                target_state_count: options.target_state_count,
+                finish_when: options.finish_when,
+                close_at: options.timeout.map(|t| SystemTime::now() + t),
+                state_count: Arc::clone(&state_count),
+                unique_state_count: Arc::clone(&unique_state_count),
+                max_depth: Arc::clone(&max_depth),
+                discoveries: Arc::clone(&discoveries),
+            };
+            let error = Arc::clone(&error);
+            let is_done = Arc::clone(&is_done);
+            std::thread::Builder::new()
+                .name("coordinator".to_owned())
+                .spawn(move || {
+                    let mut writers = Vec::new();
+                    if let Err(err) = coordinator.run(&workers, &mut writers) {
+                        log::error!("Distributed checking failed: {}", err);
+                        let _ = error.set(err);
+                    }
+                    for writer in &mut writers {
+                        let _ = send::<M::State>(writer, &Message::Stop);
+                    }
+                    is_done.store(true, Ordering::Relaxed);
+                })
+                .expect("Failed to spawn a thread")
+        };
+
+        DistributedChecker {
+            model,
+            handles: vec![handle],
+            state_count,
+            unique_state_count,
+            max_depth,
+            discoveries,
+            error,
+            is_done,
+        }
+    }
+}
+
+struct Coordinator<M: Model> {
+    model: Arc<M>,
+    #[allow(clippy::type_complexity)]
+    symmetry: Option<fn(&M::State) -> M::State>,
+    target_state_count: Option<std::num::NonZeroUsize>,
+    finish_when: crate::HasDiscoveries,
+    close_at: Option<SystemTime>,
+    state_count: Arc<AtomicUsize>,
+    unique_state_count: Arc<AtomicUsize>,
+    max_depth: Arc<AtomicUsize>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+}
+
+impl<M> Coordinator<M>
+where
+    M: Model,
+    M::State: Hash + Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Runs a check to completion, leaving the connection to each worker in `writers` so that
+    /// the workers can be stopped even if checking fails.
+    fn run(
+        &self,
+        workers: &[SocketAddr],
+        writers: &mut Vec<BufWriter<TcpStream>>,
+    ) -> std::io::Result<()> {
+        let model = &*self.model;
+        let properties = model.properties();
+        let count = workers.len();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for (index, address) in workers.iter().enumerate() {
+            let stream = connect(*address)?;
+            let reader = BufReader::new(stream.try_clone()?);
+            let mut writer = BufWriter::new(stream);
+            send::<M::State>(
+                &mut writer,
+                &Message::Start {
+                    index,
+                    workers: workers.to_vec(),
+                },
+            )?;
+            writers.push(writer);
+            forward::<M::State>(
+                format!("coordinator-{}", index),
+                index,
+                reader,
+                sender.clone(),
+            )?;
+        }
+        drop(sender);
+
+        let mut ebits = Vec::new();
+        for (i, p) in properties.iter().enumerate() {
+            if let Expectation::Eventually = p.expectation {
+                ebits.push(i);
+            }
+        }
+        let mut batches: Vec<Vec<Job<M::State>>> = (0..count).map(|_| Vec::new()).collect();
+        let mut init_state_count = 0;
+        for state in model.init_states() {
+            if !model.within_boundary(&state) {
+                continue;
+            }
+            init_state_count += 1;
+            let fingerprint = fingerprint(&state);
+            let key = key(self.symmetry, &state, fingerprint);
+            batches[owner(key, count)].push(Job {
+                fingerprint,
+                key,
+                state,
+                parent: None,
+                depth: 1,
+                ebits: ebits.clone(),
+            });
+        }
+        self.state_count.store(init_state_count, Ordering::Relaxed);
+        let mut init_batch_count = 0;
+        for (writer, batch) in writers.iter_mut().zip(batches) {
+            if batch.is_empty() {
+                continue;
+            }
+            init_batch_count += send_jobs(writer, batch)?;
+        }
+
+        // Workers are idle until they receive jobs, so the counters start balanced only if there
+        // are no initial states.
+        let mut latest = vec![
+            Counters {
+                sent: 0,
+                received: 0,
+                is_idle: true,
+            };
+            count
+        ];
+        let mut probe: Option<(Vec<Counters>, Vec<Option<Counters>>)> = None;
+        let mut discovered = BTreeMap::new();
+        let mut unique_state_counts = vec![0; count];
+        let mut worker_state_counts = vec![0; count];
+        loop {
+            let is_balanced = |counters: &[Counters]| {
+                let sent = init_batch_count + counters.iter().map(|c| c.sent).sum::<usize>();
+                let received = counters.iter().map(|c| c.received).sum::<usize>();
+                sent == received && counters.iter().all(|c| c.is_idle)
+            };
+            if probe.is_none() && is_balanced(&latest) {
+                for writer in writers.iter_mut() {
+                    send::<M::State>(writer, &Message::Probe)?;
+                }
+                probe = Some((latest.clone(), vec![None; count]));
+            }
+
+            let received = match self.close_at {
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                Some(close_at) => {
+                    let remaining = close_at
+                        .duration_since(SystemTime::now())
+                        .unwrap_or(Duration::ZERO);
+                    receiver.recv_timeout(remaining)
+                }
+            };
+            let (index, progress) = match received {
+                Ok((index, Some(Message::Progress(progress)))) => (index, progress),
+                Ok((index, None)) => return Err(lost_connection(index)),
+                Ok((_, Some(_))) => return Err(invalid_data("Expected progress")),
+                Err(RecvTimeoutError::Timeout) => {
+                    log::debug!("Reached timeout, triggering shutdown");
+                    break;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "Lost connection to workers",
+                    ))
+                }
+            };
+
+            worker_state_counts[index] = progress.state_count;
+            unique_state_counts[index] = progress.unique_state_count;
+            self.state_count.store(
+                init_state_count + worker_state_counts.iter().sum::<usize>(),
+                Ordering::Relaxed,
+            );
+            self.unique_state_count
+                .store(unique_state_counts.iter().sum(), Ordering::Relaxed);
+            self.max_depth
+                .fetch_max(progress.max_depth, Ordering::Relaxed);
+            for (name, fp) in progress.discoveries {
+                if let Some(property) = properties.iter().find(|p| p.name == name) {
+                    discovered.entry(property.name).or_insert(fp);
+                }
+            }
+            if self
+                .finish_when
+                .matches(&discovered.keys().copied().collect(), &properties)
+            {
+                log::debug!("Discovery complete. Shutting down...");
+                break;
+            }
+            if let Some(target_state_count) = self.target_state_count {
+                if target_state_count.get() <= self.state_count.load(Ordering::Relaxed) {
+                    log::debug!("Reached target state count. Shutting down...");
+                    break;
+                }
+            }
+
+            latest[index] = Counters {
+                sent: progress.sent,
+                received: progress.received,
+                is_idle: progress.is_idle,
+            };
+            if progress.is_probe {
+                let (expected, replies) = probe.as_mut().unwrap();
+                replies[index] = Some(latest[index]);
+                if replies.iter().all(Option::is_some) {
+                    // Complete if no worker sent or received jobs since the counters balanced.
+                    if replies
+                        .iter()
+                        .zip(expected.iter())
+                        .all(|(r, e)| *r == Some(*e))
+                    {
+                        log::debug!("All workers idle. Shutting down...");
+                        break;
+                    }
+                    probe = None;
+                }
+            }
+        }
+
+        for (name, key) in discovered {
+            let mut fingerprints = VecDeque::new();
+            let mut next_key = key;
+            loop {
+                send::<M::State>(
+                    &mut writers[owner(next_key, count)],
+                    &Message::Parent(next_key),
+                )?;
+                let (fingerprint, parent) = loop {
+                    match receiver.recv() {
+                        Ok((
+                            _,
+                            Some(Message::ParentOf {
+                                key,
+                                fingerprint,
+                                parent,
+                            }),
+                        )) if key == next_key => break (fingerprint, parent),
+                        Ok((_, Some(_))) => continue,
+                        Ok((index, None)) => return Err(lost_connection(index)),
+                        Err(_) => return Err(lost_connection(owner(next_key, count))),
+                    }
+                };
+                fingerprints.push_front(fingerprint);
+                match parent {
+                    Some(parent) => next_key = parent,
+                    None => break,
+                }
+            }
+            self.discoveries.insert(name, Vec::from(fingerprints));
+        }
+        Ok(())
+    }
+}
+
+/// Connects to a worker, retrying for a while in case the worker is still starting.
+fn connect(address: SocketAddr) -> std::io::Result<TcpStream> {
+    let mut attempts = 0;
+    loop {
+        match TcpStream::connect(address) {
+            Ok(stream) => {
+                stream.set_nodelay(true)?;
+                return Ok(stream);
+            }
+            Err(err) if attempts < 50 => {
+                log::debug!("Unable to connect to worker. addr={}: {}", address, err);
+                attempts += 1;
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+impl<M> Checker<M> for DistributedChecker<M>
+where
+    M: Model,
+    M::State: Hash,
+{
+    fn model(&self) -> &M {
+        &self.model
+    }
+
+    fn state_count(&self) -> usize {
+        self.state_count.load(Ordering::Relaxed)
+    }
+
+    fn unique_state_count(&self) -> usize {
+        self.unique_state_count.load(Ordering::Relaxed)
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth.load(Ordering::Relaxed)
+    }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.discoveries
+            .iter()
+            .map(|mapref| {
+                (
+                    <&'static str>::clone(mapref.key()),
+                    Path::from_fingerprints(self.model(), VecDeque::from(mapref.value().clone())),
+                )
+            })
+            .collect()
+    }
+
+    fn handles(&mut self) -> Vec<JoinHandle<()>> {
+        std::mem::take(&mut self.handles)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_done.load(Ordering::Relaxed)
+    }
+
+    fn error(&self) -> Option<&std::io::Error> {
+        self.error.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::linear_equation_solver::*;
+    use crate::*;
+    use std::net::{SocketAddr, TcpListener};
+
+    fn spawn_workers(
+        model: &LinearEquation,
+        count: usize,
+        configure: fn(CheckerBuilder<LinearEquation>) -> CheckerBuilder<LinearEquation>,
+    ) -> Vec<SocketAddr> {
+        (0..count)
+            .map(|_| {
+                // Find an available port.
+                let address = TcpListener::bind("127.0.0.1:0")
+                    .unwrap()
+                    .local_addr()
+                    .unwrap();
+                let model = LinearEquation { ..*model };
+                std::thread::spawn(move || {
+                    configure(model.checker()).serve_worker(address).unwrap()
+                });
+                address
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_complete_by_enumerating_all_states() {
+        let model = LinearEquation { a: 2, b: 4, c: 7 };
+        let workers = spawn_workers(&model, 3, |c| c);
+        let checker = model.checker().spawn_distributed(workers).join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.unique_state_count(), 256 * 256);
+    }
+
+    #[test]
+    fn can_complete_by_eliminating_properties() {
+        let model = LinearEquation { a: 2, b: 10, c: 14 };
+        let workers = spawn_workers(&model, 2, |c| c);
+        let checker = model.checker().spawn_distributed(workers).join();
+        checker.assert_properties();
+        // Workers race, so any path to a solution may be found.
+        let path = checker.discovery("solvable").unwrap();
+        let solvable = checker.model().property("solvable").condition;
+        assert!(solvable(checker.model(), path.last_state()));
+    }
+
+    #[test]
+    fn can_apply_symmetry_reduction() {
+        fn sorted(&(x, y): &(u8, u8)) -> (u8, u8) {
+            (x.min(y), x.max(y))
+        }
+        let model = LinearEquation { a: 2, b: 2, c: 7 };
+        let workers = spawn_workers(&model, 2, |c| c.symmetry_fn(sorted));
+        let checker = model
+            .checker()
+            .symmetry_fn(sorted)
+            .spawn_distributed(workers)
+            .join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.unique_state_count(), 256 * 257 / 2);
+
+        // Paths follow the states that were explored rather than their representatives.
+        let model = LinearEquation { a: 2, b: 2, c: 14 };
+        let workers = spawn_workers(&model, 2, |c| c.symmetry_fn(sorted));
+        let checker = model
+            .checker()
+            .symmetry_fn(sorted)
+            .spawn_distributed(workers)
+            .join();
+        let path = checker.discovery("solvable").unwrap();
+        let solvable = checker.model().property("solvable").condition;
+        assert!(solvable(checker.model(), path.last_state()));
+    }
+
+    #[test]
+    #[should_panic(expected = "spawn_distributed does not support visitor")]
+    fn rejects_visitors() {
+        let _ = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .visitor(|_: Path<_, _>| {})
+            .spawn_distributed(Vec::new());
+    }
+
+    #[test]
+    fn stops_with_an_error_upon_losing_a_worker() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || drop(listener.accept().unwrap()));
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .spawn_distributed(vec![address])
+            .join();
+        assert!(checker.is_done());
+        assert!(checker.error().is_some());
+        assert_eq!(checker.discovery("solvable"), None);
+    }
+}
@@ -322,7 +322,7 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::actor::Timers;
+    use crate::actor::{ClockSkew, Membership, Timers};
     use crate::test_util::binary_clock::*;
     use lazy_static::lazy_static;
 
@@ -424,9 +424,18 @@ mod test {
                     outcome: None,
                     state: Some(ActorModelState {
                         actor_states: vec![Arc::new(0), Arc::new(0)],
+                        persisted: vec![None; 2],
                         history: (0, 1),
                         timers_set: vec![Timers::new(); 2],
                         crashed: vec![false; 2],
+                        recoveries: 0,
+                        partition: None,
+                        partitions: 0,
+                        byzantine: vec![false; 2],
+                        now: Duration::ZERO,
+                        clock_skews: vec![ClockSkew::Synchronized; 2],
+                        membership: vec![Membership::Member; 2],
+                        sleep: Vec::new(),
                         network: Network::new_unordered_nonduplicating([
                             Envelope { src: Id::from(0), dst: Id::from(1), msg: Ping(0) },
                         ]),
@@ -448,9 +457,18 @@ mod test {
                 use crate::actor::actor_test_util::ping_pong::{PingPongActor, PingPongHistory};
                 let fp = fingerprint(&ActorModelState::<PingPongActor, PingPongHistory> {
                     actor_states: vec![Arc::new(0), Arc::new(0)],
+                    persisted: vec![None; 2],
                     history: (0, 1),
                     timers_set: vec![Timers::new(); 2],
                     crashed: vec![false; 2],
+                    recoveries: 0,
+                    partition: None,
+                    partitions: 0,
+                    byzantine: vec![false; 2],
+                    now: Duration::ZERO,
+                    clock_skews: vec![ClockSkew::Synchronized; 2],
+                    membership: vec![Membership::Member; 2],
+                    sleep: Vec::new(),
                     network: Network::new_unordered_nonduplicating([Envelope {
                         src: Id::from(0),
                         dst: Id::from(1),
@@ -469,9 +487,18 @@ mod test {
                 outcome: Some("DROP: Envelope { src: Id(0), dst: Id(1), msg: Ping(0) }".to_string()),
                 state: Some(ActorModelState {
                     actor_states: vec![Arc::new(0), Arc::new(0)],
+                    persisted: vec![None; 2],
                     history: (0, 1),
                     timers_set: vec![Timers::new(); 2],
                     crashed: vec![false; 2],
+                    recoveries: 0,
+                    partition: None,
+                    partitions: 0,
+                    byzantine: vec![false; 2],
+                    now: Duration::ZERO,
+                    clock_skews: vec![ClockSkew::Synchronized; 2],
+                    membership: vec![Membership::Member; 2],
+                    sleep: Vec::new(),
                     network: Network::new_unordered_nonduplicating([]),
                 }),
                 properties: vec![
@@ -494,9 +521,18 @@ mod test {
                         Arc::new(0),
                         Arc::new(1),
                     ],
+                    persisted: vec![None; 2],
                     history: (1, 2),
                     timers_set: vec![Timers::new(); 2],
                     crashed: vec![false; 2],
+                    recoveries: 0,
+                    partition: None,
+                    partitions: 0,
+                    byzantine: vec![false; 2],
+                    now: Duration::ZERO,
+                    clock_skews: vec![ClockSkew::Synchronized; 2],
+                    membership: vec![Membership::Member; 2],
+                    sleep: Vec::new(),
                     network: Network::new_unordered_nonduplicating([
                         Envelope { src: Id::from(1), dst: Id::from(0), msg: Pong(0) },
                     ]),
@@ -0,0 +1,531 @@
+//! Private module for selective re-export.
+
+use crate::checker::{collect_actions, Checker, EventuallyBits, Expectation, Path};
+use crate::has_discoveries::HasDiscoveries;
+use crate::{fingerprint, CheckerBuilder, CheckerVisitor, Fingerprint, Model, Property};
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+pub(crate) struct IddfsChecker<M: Model> {
+    // Immutable state.
+    model: Arc<M>,
+    handles: Vec<std::thread::JoinHandle<()>>,
+
+    // Mutable state.
+    state_count: Arc<AtomicUsize>,
+    unique_state_count: Arc<AtomicUsize>,
+    max_depth: Arc<AtomicUsize>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+}
+
+/// Why a depth-bounded iteration ended.
+enum Iteration {
+    /// States were left unexplored beyond the depth bound.
+    CutOff,
+    /// All reachable states are within the depth bound.
+    Complete,
+    /// Checking should stop (e.g. because of [`HasDiscoveries`] or a timeout).
+    Stop,
+}
+
+/// Settings that remain constant across iterations.
+struct Limits {
+    finish_when: HasDiscoveries,
+    target_state_count: Option<NonZeroUsize>,
+    close_at: Option<SystemTime>,
+    init_ebits: EventuallyBits,
+}
+
+impl<M> IddfsChecker<M>
+where
+    M: Model + Send + Sync + 'static,
+    M::State: Hash + Send + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>) -> Self {
+        let model = Arc::new(options.model);
+        let symmetry = options.symmetry;
+        let target_max_depth = options.target_max_depth;
+        let close_at = options.timeout.map(|t| SystemTime::now() + t);
+        let visitor = options.visitor;
+        let properties = model.properties();
+
+        let state_count = Arc::new(AtomicUsize::new(0));
+        let unique_state_count = Arc::new(AtomicUsize::new(0));
+        let max_depth = Arc::new(AtomicUsize::new(0));
+        let discoveries = Arc::new(DashMap::default());
+        let limits = Limits {
+            finish_when: options.finish_when,
+            target_state_count: options.target_state_count,
+            close_at,
+            init_ebits: {
+                let mut ebits = EventuallyBits::new();
+                for (i, p) in properties.iter().enumerate() {
+                    if let Property {
+                        expectation: Expectation::Eventually,
+                        ..
+                    } = p
+                    {
+                        ebits.insert(i);
+                    }
+                }
+                ebits
+            },
+        };
+
+        let handle = {
+            let model = Arc::clone(&model);
+            let state_count = Arc::clone(&state_count);
+            let unique_state_count = Arc::clone(&unique_state_count);
+            let max_depth = Arc::clone(&max_depth);
+            let discoveries = Arc::clone(&discoveries);
+            std::thread::Builder::new()
+                .name("checker-0".to_owned())
+                .spawn(move || {
+                    log::debug!("0: Thread started.");
+                    let mut cache = Cache::new();
+                    let mut depth_bound = 1;
+                    loop {
+                        log::debug!("0: Checking to depth {}.", depth_bound);
+                        let iteration = Self::check_to_depth(
+                            &model,
+                            &properties,
+                            depth_bound,
+                            &limits,
+                            &mut cache,
+                            &state_count,
+                            &unique_state_count,
+                            &max_depth,
+                            &discoveries,
+                            &visitor,
+                            symmetry,
+                        );
+                        match iteration {
+                            Iteration::CutOff => {}
+                            Iteration::Complete => {
+                                log::debug!("0: All states checked. Shutting down...");
+                                return;
+                            }
+                            Iteration::Stop => {
+                                log::debug!("0: Stopping early. Shutting down...");
+                                return;
+                            }
+                        }
+                        depth_bound += 1;
+                        if let Some(target_max_depth) = target_max_depth {
+                            if depth_bound >= target_max_depth.get() {
+                                log::debug!("0: Reached max depth. Shutting down...");
+                                return;
+                            }
+                        }
+                    }
+                })
+                .expect("Failed to spawn a thread")
+        };
+        IddfsChecker {
+            model,
+            handles: vec![handle],
+            state_count,
+            unique_state_count,
+            max_depth,
+            discoveries,
+        }
+    }
+
+    /// Checks every state reachable within `depth_bound` steps, counting initial states as depth
+    /// one, via a depth-first search that only remembers the current path and a [`Cache`]. A
+    /// state is revisited if reached via a shorter path than the one with which it was cached, so
+    /// each is checked at its minimal depth, which means that any discovery is made via a path of
+    /// minimal length.
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::type_complexity)]
+    fn check_to_depth(
+        model: &M,
+        properties: &[Property<M>],
+        depth_bound: usize,
+        limits: &Limits,
+        cache: &mut Cache,
+        state_count: &AtomicUsize,
+        unique_state_count: &AtomicUsize,
+        global_max_depth: &AtomicUsize,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+        symmetry: Option<fn(&M::State) -> M::State>,
+    ) -> Iteration {
+        let key = |state: &M::State| match symmetry {
+            Some(representative) => fingerprint(&representative(state)),
+            None => fingerprint(state),
+        };
+        cache.clear(depth_bound);
+
+        // The current path, and states yet to be checked from each of its states.
+        let mut path: Vec<Frame<M::State>> = Vec::new();
+        let mut init_pending: Vec<M::State> = model
+            .init_states()
+            .into_iter()
+            .filter(|s| model.within_boundary(s))
+            .collect();
+        if depth_bound == 1 {
+            state_count.fetch_add(init_pending.len(), Ordering::Relaxed);
+        }
+        init_pending.reverse(); // so that initial states are checked in order
+        let mut actions = Vec::new();
+        loop {
+            let (state, ebits) = match path.last_mut() {
+                None => match init_pending.pop() {
+                    None => break,
+                    Some(state) => (state, limits.init_ebits.clone()),
+                },
+                Some(frame) => match frame.pending.pop() {
+                    None => {
+                        path.pop();
+                        continue;
+                    }
+                    Some(state) => (state, frame.ebits.clone()),
+                },
+            };
+            let state_key = key(&state);
+            let depth = path.len() + 1;
+            if path.iter().any(|frame| frame.key == state_key) {
+                continue; // returned to the path
+            }
+            let min_depth = match cache.check(state_key, depth) {
+                None => continue, // already checked via a path at least as short
+                Some(min_depth) => min_depth,
+            };
+            // States checked at a shallower depth, even via another path, were checked previously.
+            let is_new = min_depth == depth_bound;
+            if is_new {
+                unique_state_count.fetch_add(1, Ordering::Relaxed);
+            }
+            if depth > global_max_depth.load(Ordering::Relaxed) {
+                global_max_depth.store(depth, Ordering::Relaxed);
+            }
+            let fingerprints = || {
+                let mut fingerprints: Vec<_> = path.iter().map(|frame| frame.fingerprint).collect();
+                fingerprints.push(fingerprint(&state));
+                fingerprints
+            };
+
+            if is_new {
+                if let Some(visitor) = visitor {
+                    visitor.visit(model, Path::from_fingerprints(model, fingerprints().into()));
+                }
+            }
+            let mut ebits = ebits;
+            for (i, property) in properties.iter().enumerate() {
+                if discoveries.contains_key(property.name) {
+                    continue;
+                }
+                match property {
+                    Property {
+                        expectation: Expectation::Always,
+                        condition: always,
+                        ..
+                    } => {
+                        if is_new && !always(model, &state) {
+                            discoveries.insert(property.name, fingerprints());
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Sometimes,
+                        condition: sometimes,
+                        ..
+                    } => {
+                        if is_new && sometimes(model, &state) {
+                            discoveries.insert(property.name, fingerprints());
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Eventually,
+                        condition: eventually,
+                        ..
+                    } => {
+                        if eventually(model, &state) {
+                            ebits.remove(i);
+                        }
+                    }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => unreachable!("rejected when spawning the checker"),
+                }
+            }
+            if limits
+                .finish_when
+                .matches(&discoveries.iter().map(|r| *r.key()).collect(), properties)
+            {
+                return Iteration::Stop;
+            }
+            if let Some(target_state_count) = limits.target_state_count {
+                if target_state_count.get() <= state_count.load(Ordering::Relaxed) {
+                    return Iteration::Stop;
+                }
+            }
+            if let Some(close_at) = limits.close_at {
+                if close_at <= SystemTime::now() {
+                    return Iteration::Stop;
+                }
+            }
+
+            // Collect newly generated states (to be checked in the order of their actions), or
+            // note whether any beyond the bound remain to be checked.
+            let mut is_terminal = !collect_actions(model, &state, &mut actions, false);
+            let mut pending = Vec::new();
+            for next_state in model
+                .next_states_for(&state, &mut actions)
+                .into_iter()
+                .flatten()
+            {
+                if !model.within_boundary(&next_state) {
+                    continue;
+                }
+                is_terminal = false;
+                if depth == depth_bound {
+                    if is_new {
+                        // Counted when first generated rather than in each iteration.
+                        state_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    cache.reach_beyond(key(&next_state));
+                } else {
+                    pending.push(next_state);
+                }
+            }
+            if is_terminal {
+                for (i, property) in properties.iter().enumerate() {
+                    if ebits.contains(i) && !discoveries.contains_key(property.name) {
+                        discoveries.insert(property.name, fingerprints());
+                    }
+                }
+            }
+            if depth < depth_bound {
+                pending.reverse();
+                path.push(Frame {
+                    fingerprint: fingerprint(&state),
+                    key: state_key,
+                    ebits,
+                    pending,
+                });
+            }
+        }
+
+        if cache.is_complete() {
+            Iteration::Complete
+        } else {
+            Iteration::CutOff
+        }
+    }
+}
+
+/// A state on the current path of a depth-bounded iteration.
+struct Frame<State> {
+    fingerprint: Fingerprint,
+    /// Distinct from the fingerprint with [`CheckerBuilder::symmetry`].
+    key: Fingerprint,
+    ebits: EventuallyBits,
+    /// States that follow this one and remain to be checked, in reverse order.
+    pending: Vec<State>,
+}
+
+/// Remembers the depth at which states were checked during an iteration, so that a state reached
+/// via multiple paths is not checked again via a path that is no shorter, along with the states
+/// just beyond the depth bound that have yet to be checked. The minimal depth at which each state
+/// was checked is kept across iterations, so that a state reached via a longer path is not counted
+/// again. Its size is fixed, and a state is forgotten when another with the same slot is recorded,
+/// so larger state spaces cause more states to be checked (and counted) again.
+struct Cache {
+    /// The key of each state, the minimal depth at which it was checked, and the depth at which it
+    /// was checked (or reached beyond the bound) during this iteration.
+    #[allow(clippy::type_complexity)]
+    slots: Vec<Option<(Fingerprint, usize, Option<usize>)>>,
+    depth_bound: usize,
+    /// Whether a state beyond the bound was forgotten before being checked.
+    is_beyond_forgotten: bool,
+}
+
+impl Cache {
+    /// The number of states that can be remembered, which occupy 16 bytes each.
+    const SIZE: usize = 1 << 16;
+
+    fn new() -> Self {
+        Cache {
+            slots: vec![None; Self::SIZE],
+            depth_bound: 0,
+            is_beyond_forgotten: false,
+        }
+    }
+
+    fn clear(&mut self, depth_bound: usize) {
+        for (_, _, depth) in self.slots.iter_mut().flatten() {
+            *depth = None;
+        }
+        self.depth_bound = depth_bound;
+        self.is_beyond_forgotten = false;
+    }
+
+    /// Records that a state is checked at a particular depth, returning the minimal depth at which
+    /// it was checked in any iteration, unless it was already checked at that depth or less during
+    /// this iteration, in which case this returns [`None`].
+    fn check(&mut self, key: Fingerprint, depth: usize) -> Option<usize> {
+        let slot = &mut self.slots[(key.get() % Self::SIZE as u64) as usize];
+        let min_depth = match *slot {
+            Some((k, _, Some(d))) if k == key && d <= depth => return None,
+            Some((k, min_depth, _)) if k == key => min_depth.min(depth),
+            Some((_, _, Some(d))) if d > self.depth_bound => {
+                self.is_beyond_forgotten = true;
+                depth
+            }
+            _ => depth,
+        };
+        *slot = Some((key, min_depth, Some(depth)));
+        Some(min_depth)
+    }
+
+    /// Records that a state was reached just beyond the depth bound.
+    fn reach_beyond(&mut self, key: Fingerprint) {
+        self.check(key, self.depth_bound + 1);
+    }
+
+    /// Indicates whether every state reached just beyond the depth bound was checked within it.
+    fn is_complete(&self) -> bool {
+        !self.is_beyond_forgotten
+            && self
+                .slots
+                .iter()
+                .all(|slot| !matches!(slot, Some((_, _, Some(d))) if *d > self.depth_bound))
+    }
+}
+
+impl<M> Checker<M> for IddfsChecker<M>
+where
+    M: Model,
+    M::State: Hash,
+{
+    fn model(&self) -> &M {
+        &self.model
+    }
+
+    fn state_count(&self) -> usize {
+        self.state_count.load(Ordering::Relaxed)
+    }
+
+    fn unique_state_count(&self) -> usize {
+        self.unique_state_count.load(Ordering::Relaxed)
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth.load(Ordering::Relaxed)
+    }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.discoveries
+            .iter()
+            .map(|mapref| {
+                (
+                    <&'static str>::clone(mapref.key()),
+                    Path::from_fingerprints(self.model(), VecDeque::from(mapref.value().clone())),
+                )
+            })
+            .collect()
+    }
+
+    fn handles(&mut self) -> Vec<JoinHandle<()>> {
+        std::mem::take(&mut self.handles)
+    }
+
+    fn is_done(&self) -> bool {
+        self.handles.iter().all(|h| h.is_finished())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::linear_equation_solver::*;
+    use crate::*;
+
+    #[test]
+    fn visits_states_in_order_of_depth() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .visitor(recorder)
+            .spawn_iddfs()
+            .join();
+        let depth = |&(x, y): &(u8, u8)| x as usize + y as usize;
+        let depths: Vec<_> = accessor().iter().map(depth).collect();
+        let mut sorted = depths.clone();
+        sorted.sort();
+        assert_eq!(depths, sorted);
+    }
+
+    #[test]
+    fn can_complete_by_eliminating_properties() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .spawn_iddfs()
+            .join();
+        checker.assert_properties();
+        assert_eq!(checker.unique_state_count(), 8); // 1 + 2 + 3 + 2
+
+        // Unlike DFS, the shortest example is found.
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX, Guess::IncreaseX, Guess::IncreaseY]
+        );
+    }
+
+    #[test]
+    fn can_complete_with_cycles() {
+        use crate::test_util::binary_clock::*;
+        let checker = BinaryClock.checker().spawn_iddfs().join();
+        assert!(checker.is_done());
+        checker.assert_properties();
+        assert_eq!(checker.unique_state_count(), 2);
+        assert_eq!(checker.max_depth(), 1);
+    }
+
+    #[test]
+    fn respects_target_max_depth() {
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }
+            .checker()
+            .target_max_depth(4)
+            .spawn_iddfs()
+            .join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.max_depth(), 3);
+        assert_eq!(checker.unique_state_count(), 6); // 1 + 2 + 3
+    }
+
+    #[test]
+    fn counts_states_reached_via_longer_paths_once() {
+        use crate::test_util::dgraph::DGraph;
+        // 2 is reached at depth 2 and, via 1, at depth 3.
+        let model = || {
+            DGraph::with_property(Property::always("unused", |_, _| true))
+                .with_path(vec![0, 1, 2, 3, 4])
+                .with_path(vec![0, 2])
+        };
+        let bfs = model().checker().spawn_bfs().join();
+        let iddfs = model().checker().spawn_iddfs().join();
+        assert_eq!(bfs.unique_state_count(), 5);
+        assert_eq!(bfs.state_count(), 6);
+        assert_eq!(iddfs.unique_state_count(), bfs.unique_state_count());
+        assert_eq!(iddfs.state_count(), bfs.state_count());
+        assert_eq!(iddfs.max_depth(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "spawn_iddfs does not support liveness checking")]
+    fn rejects_liveness_checking() {
+        let _ = LinearEquation { a: 2, b: 10, c: 14 }
+            .checker()
+            .liveness()
+            .spawn_iddfs();
+    }
+}
@@ -0,0 +1,927 @@
+//! Private module for checking [`Expectation::Ltl`] and [`Expectation::Eventually`] properties
+//! over cycles.
+//!
+//! Unlike safety properties, which can be checked one state at a time, temporal properties
+//! require the checker to reason about infinite behaviors. This module builds the reachable state
+//! graph explicitly, takes its product with a Büchi automaton for the negated formula, and then
+//! searches that product for an accepting cycle (a "lasso") that is fair with respect to
+//! [`Model::fairness`]. Any such lasso is a counterexample.
+//!
+//! It also tracks the cycles through which [`CheckerBuilder::sound_eventually`] checks
+//! [`Expectation::Eventually`] properties during the main search.
+//!
+//! [`CheckerBuilder::sound_eventually`]: crate::CheckerBuilder::sound_eventually
+
+use crate::checker::ltl::Buchi;
+use crate::checker::EventuallyBits;
+use crate::{fingerprint, Expectation, Fairness, Fingerprint, Ltl, Model, Property};
+use dashmap::DashMap;
+use id_set::IdSet;
+use nohash_hasher::NoHashHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasherDefault, Hash};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// The [`CheckerBuilder`](crate::CheckerBuilder) settings that bound the exploration of the state
+/// graph, just as they bound the main search.
+#[allow(clippy::type_complexity)]
+pub(crate) struct Bounds<M: Model> {
+    pub(crate) symmetry: Option<fn(&M::State) -> M::State>,
+    pub(crate) target_state_count: Option<NonZeroUsize>,
+    pub(crate) target_max_depth: Option<NonZeroUsize>,
+    pub(crate) close_at: Option<SystemTime>,
+}
+
+impl<M: Model> Bounds<M> {
+    /// The key under which a state is recorded.
+    fn key(&self, state: &M::State) -> Fingerprint
+    where
+        M::State: Hash,
+    {
+        match self.symmetry {
+            Some(representative) => fingerprint(&representative(state)),
+            None => fingerprint(state),
+        }
+    }
+}
+
+/// Searches for counterexamples to the model's [`Expectation::Ltl`] properties (and its
+/// [`Expectation::Eventually`] properties if `include_eventually`), recording each as a sequence
+/// of fingerprints whose final fingerprint repeats an earlier one (the start of the cycle), or as
+/// a path ending in a terminal state.
+///
+/// States beyond the [`Bounds`] are left unexplored rather than treated as terminal, so only
+/// cycles among explored states are reported.
+pub(crate) fn check_liveness<M>(
+    model: &M,
+    properties: &[Property<M>],
+    include_eventually: bool,
+    bounds: &Bounds<M>,
+    discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+) where
+    M: Model,
+    M::State: Hash,
+{
+    let mut atoms = Vec::new();
+    let automata: Vec<_> = properties
+        .iter()
+        .filter_map(|p| match (&p.expectation, &p.formula) {
+            (Expectation::Ltl, Some(formula)) => Some((p.name, formula.to_buchi(true, &mut atoms))),
+            (Expectation::Eventually, _) if include_eventually => {
+                let formula = Ltl::eventually(Ltl::atom(p.condition));
+                Some((p.name, formula.to_buchi(true, &mut atoms)))
+            }
+            _ => None,
+        })
+        .collect();
+    if automata.is_empty() {
+        return;
+    }
+    let graph = StateGraph::explore(model, &atoms, bounds);
+    log::debug!(
+        "Checking {} liveness properties over {} states.",
+        automata.len(),
+        graph.keys.len()
+    );
+    for (name, buchi) in automata {
+        if let Some(lasso) = graph.find_lasso(&buchi) {
+            // The main search does not report properties checked here, but a resumed checkpoint
+            // may already hold a discovery.
+            discoveries
+                .entry(name)
+                .or_insert_with(|| graph.fingerprints(model, bounds, &lasso));
+        }
+    }
+}
+
+/// A path through a [`StateGraph`] that ends in a cycle returning to the last state of the stem,
+/// or that ends in a terminal state if the cycle is empty.
+struct Lasso {
+    stem: Vec<usize>,
+    cycle: Vec<usize>,
+}
+
+/// The reachable states of a model (within its boundary) along with the predicates that hold
+/// for each and the fairness classes of its actions.
+pub(crate) struct StateGraph {
+    /// The [`Bounds::key`] of each state.
+    keys: Vec<Fingerprint>,
+    init: Vec<usize>,
+    /// Whether the successors of each state were explored, which is not the case for states
+    /// beyond the [`Bounds`].
+    expanded: Vec<bool>,
+    /// The distinct `(next state, fairness class)` pairs of each state's actions, so that a class
+    /// is only taken along an edge that one of its own actions follows.
+    successors: Vec<Vec<(usize, Option<usize>)>>,
+    labels: Vec<IdSet>,
+    /// The fairness classes with an action enabled in each state.
+    enabled: Vec<IdSet>,
+    /// The constraint for each fairness class.
+    fairness: Vec<Fairness>,
+}
+
+impl StateGraph {
+    pub(crate) fn explore<M>(
+        model: &M,
+        atoms: &[fn(&M, &M::State) -> bool],
+        bounds: &Bounds<M>,
+    ) -> Self
+    where
+        M: Model,
+        M::State: Hash,
+    {
+        let mut graph = StateGraph {
+            keys: Vec::new(),
+            init: Vec::new(),
+            expanded: Vec::new(),
+            successors: Vec::new(),
+            labels: Vec::new(),
+            enabled: Vec::new(),
+            fairness: Vec::new(),
+        };
+        let mut ids = HashMap::new();
+        let mut classes = HashMap::new();
+        let mut edges = HashSet::new();
+        let mut pending = VecDeque::new();
+        let mut intern =
+            |graph: &mut StateGraph, pending: &mut VecDeque<_>, state: M::State, depth: usize| {
+                let key = bounds.key(&state);
+                *ids.entry(key).or_insert_with(|| {
+                    graph.keys.push(key);
+                    graph.expanded.push(false);
+                    graph.successors.push(Vec::new());
+                    graph.labels.push(
+                        (0..atoms.len())
+                            .filter(|&i| atoms[i](model, &state))
+                            .collect(),
+                    );
+                    graph.enabled.push(IdSet::new());
+                    pending.push_back((graph.keys.len() - 1, state, depth));
+                    graph.keys.len() - 1
+                })
+            };
+        for state in model.init_states() {
+            if !model.within_boundary(&state) {
+                continue;
+            }
+            let id = intern(&mut graph, &mut pending, state, 1);
+            if !graph.init.contains(&id) {
+                graph.init.push(id);
+            }
+        }
+        let mut actions = Vec::new();
+        while let Some((id, state, depth)) = pending.pop_front() {
+            if let Some(target_max_depth) = bounds.target_max_depth {
+                if depth >= target_max_depth.get() {
+                    continue;
+                }
+            }
+            if let Some(target_state_count) = bounds.target_state_count {
+                if target_state_count.get() <= graph.keys.len() {
+                    log::debug!("Reached target state count. Liveness exploration stopped.");
+                    break;
+                }
+            }
+            if let Some(close_at) = bounds.close_at {
+                if close_at <= SystemTime::now() {
+                    log::debug!("Reached timeout. Liveness exploration stopped.");
+                    break;
+                }
+            }
+            graph.expanded[id] = true;
+            model.actions(&state, &mut actions);
+            let fairness: Vec<_> = actions.iter().map(|a| model.fairness(a)).collect();
+            let next_states = model.next_states_for(&state, &mut actions);
+            for (fairness, next_state) in fairness.into_iter().zip(next_states) {
+                let next_state = match next_state {
+                    Some(next_state) if model.within_boundary(&next_state) => next_state,
+                    _ => continue,
+                };
+                let next_id = intern(&mut graph, &mut pending, next_state, depth + 1);
+                let class = fairness.map(|fairness| {
+                    let class = *classes.entry(fairness).or_insert_with(|| {
+                        graph.fairness.push(fairness);
+                        graph.fairness.len() - 1
+                    });
+                    graph.enabled[id].insert(class);
+                    class
+                });
+                if edges.insert((id, next_id, class)) {
+                    graph.successors[id].push((next_id, class));
+                }
+            }
+        }
+        graph
+    }
+
+    /// Terminal states are treated as stuttering forever. States beyond the [`Bounds`] are not
+    /// terminal, as their successors are unknown.
+    fn is_terminal(&self, state: usize) -> bool {
+        self.expanded[state] && self.successors[state].is_empty()
+    }
+
+    /// Recovers the fingerprints of the states along a lasso. Under symmetry reduction the graph
+    /// only distinguishes states up to their representatives, so the cycle is repeated until it
+    /// returns to an identical state rather than merely an equivalent one.
+    fn fingerprints<M>(&self, model: &M, bounds: &Bounds<M>, lasso: &Lasso) -> Vec<Fingerprint>
+    where
+        M: Model,
+        M::State: Hash,
+    {
+        let successor = |state: &M::State, id: usize| {
+            model
+                .next_states(state)
+                .into_iter()
+                .find(|s| model.within_boundary(s) && bounds.key(s) == self.keys[id])
+                .expect("graph edges correspond to model transitions")
+        };
+        let mut state = model
+            .init_states()
+            .into_iter()
+            .find(|s| model.within_boundary(s) && bounds.key(s) == self.keys[lasso.stem[0]])
+            .expect("graph initial states correspond to model initial states");
+        let mut fingerprints = vec![fingerprint(&state)];
+        for &id in &lasso.stem[1..] {
+            state = successor(&state, id);
+            fingerprints.push(fingerprint(&state));
+        }
+        if lasso.cycle.is_empty() {
+            return fingerprints;
+        }
+        let mut entries = vec![fingerprint(&state)];
+        loop {
+            for &id in &lasso.cycle {
+                state = successor(&state, id);
+                fingerprints.push(fingerprint(&state));
+            }
+            let entry = fingerprint(&state);
+            if entries.contains(&entry) {
+                return fingerprints;
+            }
+            entries.push(entry);
+        }
+    }
+
+    /// Returns a fair accepting lasso through the product of this graph and the automaton, if
+    /// any.
+    fn find_lasso(&self, buchi: &Buchi) -> Option<Lasso> {
+        let product = Product::new(self, buchi);
+        let nodes: Vec<_> = (0..product.nodes.len()).collect();
+        let (scc, waypoints) = self.find_fair_scc(&product, buchi, &nodes)?;
+        let (path, entry) = product.lasso(&scc, &waypoints);
+
+        let states: Vec<_> = path.iter().map(|&n| product.nodes[n].0).collect();
+        if self.is_terminal(states[entry]) {
+            // Only a terminal state stutters, so the cycle consists of that state alone.
+            let end = states.iter().position(|&s| s == states[entry]).unwrap();
+            return Some(Lasso {
+                stem: states[..=end].to_vec(),
+                cycle: Vec::new(),
+            });
+        }
+        Some(Lasso {
+            stem: states[..=entry].to_vec(),
+            cycle: states[entry + 1..].to_vec(),
+        })
+    }
+
+    /// Finds a strongly connected subgraph of the specified product nodes that contains a fair
+    /// accepting cycle, preferring the one with the shortest stem. Also returns the nodes (and
+    /// edges) that such a cycle must visit. The approach is from "Temporal and Modal Logic" by
+    /// Emerson, and "Efficient Büchi Automata from LTL Formulae" by Somenzi and Bloem.
+    #[allow(clippy::type_complexity)]
+    fn find_fair_scc(
+        &self,
+        product: &Product,
+        buchi: &Buchi,
+        nodes: &[usize],
+    ) -> Option<(Vec<usize>, Vec<(usize, Option<usize>)>)> {
+        let mut best: Option<(Vec<usize>, Vec<(usize, Option<usize>)>)> = None;
+        for mut scc in sccs(&product.successors, nodes) {
+            scc.sort_unstable();
+            // Node IDs are assigned in BFS order, so the smallest has the shortest stem.
+            if best.as_ref().is_some_and(|(b, _)| b[0] < scc[0]) {
+                continue;
+            }
+            if let Some(found) = self.check_scc(product, buchi, scc) {
+                if best.as_ref().is_none_or(|(b, _)| found.0[0] < b[0]) {
+                    best = Some(found);
+                }
+            }
+        }
+        best
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn check_scc(
+        &self,
+        product: &Product,
+        buchi: &Buchi,
+        scc: Vec<usize>,
+    ) -> Option<(Vec<usize>, Vec<(usize, Option<usize>)>)> {
+        let mut waypoints = Vec::new();
+
+        // A cycle needs an edge, and a fair one takes every class that it can.
+        let mut has_edge = false;
+        let mut taken = IdSet::new();
+        for &n in &scc {
+            let within: Vec<_> = product.successors[n]
+                .iter()
+                .filter(|m| scc.binary_search(m).is_ok())
+                .collect();
+            has_edge |= !within.is_empty();
+            let edges = &self.successors[product.nodes[n].0];
+            for (dst, class) in edges.iter().filter_map(|&(dst, class)| Some((dst, class?))) {
+                if taken.contains(class) {
+                    continue;
+                }
+                if let Some(&&m) = within.iter().find(|&&&m| product.nodes[m].0 == dst) {
+                    taken.insert(class);
+                    waypoints.push((n, Some(m)));
+                }
+            }
+        }
+        if !has_edge {
+            return None;
+        }
+
+        for set in &buchi.acceptance {
+            let n = *scc.iter().find(|&&n| set[product.nodes[n].1])?;
+            waypoints.push((n, None));
+        }
+
+        for (class, fairness) in self.fairness.iter().enumerate() {
+            if taken.contains(class) {
+                continue;
+            }
+            let is_enabled = |n: &usize| self.enabled[product.nodes[*n].0].contains(class);
+            if !scc.iter().any(is_enabled) {
+                continue;
+            }
+            match fairness {
+                Fairness::Weak(_) => {
+                    // Fair only if the cycle passes through a state where the class is disabled.
+                    let n = *scc.iter().find(|n| !is_enabled(n))?;
+                    waypoints.push((n, None));
+                }
+                Fairness::Strong(_) => {
+                    // Fair only if the cycle avoids states where the class is enabled.
+                    let rest: Vec<_> = scc.into_iter().filter(|n| !is_enabled(n)).collect();
+                    return self.find_fair_scc(product, buchi, &rest);
+                }
+            }
+        }
+        Some((scc, waypoints))
+    }
+}
+
+/// The reachable portion of the synchronous product of a [`StateGraph`] and a [`Buchi`]
+/// automaton.
+struct Product {
+    /// `(state, automaton node)` pairs, indexed in BFS order.
+    nodes: Vec<(usize, usize)>,
+    parents: Vec<Option<usize>>,
+    successors: Vec<Vec<usize>>,
+}
+
+impl Product {
+    fn new(graph: &StateGraph, buchi: &Buchi) -> Self {
+        let mut product = Product {
+            nodes: Vec::new(),
+            parents: Vec::new(),
+            successors: Vec::new(),
+        };
+        let admits = |s: usize, q: usize| buchi.admits(q, |atom| graph.labels[s].contains(atom));
+        let mut ids = HashMap::new();
+        let mut edges = HashSet::new();
+        let mut pending = VecDeque::new();
+        let mut intern = |product: &mut Product,
+                          pending: &mut VecDeque<usize>,
+                          node: (usize, usize),
+                          parent: Option<usize>| {
+            *ids.entry(node).or_insert_with(|| {
+                product.nodes.push(node);
+                product.parents.push(parent);
+                product.successors.push(Vec::new());
+                pending.push_back(product.nodes.len() - 1);
+                product.nodes.len() - 1
+            })
+        };
+        for &s in &graph.init {
+            for &q in &buchi.initial {
+                if admits(s, q) {
+                    intern(&mut product, &mut pending, (s, q), None);
+                }
+            }
+        }
+        while let Some(id) = pending.pop_front() {
+            let (s, q) = product.nodes[id];
+            let next_states: Vec<usize> = if graph.is_terminal(s) {
+                vec![s]
+            } else {
+                graph.successors[s]
+                    .iter()
+                    .map(|&(next_s, _)| next_s)
+                    .collect()
+            };
+            for next_s in next_states {
+                for &next_q in &buchi.successors[q] {
+                    if admits(next_s, next_q) {
+                        let next_id =
+                            intern(&mut product, &mut pending, (next_s, next_q), Some(id));
+                        if edges.insert((id, next_id)) {
+                            product.successors[id].push(next_id);
+                        }
+                    }
+                }
+            }
+        }
+        product
+    }
+
+    /// Builds a path from an initial node to the smallest node of the (sorted) SCC, followed by
+    /// a cycle through every waypoint that returns to that node. A waypoint with a second node
+    /// indicates an edge to traverse. Also returns the index of the node where the cycle starts.
+    fn lasso(&self, scc: &[usize], waypoints: &[(usize, Option<usize>)]) -> (Vec<usize>, usize) {
+        let entry = scc[0];
+        let mut path = vec![entry];
+        while let Some(parent) = self.parents[*path.last().unwrap()] {
+            path.push(parent);
+        }
+        path.reverse();
+        let entry_index = path.len() - 1;
+
+        let mut current = entry;
+        let mut has_stepped = false;
+        for &(node, next) in waypoints {
+            let segment = shortest_path_within(&self.successors, scc, current, node, false);
+            has_stepped |= !segment.is_empty();
+            current = *segment.last().unwrap_or(&current);
+            path.extend(segment);
+            if let Some(next) = next {
+                has_stepped = true;
+                current = next;
+                path.push(next);
+            }
+        }
+        path.extend(shortest_path_within(
+            &self.successors,
+            scc,
+            current,
+            entry,
+            !has_stepped,
+        ));
+        (path, entry_index)
+    }
+}
+
+/// The graph of states visited with unsatisfied [`Expectation::Eventually`] properties under
+/// [`CheckerBuilder::sound_eventually`](crate::CheckerBuilder::sound_eventually), keyed by
+/// `visited_key`. The key includes the unsatisfied properties, so a cycle in this graph is a
+/// behavior that never satisfies them. Cycles are found once every checker thread has exited,
+/// as a cycle may span states expanded by different threads.
+pub(crate) struct EventuallyCycles {
+    nodes: DashMap<Fingerprint, CycleNode, BuildHasherDefault<NoHashHasher<u64>>>,
+    running: AtomicUsize,
+    is_searched: AtomicBool,
+}
+
+struct CycleNode {
+    fingerprint: Fingerprint,
+    /// The state from which this one was first reached, for recovering a path from an initial
+    /// state.
+    parent: Option<Fingerprint>,
+    depth: usize,
+    /// The properties still unsatisfied after this state, which are those of its successors.
+    ebits: EventuallyBits,
+    successors: Vec<Fingerprint>,
+}
+
+impl EventuallyCycles {
+    pub(crate) fn new(thread_count: usize) -> Self {
+        EventuallyCycles {
+            nodes: DashMap::default(),
+            running: AtomicUsize::new(thread_count),
+            is_searched: AtomicBool::new(false),
+        }
+    }
+
+    /// Records a transition between two `(key, fingerprint)` pairs, given the depth of the source
+    /// and the properties that it leaves unsatisfied.
+    pub(crate) fn insert(
+        &self,
+        (src_key, src_fp): (Fingerprint, Fingerprint),
+        depth: usize,
+        ebits: &EventuallyBits,
+        (dst_key, dst_fp): (Fingerprint, Fingerprint),
+    ) {
+        {
+            let mut src = self.nodes.entry(src_key).or_insert_with(|| CycleNode {
+                fingerprint: src_fp,
+                parent: None,
+                depth,
+                ebits: EventuallyBits::new(),
+                successors: Vec::new(),
+            });
+            if src.successors.is_empty() {
+                src.ebits = ebits.clone();
+            }
+            src.successors.push(dst_key);
+        }
+        self.nodes.entry(dst_key).or_insert_with(|| CycleNode {
+            fingerprint: dst_fp,
+            parent: Some(src_key),
+            depth: depth + 1,
+            ebits: EventuallyBits::new(),
+            successors: Vec::new(),
+        });
+    }
+
+    /// The fingerprints of the states along the path by which a key was first reached.
+    pub(crate) fn stem(&self, key: Fingerprint) -> Vec<Fingerprint> {
+        let mut fingerprints = Vec::new();
+        let mut next_key = Some(key);
+        while let Some(key) = next_key {
+            let node = self
+                .nodes
+                .get(&key)
+                .expect("stems consist of recorded states");
+            fingerprints.push(node.fingerprint);
+            next_key = node.parent;
+        }
+        fingerprints.reverse();
+        fingerprints
+    }
+
+    /// Whether the search for cycles has finished, which is the case once every checker thread
+    /// has exited.
+    pub(crate) fn is_searched(&self) -> bool {
+        self.is_searched.load(Ordering::SeqCst)
+    }
+
+    /// Called as each checker thread exits. The last one reports a counterexample for each
+    /// property unsatisfied along a cycle (unless the thread is panicking), using `stem` to
+    /// recover the path to the cycle. Cycles closer to an initial state are preferred.
+    pub(crate) fn exit<M: Model>(
+        &self,
+        properties: &[Property<M>],
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        stem: impl Fn(Fingerprint) -> Vec<Fingerprint>,
+    ) {
+        if self.running.fetch_sub(1, Ordering::SeqCst) != 1 {
+            return;
+        }
+        if !std::thread::panicking() {
+            self.search(properties, discoveries, stem);
+        }
+        self.is_searched.store(true, Ordering::SeqCst);
+    }
+
+    fn search<M: Model>(
+        &self,
+        properties: &[Property<M>],
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        stem: impl Fn(Fingerprint) -> Vec<Fingerprint>,
+    ) {
+        let keys: Vec<Fingerprint> = self.nodes.iter().map(|node| *node.key()).collect();
+        let ids: HashMap<Fingerprint, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(id, key)| (*key, id))
+            .collect();
+        let successors: Vec<Vec<usize>> = keys
+            .iter()
+            .map(|key| {
+                self.nodes
+                    .get(key)
+                    .unwrap()
+                    .successors
+                    .iter()
+                    .map(|s| ids[s])
+                    .collect()
+            })
+            .collect();
+        log::debug!("Searching for cycles among {} states.", keys.len());
+
+        let mut cycles: Vec<_> = sccs(&successors, &(0..keys.len()).collect::<Vec<_>>())
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || successors[scc[0]].contains(&scc[0]))
+            .map(|mut scc| {
+                scc.sort_unstable();
+                let entry = *scc
+                    .iter()
+                    .min_by_key(|&&id| self.nodes.get(&keys[id]).unwrap().depth)
+                    .unwrap();
+                (entry, scc)
+            })
+            .collect();
+        cycles.sort_by_key(|(entry, _)| self.nodes.get(&keys[*entry]).unwrap().depth);
+        for (entry, scc) in cycles {
+            let ebits = self.nodes.get(&keys[entry]).unwrap().ebits.clone();
+            let mut fingerprints = None;
+            for (i, property) in properties.iter().enumerate() {
+                if !ebits.contains(i) || discoveries.contains_key(property.name) {
+                    continue;
+                }
+                let fingerprints = fingerprints.get_or_insert_with(|| {
+                    let mut fingerprints = stem(keys[entry]);
+                    for id in shortest_path_within(&successors, &scc, entry, entry, true) {
+                        fingerprints.push(self.nodes.get(&keys[id]).unwrap().fingerprint);
+                    }
+                    fingerprints
+                });
+                discoveries
+                    .entry(property.name)
+                    .or_insert_with(|| fingerprints.clone());
+            }
+        }
+    }
+}
+
+/// Runs a closure when dropped, so that [`EventuallyCycles::exit`] is called however a checker
+/// thread exits.
+pub(crate) struct OnExit<F: FnMut()>(pub(crate) F);
+
+impl<F: FnMut()> Drop for OnExit<F> {
+    fn drop(&mut self) {
+        (self.0)()
+    }
+}
+
+/// Returns the nodes following `src` on a shortest path to `dst` that stays within the SCC.
+fn shortest_path_within(
+    successors: &[Vec<usize>],
+    scc: &[usize],
+    src: usize,
+    dst: usize,
+    nonempty: bool,
+) -> Vec<usize> {
+    if src == dst && !nonempty {
+        return Vec::new();
+    }
+    let mut parents = HashMap::new();
+    let mut pending = VecDeque::from([src]);
+    while let Some(n) = pending.pop_front() {
+        for &next in &successors[n] {
+            if scc.binary_search(&next).is_err() || parents.contains_key(&next) {
+                continue;
+            }
+            parents.insert(next, n);
+            if next == dst {
+                let mut segment = vec![dst];
+                let mut n = n;
+                while n != src {
+                    segment.push(n);
+                    n = parents[&n];
+                }
+                segment.reverse();
+                return segment;
+            }
+            pending.push_back(next);
+        }
+    }
+    unreachable!("nodes of a strongly connected component are mutually reachable");
+}
+
+/// Tarjan's algorithm for strongly connected components of the subgraph induced by `nodes`,
+/// without recursion to avoid overflowing the stack on large state spaces.
+fn sccs(successors: &[Vec<usize>], nodes: &[usize]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; successors.len()];
+    let mut lowlink = vec![0; successors.len()];
+    let mut on_stack = vec![false; successors.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut result = Vec::new();
+    let mut is_member = vec![false; successors.len()];
+    for &n in nodes {
+        is_member[n] = true;
+    }
+
+    for &root in nodes {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some((v, i)) = calls.last_mut() {
+            let v = *v;
+            if let Some(&w) = successors[v].get(*i) {
+                *i += 1;
+                if !is_member[w] {
+                    continue;
+                }
+                if index[w] == UNVISITED {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(u, _)) = calls.last() {
+                lowlink[u] = lowlink[u].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut scc = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    scc.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                result.push(scc);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, CheckerBuilder};
+
+    /// Can move from 0 to 1 (satisfying the property) or cycle between 0 and 2.
+    struct Spinner(Option<Fairness>);
+    impl Model for Spinner {
+        type State = u8;
+        type Action = u8;
+        fn init_states(&self) -> Vec<Self::State> {
+            vec![0]
+        }
+        fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+            match state {
+                0 => actions.extend([1, 2]),
+                2 => actions.push(0),
+                _ => {}
+            }
+        }
+        fn next_state(&self, _: &Self::State, action: Self::Action) -> Option<Self::State> {
+            Some(action)
+        }
+        fn fairness(&self, action: &Self::Action) -> Option<Fairness> {
+            if *action == 1 {
+                self.0
+            } else {
+                None
+            }
+        }
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![Property::eventually("reaches 1", |_, s| *s == 1)]
+        }
+    }
+
+    fn check(model: Spinner) -> Option<Vec<u8>> {
+        CheckerBuilder::new(model)
+            .liveness()
+            .spawn_bfs()
+            .join()
+            .discovery("reaches 1")
+            .map(|path| path.into_states())
+    }
+
+    #[test]
+    fn discards_unfair_cycles() {
+        assert_eq!(check(Spinner(None)), Some(vec![0, 2, 0]));
+        // Not continuously enabled, so weak fairness is insufficient.
+        assert_eq!(
+            check(Spinner(Some(Fairness::weak(())))),
+            Some(vec![0, 2, 0])
+        );
+        assert_eq!(check(Spinner(Some(Fairness::strong(())))), None);
+    }
+
+    /// Flips a bit with either of two actions, each in its own weakly fair class.
+    struct Flipper;
+    impl Model for Flipper {
+        type State = bool;
+        type Action = char;
+        fn init_states(&self) -> Vec<Self::State> {
+            vec![false]
+        }
+        fn actions(&self, _: &Self::State, actions: &mut Vec<Self::Action>) {
+            actions.extend(['a', 'b']);
+        }
+        fn next_state(&self, state: &Self::State, _: Self::Action) -> Option<Self::State> {
+            Some(!state)
+        }
+        fn fairness(&self, action: &Self::Action) -> Option<Fairness> {
+            Some(Fairness::weak(action))
+        }
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![Property::eventually("never", |_, _| false)]
+        }
+    }
+
+    #[test]
+    fn takes_fairness_classes_per_action() {
+        // Both actions lead between the same states, but a fair cycle must take each of them, so
+        // it traverses that edge once per class.
+        let checker = CheckerBuilder::new(Flipper).liveness().spawn_bfs().join();
+        assert_eq!(
+            checker.discovery("never").unwrap().into_states(),
+            vec![false, true, false, true, false]
+        );
+    }
+
+    /// Counts up forever.
+    struct Counter;
+    impl Model for Counter {
+        type State = u32;
+        type Action = ();
+        fn init_states(&self) -> Vec<Self::State> {
+            vec![0]
+        }
+        fn actions(&self, _: &Self::State, actions: &mut Vec<Self::Action>) {
+            actions.push(());
+        }
+        fn next_state(&self, state: &Self::State, _: Self::Action) -> Option<Self::State> {
+            Some(state + 1)
+        }
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![Property::eventually("reaches max", |_, s| *s == u32::MAX)]
+        }
+    }
+
+    #[test]
+    fn honors_checker_bounds() {
+        // States beyond the bounds are unexplored rather than terminal.
+        let checker = CheckerBuilder::new(Counter)
+            .liveness()
+            .target_max_depth(10)
+            .spawn_bfs()
+            .join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discovery("reaches max"), None);
+        let checker = CheckerBuilder::new(Counter)
+            .liveness()
+            .target_state_count(10)
+            .spawn_dfs()
+            .join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discovery("reaches max"), None);
+    }
+
+    /// Swaps two values forever.
+    struct Swapper;
+    impl Model for Swapper {
+        type State = [u8; 2];
+        type Action = ();
+        fn init_states(&self) -> Vec<Self::State> {
+            vec![[0, 1]]
+        }
+        fn actions(&self, _: &Self::State, actions: &mut Vec<Self::Action>) {
+            actions.push(());
+        }
+        fn next_state(&self, state: &Self::State, _: Self::Action) -> Option<Self::State> {
+            Some([state[1], state[0]])
+        }
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![Property::eventually("equal", |_, s: &[u8; 2]| s[0] == s[1])]
+        }
+    }
+
+    #[test]
+    fn repeats_cycles_under_symmetry_until_states_repeat() {
+        // Both states share a representative, so the reduced graph has a self-loop.
+        let checker = CheckerBuilder::new(Swapper)
+            .liveness()
+            .symmetry_fn(|s: &[u8; 2]| [s[0].min(s[1]), s[0].max(s[1])])
+            .spawn_bfs()
+            .join();
+        assert_eq!(
+            checker.discovery("equal").unwrap().into_states(),
+            vec![[0, 1], [1, 0], [0, 1]]
+        );
+        checker.assert_discovery("equal", vec![(), ()]);
+    }
+
+    #[test]
+    fn finds_strongly_connected_components() {
+        // 0 -> 1 -> 2 -> 1, 2 -> 3, 3 -> 3
+        let successors = vec![vec![1], vec![2], vec![1, 3], vec![3]];
+        let mut sccs: Vec<_> = sccs(&successors, &[0, 1, 2, 3])
+            .into_iter()
+            .map(|mut scc| {
+                scc.sort();
+                scc
+            })
+            .collect();
+        sccs.sort();
+        assert_eq!(sccs, vec![vec![0], vec![1, 2], vec![3]]);
+    }
+}
@@ -0,0 +1,503 @@
+//! Private module for selective re-export.
+
+use crate::Model;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Debug, Formatter};
+
+/// A [linear temporal logic](https://en.wikipedia.org/wiki/Linear_temporal_logic) formula over
+/// state predicates. See [`Property::ltl`].
+///
+/// Formulas are interpreted over infinite behaviors. A behavior that reaches a terminal state
+/// (one without successors within the [model boundary]) is treated as remaining in that state
+/// forever.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Ltl, Model, Property};
+///
+/// struct Counter;
+/// impl Model for Counter {
+///     type State = u8;
+///     type Action = ();
+///     fn init_states(&self) -> Vec<Self::State> { vec![0] }
+///     fn actions(&self, _: &Self::State, actions: &mut Vec<Self::Action>) { actions.push(()) }
+///     fn next_state(&self, s: &Self::State, _: Self::Action) -> Option<Self::State> {
+///         Some((s + 1) % 4)
+///     }
+///     fn properties(&self) -> Vec<Property<Self>> {
+///         vec![
+///             // Every zero is followed by a three.
+///             Property::ltl("zero leads to three", Ltl::leads_to(
+///                 Ltl::atom(|_, s| *s == 0),
+///                 Ltl::atom(|_, s| *s == 3),
+///             )),
+///             // The counter wraps infinitely often.
+///             Property::ltl("wraps", Ltl::infinitely_often(Ltl::atom(|_, s| *s == 0))),
+///         ]
+///     }
+/// }
+/// ```
+///
+/// [`Property::ltl`]: crate::Property::ltl
+/// [model boundary]: crate::Model::within_boundary
+pub enum Ltl<M: Model> {
+    /// Holds for every behavior.
+    True,
+    /// Holds for no behavior.
+    False,
+    /// Holds if the predicate holds for the first state of the behavior.
+    Atom(fn(&M, &M::State) -> bool),
+    /// Holds if the subformula does not.
+    Not(Box<Ltl<M>>),
+    /// Holds if both subformulas hold.
+    And(Box<Ltl<M>>, Box<Ltl<M>>),
+    /// Holds if either subformula holds.
+    Or(Box<Ltl<M>>, Box<Ltl<M>>),
+    /// Holds if the subformula holds for the behavior starting from the next state.
+    Next(Box<Ltl<M>>),
+    /// Holds if the second subformula eventually holds, and the first holds until then.
+    Until(Box<Ltl<M>>, Box<Ltl<M>>),
+    /// Holds if the second subformula holds up to and including the point at which the first
+    /// holds, or forever if the first never holds. The dual of [`Ltl::Until`].
+    Release(Box<Ltl<M>>, Box<Ltl<M>>),
+    /// Holds if the subformula holds for every suffix of the behavior.
+    Always(Box<Ltl<M>>),
+    /// Holds if the subformula holds for some suffix of the behavior.
+    Eventually(Box<Ltl<M>>),
+}
+
+impl<M: Model> Ltl<M> {
+    /// A formula that holds if a state predicate holds for the first state.
+    pub fn atom(condition: fn(&M, &M::State) -> bool) -> Self {
+        Ltl::Atom(condition)
+    }
+
+    /// A formula that holds if `formula` holds from every point onward (`□ formula`).
+    pub fn always(formula: Self) -> Self {
+        Ltl::Always(Box::new(formula))
+    }
+
+    /// A formula that holds if `formula` holds from some point onward (`◇ formula`).
+    pub fn eventually(formula: Self) -> Self {
+        Ltl::Eventually(Box::new(formula))
+    }
+
+    /// A formula that holds if `formula` holds from the next state onward (`○ formula`).
+    pub fn next(formula: Self) -> Self {
+        Ltl::Next(Box::new(formula))
+    }
+
+    /// A formula that holds if every `p` is eventually followed by `q` (`□(p → ◇q)`).
+    pub fn leads_to(p: Self, q: Self) -> Self {
+        Ltl::always(p.implies(Ltl::eventually(q)))
+    }
+
+    /// A formula that holds if `formula` holds infinitely often (`□◇ formula`).
+    pub fn infinitely_often(formula: Self) -> Self {
+        Ltl::always(Ltl::eventually(formula))
+    }
+
+    /// A formula that holds if both `self` and `other` hold.
+    pub fn and(self, other: Self) -> Self {
+        Ltl::And(Box::new(self), Box::new(other))
+    }
+
+    /// A formula that holds if either `self` or `other` holds.
+    pub fn or(self, other: Self) -> Self {
+        Ltl::Or(Box::new(self), Box::new(other))
+    }
+
+    /// A formula that holds if `other` holds whenever `self` holds.
+    pub fn implies(self, other: Self) -> Self {
+        (!self).or(other)
+    }
+
+    /// A formula that holds if `other` eventually holds and `self` holds until then.
+    pub fn until(self, other: Self) -> Self {
+        Ltl::Until(Box::new(self), Box::new(other))
+    }
+
+    /// A formula that holds if `other` holds until and including the point at which `self`
+    /// holds, or forever if `self` never holds.
+    pub fn release(self, other: Self) -> Self {
+        Ltl::Release(Box::new(self), Box::new(other))
+    }
+
+    /// Translates the formula (or its negation) into a [`Buchi`] automaton, registering
+    /// predicates with `atoms` so that they can be evaluated once per state.
+    pub(crate) fn to_buchi(
+        &self,
+        negate: bool,
+        atoms: &mut Vec<fn(&M, &M::State) -> bool>,
+    ) -> Buchi {
+        let mut arena = Arena::default();
+        let root = arena.normalize(self, negate, atoms);
+        Buchi::from_nnf(&arena, root)
+    }
+
+    /// Indicates whether the formula holds for the behavior `states[0], ..., states[n - 1]`
+    /// followed by a return to `states[loop_start]`.
+    pub(crate) fn holds_on_lasso(
+        &self,
+        model: &M,
+        states: &[&M::State],
+        loop_start: usize,
+    ) -> bool {
+        let mut atoms = Vec::new();
+        let mut arena = Arena::default();
+        let root = arena.normalize(self, false, &mut atoms);
+        let len = states.len();
+        let succ = |i: usize| if i + 1 < len { i + 1 } else { loop_start };
+
+        // Children are interned before parents, so evaluating in index order is bottom-up.
+        let mut values: Vec<Vec<bool>> = Vec::with_capacity(arena.nodes.len());
+        for node in &arena.nodes {
+            let value = match *node {
+                Nnf::True => vec![true; len],
+                Nnf::False => vec![false; len],
+                Nnf::Lit(atom, positive) => states
+                    .iter()
+                    .map(|s| atoms[atom](model, s) == positive)
+                    .collect(),
+                Nnf::And(a, b) => (0..len).map(|i| values[a][i] && values[b][i]).collect(),
+                Nnf::Or(a, b) => (0..len).map(|i| values[a][i] || values[b][i]).collect(),
+                Nnf::Next(a) => (0..len).map(|i| values[a][succ(i)]).collect(),
+                Nnf::Until(a, b) => {
+                    // Least fixpoint.
+                    let mut value = vec![false; len];
+                    for _ in 0..=len {
+                        for i in (0..len).rev() {
+                            value[i] = values[b][i] || (values[a][i] && value[succ(i)]);
+                        }
+                    }
+                    value
+                }
+                Nnf::Release(a, b) => {
+                    // Greatest fixpoint.
+                    let mut value = vec![true; len];
+                    for _ in 0..=len {
+                        for i in (0..len).rev() {
+                            value[i] = values[b][i] && (values[a][i] || value[succ(i)]);
+                        }
+                    }
+                    value
+                }
+            };
+            values.push(value);
+        }
+        values[root][0]
+    }
+}
+
+impl<M: Model> std::ops::Not for Ltl<M> {
+    type Output = Self;
+    fn not(self) -> Self {
+        Ltl::Not(Box::new(self))
+    }
+}
+
+// Manual implementation to avoid `Clone` constraint that `#derive(Clone)` would introduce on
+// `Ltl<M>` type parameters.
+impl<M: Model> Clone for Ltl<M> {
+    fn clone(&self) -> Self {
+        match self {
+            Ltl::True => Ltl::True,
+            Ltl::False => Ltl::False,
+            Ltl::Atom(condition) => Ltl::Atom(*condition),
+            Ltl::Not(a) => Ltl::Not(a.clone()),
+            Ltl::And(a, b) => Ltl::And(a.clone(), b.clone()),
+            Ltl::Or(a, b) => Ltl::Or(a.clone(), b.clone()),
+            Ltl::Next(a) => Ltl::Next(a.clone()),
+            Ltl::Until(a, b) => Ltl::Until(a.clone(), b.clone()),
+            Ltl::Release(a, b) => Ltl::Release(a.clone(), b.clone()),
+            Ltl::Always(a) => Ltl::Always(a.clone()),
+            Ltl::Eventually(a) => Ltl::Eventually(a.clone()),
+        }
+    }
+}
+
+// Manual implementation to avoid `Debug` constraint that `#derive(Debug)` would introduce on
+// `Ltl<M>` type parameters.
+impl<M: Model> Debug for Ltl<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ltl::True => write!(f, "true"),
+            Ltl::False => write!(f, "false"),
+            Ltl::Atom(_) => write!(f, "atom"),
+            Ltl::Not(a) => write!(f, "¬{:?}", a),
+            Ltl::And(a, b) => write!(f, "({:?} ∧ {:?})", a, b),
+            Ltl::Or(a, b) => write!(f, "({:?} ∨ {:?})", a, b),
+            Ltl::Next(a) => write!(f, "○{:?}", a),
+            Ltl::Until(a, b) => write!(f, "({:?} U {:?})", a, b),
+            Ltl::Release(a, b) => write!(f, "({:?} R {:?})", a, b),
+            Ltl::Always(a) => write!(f, "□{:?}", a),
+            Ltl::Eventually(a) => write!(f, "◇{:?}", a),
+        }
+    }
+}
+
+/// A formula in negation normal form, referencing subformulas by [`Arena`] index.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Nnf {
+    True,
+    False,
+    Lit(usize, bool),
+    And(usize, usize),
+    Or(usize, usize),
+    Next(usize),
+    Until(usize, usize),
+    Release(usize, usize),
+}
+
+/// Interns subformulas so that they can be compared and collected cheaply.
+#[derive(Default)]
+struct Arena {
+    nodes: Vec<Nnf>,
+    index: HashMap<Nnf, usize>,
+}
+
+impl Arena {
+    fn intern(&mut self, node: Nnf) -> usize {
+        let nodes = &mut self.nodes;
+        *self.index.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    /// Pushes negations down to the predicates and rewrites `□`/`◇` in terms of `R`/`U`.
+    fn normalize<M: Model>(
+        &mut self,
+        formula: &Ltl<M>,
+        negate: bool,
+        atoms: &mut Vec<fn(&M, &M::State) -> bool>,
+    ) -> usize {
+        let node = match formula {
+            Ltl::True if negate => Nnf::False,
+            Ltl::True => Nnf::True,
+            Ltl::False if negate => Nnf::True,
+            Ltl::False => Nnf::False,
+            Ltl::Atom(condition) => {
+                atoms.push(*condition);
+                Nnf::Lit(atoms.len() - 1, !negate)
+            }
+            Ltl::Not(a) => return self.normalize(a, !negate, atoms),
+            Ltl::And(a, b) | Ltl::Or(a, b) => {
+                let a = self.normalize(a, negate, atoms);
+                let b = self.normalize(b, negate, atoms);
+                if matches!(formula, Ltl::And(..)) != negate {
+                    Nnf::And(a, b)
+                } else {
+                    Nnf::Or(a, b)
+                }
+            }
+            Ltl::Next(a) => Nnf::Next(self.normalize(a, negate, atoms)),
+            Ltl::Until(a, b) | Ltl::Release(a, b) => {
+                let a = self.normalize(a, negate, atoms);
+                let b = self.normalize(b, negate, atoms);
+                if matches!(formula, Ltl::Until(..)) != negate {
+                    Nnf::Until(a, b)
+                } else {
+                    Nnf::Release(a, b)
+                }
+            }
+            Ltl::Always(a) | Ltl::Eventually(a) => {
+                let a = self.normalize(a, negate, atoms);
+                if matches!(formula, Ltl::Always(..)) != negate {
+                    Nnf::Release(self.intern(Nnf::False), a)
+                } else {
+                    Nnf::Until(self.intern(Nnf::True), a)
+                }
+            }
+        };
+        self.intern(node)
+    }
+}
+
+/// A generalized Büchi automaton whose nodes are labeled by the predicate values that a state
+/// must have for a run to occupy the node.
+pub(crate) struct Buchi {
+    /// The `(atom, value)` pairs that a state must satisfy to occupy each node.
+    pub(crate) literals: Vec<Vec<(usize, bool)>>,
+    pub(crate) initial: Vec<usize>,
+    pub(crate) successors: Vec<Vec<usize>>,
+    /// Accepting runs visit at least one node of each set infinitely often.
+    pub(crate) acceptance: Vec<Vec<bool>>,
+}
+
+impl Buchi {
+    /// Indicates whether a state with the specified true atoms can occupy a node.
+    pub(crate) fn admits(&self, node: usize, is_true: impl Fn(usize) -> bool) -> bool {
+        self.literals[node]
+            .iter()
+            .all(|&(atom, value)| is_true(atom) == value)
+    }
+
+    /// The tableau construction from "Simple On-the-fly Automatic Verification of Linear
+    /// Temporal Logic" by Gerth, Peled, Vardi, and Wolper.
+    fn from_nnf(arena: &Arena, root: usize) -> Self {
+        const INIT: usize = usize::MAX;
+
+        #[derive(Clone)]
+        struct Node {
+            incoming: BTreeSet<usize>,
+            new: BTreeSet<usize>,
+            old: BTreeSet<usize>,
+            next: BTreeSet<usize>,
+        }
+
+        let mut done: Vec<Node> = Vec::new();
+        let mut pending = vec![Node {
+            incoming: BTreeSet::from([INIT]),
+            new: BTreeSet::from([root]),
+            old: BTreeSet::new(),
+            next: BTreeSet::new(),
+        }];
+        while let Some(mut node) = pending.pop() {
+            let formula = match node.new.pop_first() {
+                Some(formula) => formula,
+                None => {
+                    if let Some(existing) = done
+                        .iter_mut()
+                        .find(|d| d.old == node.old && d.next == node.next)
+                    {
+                        existing.incoming.extend(node.incoming);
+                    } else {
+                        pending.push(Node {
+                            incoming: BTreeSet::from([done.len()]),
+                            new: node.next.clone(),
+                            old: BTreeSet::new(),
+                            next: BTreeSet::new(),
+                        });
+                        done.push(node);
+                    }
+                    continue;
+                }
+            };
+            let mut with = |mut node: Node, new: &[usize], next: &[usize]| {
+                node.new.extend(
+                    new.iter()
+                        .filter(|f| !node.old.contains(f) && **f != formula),
+                );
+                node.next.extend(next);
+                node.old.insert(formula);
+                pending.push(node);
+            };
+            match arena.nodes[formula] {
+                Nnf::False => {} // contradiction
+                Nnf::True => with(node, &[], &[]),
+                Nnf::Lit(atom, value) => {
+                    let contradicts = arena
+                        .index
+                        .get(&Nnf::Lit(atom, !value))
+                        .is_some_and(|negation| node.old.contains(negation));
+                    if !contradicts {
+                        with(node, &[], &[]);
+                    }
+                }
+                Nnf::And(a, b) => with(node, &[a, b], &[]),
+                Nnf::Next(a) => with(node, &[], &[a]),
+                Nnf::Or(a, b) => {
+                    with(node.clone(), &[a], &[]);
+                    with(node, &[b], &[]);
+                }
+                Nnf::Until(a, b) => {
+                    with(node.clone(), &[a], &[formula]);
+                    with(node, &[b], &[]);
+                }
+                Nnf::Release(a, b) => {
+                    with(node.clone(), &[b], &[formula]);
+                    with(node, &[a, b], &[]);
+                }
+            }
+        }
+
+        let mut successors = vec![Vec::new(); done.len()];
+        let mut initial = Vec::new();
+        for (id, node) in done.iter().enumerate() {
+            for &src in &node.incoming {
+                if src == INIT {
+                    initial.push(id);
+                } else {
+                    successors[src].push(id);
+                }
+            }
+        }
+        let acceptance = arena
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(formula, node)| match *node {
+                Nnf::Until(_, b) => Some(
+                    done.iter()
+                        .map(|n| !n.old.contains(&formula) || n.old.contains(&b))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .collect();
+        let literals = done
+            .iter()
+            .map(|n| {
+                n.old
+                    .iter()
+                    .filter_map(|f| match arena.nodes[*f] {
+                        Nnf::Lit(atom, value) => Some((atom, value)),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        Buchi {
+            literals,
+            initial,
+            successors,
+            acceptance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::dgraph::DGraph;
+
+    fn is_odd() -> Ltl<DGraph> {
+        Ltl::atom(|_, s| s % 2 == 1)
+    }
+
+    fn holds(formula: Ltl<DGraph>, states: &[u8], loop_start: usize) -> bool {
+        let model = DGraph::with_property(crate::Property::always("unused", |_, _| true));
+        let states: Vec<_> = states.iter().collect();
+        formula.holds_on_lasso(&model, &states, loop_start)
+    }
+
+    #[test]
+    fn can_evaluate_on_lasso() {
+        assert!(holds(Ltl::eventually(is_odd()), &[0, 2, 3], 2));
+        assert!(!holds(Ltl::eventually(is_odd()), &[0, 2, 4], 1));
+        assert!(holds(Ltl::infinitely_often(is_odd()), &[0, 1, 2], 1));
+        assert!(!holds(Ltl::infinitely_often(is_odd()), &[1, 2, 4], 1));
+        assert!(holds(Ltl::always(!is_odd()), &[0, 2, 4], 0));
+        assert!(holds(Ltl::next(is_odd()), &[0, 1], 1));
+        assert!(holds((!is_odd()).until(is_odd()), &[0, 2, 1], 2));
+        assert!(!holds((!is_odd()).until(is_odd()), &[0, 2], 0));
+        assert!(!holds(Ltl::leads_to(!is_odd(), is_odd()), &[1, 0, 2], 1));
+    }
+
+    #[test]
+    fn buchi_has_accepting_set_per_until() {
+        let buchi = Ltl::always(is_odd()).to_buchi(false, &mut Vec::new());
+        assert!(buchi.acceptance.is_empty());
+        let buchi = Ltl::always(is_odd()).to_buchi(true, &mut Vec::new());
+        assert_eq!(buchi.acceptance.len(), 1); // ◇¬odd
+        let mut atoms = Vec::new();
+        let buchi = (!is_odd())
+            .until(is_odd())
+            .and(Ltl::eventually(is_odd()))
+            .to_buchi(false, &mut atoms);
+        assert_eq!(buchi.acceptance.len(), 2);
+        assert_eq!(atoms.len(), 3);
+    }
+}
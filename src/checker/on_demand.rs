@@ -1,6 +1,7 @@
 //! Private module for selective re-export.
 
-use crate::checker::{Checker, EventuallyBits, Expectation, Path};
+use crate::checker::liveness::{EventuallyCycles, OnExit};
+use crate::checker::{collect_actions, visited_key, Checker, EventuallyBits, Expectation, Path};
 use crate::job_market::JobBroker;
 use crate::{
     fingerprint, CheckerBuilder, CheckerVisitor, ControlFlow, Fingerprint, Model, Property,
@@ -30,7 +31,8 @@ pub(crate) struct OnDemandChecker<M: Model> {
     max_depth: Arc<AtomicUsize>,
     generated:
         Arc<DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>>,
-    discoveries: Arc<DashMap<&'static str, Fingerprint>>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+    cycles: Option<Arc<EventuallyCycles>>,
     control_flow: std::sync::mpsc::SyncSender<ControlFlow>,
 }
 type Job<State> = (State, Fingerprint, EventuallyBits, NonZeroUsize);
@@ -44,6 +46,7 @@ where
         let model = Arc::new(options.model);
         let target_state_count = options.target_state_count;
         let thread_count = options.thread_count;
+        let sound_eventually = options.sound_eventually;
         let visitor = Arc::new(options.visitor);
         let property_count = model.properties().len();
 
@@ -58,13 +61,6 @@ where
             .collect();
         let state_count = Arc::new(AtomicUsize::new(init_states.len()));
         let max_depth = Arc::new(AtomicUsize::new(0));
-        let generated = Arc::new({
-            let generated = DashMap::default();
-            for s in &init_states {
-                generated.insert(fingerprint(s), None);
-            }
-            generated
-        });
         let ebits = {
             let mut ebits = EventuallyBits::new();
             for (i, p) in model.properties().iter().enumerate() {
@@ -78,6 +74,16 @@ where
             }
             ebits
         };
+        let generated = Arc::new(DashMap::default());
+        let aliases = Arc::new(DashMap::default());
+        for s in &init_states {
+            let fp = fingerprint(s);
+            let key = visited_key(fp, &ebits, sound_eventually);
+            generated.insert(key, None);
+            if key != fp {
+                aliases.insert(key, fp);
+            }
+        }
         let pending: VecDeque<_> = init_states
             .into_iter()
             .map(|s| {
@@ -86,6 +92,7 @@ where
             })
             .collect();
         let discoveries = Arc::new(DashMap::default());
+        let cycles = sound_eventually.then(|| Arc::new(EventuallyCycles::new(thread_count)));
         let mut handles = Vec::new();
 
         let close_at = options.timeout.map(|t| SystemTime::now() + t);
@@ -99,7 +106,9 @@ where
             let state_count = Arc::clone(&state_count);
             let max_depth = Arc::clone(&max_depth);
             let generated = Arc::clone(&generated);
+            let aliases = Arc::clone(&aliases);
             let discoveries = Arc::clone(&discoveries);
+            let cycles = cycles.clone();
 
             let (controlflow_sender, controlflow_receiver) = std::sync::mpsc::channel();
             controlflow_channels.push(controlflow_sender);
@@ -109,6 +118,13 @@ where
                     .name(format!("checker-{}", t))
                     .spawn(move || {
                         log::debug!("{}: Thread started.", t);
+                        let _exit = cycles.as_deref().map(|cycles| {
+                            OnExit(|| {
+                                cycles.exit(&model.properties(), &discoveries, |key| {
+                                    reconstruct_fingerprints(&generated, &aliases, key)
+                                })
+                            })
+                        });
                         let mut pending = VecDeque::new();
                         let mut targetted_pending = VecDeque::new();
                         let mut wait_for_fingerprints = true;
@@ -181,11 +197,14 @@ where
                                 &model,
                                 &state_count,
                                 &generated,
+                                &aliases,
                                 &mut targetted_pending,
                                 &discoveries,
                                 &visitor,
                                 1500,
                                 &max_depth,
+                                sound_eventually,
+                                cycles.as_deref(),
                             );
                             pending.append(&mut targetted_pending);
                             if discoveries.len() == property_count {
@@ -234,6 +253,7 @@ where
             max_depth,
             generated,
             discoveries,
+            cycles,
             control_flow: controlflow_to_check_sender,
         }
     }
@@ -247,11 +267,14 @@ where
             Option<Fingerprint>,
             BuildHasherDefault<NoHashHasher<u64>>,
         >,
+        aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
         pending: &mut VecDeque<Job<M::State>>,
-        discoveries: &DashMap<&'static str, Fingerprint>,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
         visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
         max_count: usize,
         global_max_depth: &AtomicUsize,
+        sound_eventually: bool,
+        cycles: Option<&EventuallyCycles>,
     ) {
         let properties = model.properties();
 
@@ -266,6 +289,7 @@ where
                 None => return,
                 Some(pair) => pair,
             };
+            let state_key = visited_key(state_fp, &ebits, sound_eventually);
 
             if max_depth.get() > current_max_depth {
                 let _ = global_max_depth.compare_exchange(
@@ -278,7 +302,10 @@ where
             }
 
             if let Some(visitor) = visitor {
-                visitor.visit(model, reconstruct_path(model, generated, state_fp));
+                visitor.visit(
+                    model,
+                    reconstruct_path(model, generated, aliases, state_key),
+                );
             }
 
             // Done if discoveries found for all properties.
@@ -295,7 +322,10 @@ where
                     } => {
                         if !always(model, &state) {
                             // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
+                            discoveries.insert(
+                                property.name,
+                                reconstruct_fingerprints(generated, aliases, state_key),
+                            );
                         } else {
                             is_awaiting_discoveries = true;
                         }
@@ -307,7 +337,10 @@ where
                     } => {
                         if sometimes(model, &state) {
                             // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
+                            discoveries.insert(
+                                property.name,
+                                reconstruct_fingerprints(generated, aliases, state_key),
+                            );
                         } else {
                             is_awaiting_discoveries = true;
                         }
@@ -327,6 +360,10 @@ where
                             ebits.remove(i);
                         }
                     }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => unreachable!("rejected when spawning the checker"),
                 }
             }
             if !is_awaiting_discoveries {
@@ -334,9 +371,11 @@ where
             }
 
             // Otherwise enqueue newly generated states (with related metadata).
-            let mut is_terminal = true;
-            model.actions(&state, &mut actions);
-            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
+            let mut is_terminal = !collect_actions(model, &state, &mut actions, sound_eventually);
+            let next_states = model
+                .next_states_for(&state, &mut actions)
+                .into_iter()
+                .flatten();
             for next_state in next_states {
                 let next_fp = fingerprint(&next_state);
                 log::debug!(
@@ -350,25 +389,31 @@ where
                 }
                 state_count.fetch_add(1, Ordering::Relaxed);
 
-                // Skip if already generated.
-                //
-                // FIXME: we should really include ebits in the fingerprint here --
-                // it is possible to arrive at a DAG join with two different ebits
-                // values, and subsequently treat the fact that some eventually
-                // property held on the path leading to the first visit as meaning
-                // that it holds in the path leading to the second visit -- another
-                // possible false-negative.
-                if let Entry::Vacant(next_entry) = generated.entry(next_fp) {
-                    next_entry.insert(Some(state_fp));
+                // Skip if already generated, which is not terminal as it may be a join or a
+                // cycle. With `sound_eventually`, cycles are found once the search ends.
+                let next_key = visited_key(next_fp, &ebits, sound_eventually);
+                if let Some(cycles) = cycles {
+                    if !ebits.is_empty() {
+                        cycles.insert(
+                            (state_key, state_fp),
+                            max_depth.get(),
+                            &ebits,
+                            (next_key, next_fp),
+                        );
+                    }
+                }
+                let is_new = match generated.entry(next_key) {
+                    Entry::Vacant(next_entry) => {
+                        next_entry.insert(Some(state_key));
+                        true
+                    }
+                    Entry::Occupied(_) => false,
+                };
+                if is_new {
+                    if next_key != next_fp {
+                        aliases.insert(next_key, next_fp);
+                    }
                 } else {
-                    // FIXME: arriving at an already-known state may be a loop (in which case it
-                    // could, in a fancier implementation, be considered a terminal state for
-                    // purposes of eventually-property checking) but it might also be a join in
-                    // a DAG, which makes it non-terminal. These cases can be disambiguated (at
-                    // some cost), but for now we just _don't_ treat them as terminal, and tell
-                    // users they need to explicitly ensure model path-acyclicality when they're
-                    // using eventually properties (using a boundary or empty actions or
-                    // whatever).
                     is_terminal = false;
                     continue;
                 }
@@ -386,7 +431,10 @@ where
                 for (i, property) in properties.iter().enumerate() {
                     if ebits.contains(i) {
                         // Races other threads, but that's fine.
-                        discoveries.insert(property.name, state_fp);
+                        discoveries.insert(
+                            property.name,
+                            reconstruct_fingerprints(generated, aliases, state_key),
+                        );
                     }
                 }
             }
@@ -432,7 +480,7 @@ where
             .map(|mapref| {
                 (
                     <&'static str>::clone(mapref.key()),
-                    reconstruct_path(self.model(), &self.generated, *mapref.value()),
+                    Path::from_fingerprints(self.model(), VecDeque::from(mapref.value().clone())),
                 )
             })
             .collect()
@@ -443,31 +491,46 @@ where
     }
 
     fn is_done(&self) -> bool {
-        self.job_broker.is_closed() || self.discoveries.len() == self.model.properties().len()
+        let is_searched = self.cycles.as_ref().is_none_or(|c| c.is_searched());
+        (self.job_broker.is_closed() && is_searched)
+            || self.discoveries.len() == self.model.properties().len()
     }
 }
 
 fn reconstruct_path<M>(
     model: &M,
     generated: &DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
-    fp: Fingerprint,
+    aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+    key: Fingerprint,
 ) -> Path<M::State, M::Action>
 where
     M: Model,
     M::State: Hash,
 {
+    Path::from_fingerprints(
+        model,
+        reconstruct_fingerprints(generated, aliases, key).into(),
+    )
+}
+
+fn reconstruct_fingerprints(
+    generated: &DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
+    aliases: &DashMap<Fingerprint, Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
+    key: Fingerprint,
+) -> Vec<Fingerprint> {
     // First build a stack of digests representing the path (with the init digest at top of
     // stack). Then unwind the stack of digests into a vector of states. The TLC model checker
     // uses a similar technique, which is documented in the paper "Model Checking TLA+
     // Specifications" by Yu, Manolios, and Lamport.
 
     let mut fingerprints = VecDeque::new();
-    let mut next_fp = fp;
-    while let Some(source) = generated.get(&next_fp) {
+    let mut next_key = key;
+    while let Some(source) = generated.get(&next_key) {
+        let next_fp = aliases.get(&next_key).map_or(next_key, |fp| *fp);
         match *source {
-            Some(prev_fingerprint) => {
+            Some(prev_key) => {
                 fingerprints.push_front(next_fp);
-                next_fp = prev_fingerprint;
+                next_key = prev_key;
             }
             None => {
                 fingerprints.push_front(next_fp);
@@ -475,7 +538,7 @@ where
             }
         }
     }
-    Path::from_fingerprints(model, fingerprints)
+    fingerprints.into()
 }
 
 #[cfg(test)]
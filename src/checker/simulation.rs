@@ -348,6 +348,10 @@ where
                             ebits.remove(i);
                         }
                     }
+                    Property {
+                        expectation: Expectation::Ltl,
+                        ..
+                    } => unreachable!("rejected when spawning the checker"),
                 }
             }
             if !is_awaiting_discoveries {
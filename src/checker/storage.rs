@@ -0,0 +1,530 @@
+//! Private module for selective re-export.
+
+use crate::Fingerprint;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use nohash_hasher::NoHashHasher;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::hash::BuildHasherDefault;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Records the states visited by [`CheckerBuilder::spawn_bfs`], each of which is identified by
+/// a fingerprint and associated with the fingerprint of the state from which it was first
+/// reached (or `None` for an initial state). Paths are reconstructed by following these links.
+///
+/// [`InMemoryStorage`] is used by default, while [`DiskStorage`] supports state spaces that do
+/// not fit in memory. See [`CheckerBuilder::storage`].
+///
+/// [`CheckerBuilder::spawn_bfs`]: crate::CheckerBuilder::spawn_bfs
+/// [`CheckerBuilder::storage`]: crate::CheckerBuilder::storage
+pub trait StateStorage: Send + Sync {
+    /// Records a visited state unless it was already recorded. Returns `true` if the state was
+    /// not previously recorded.
+    fn insert(&self, fingerprint: Fingerprint, parent: Option<Fingerprint>) -> bool;
+
+    /// Returns `None` if the state was not visited, and otherwise the fingerprint of its parent.
+    fn parent(&self, fingerprint: Fingerprint) -> Option<Option<Fingerprint>>;
+
+    /// The number of visited states.
+    fn len(&self) -> usize;
+
+    /// Indicates whether no states have been visited.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls `f` with each visited state and its parent.
+    fn for_each(&self, f: &mut dyn FnMut(Fingerprint, Option<Fingerprint>));
+
+    /// Records the key of a visited state that is awaiting expansion, given the number of such
+    /// states that the calling thread already holds in memory. Returns `false` if the checker
+    /// should hold the state in memory instead, which is the default. Storage that holds part of
+    /// the pending frontier must return keys via [`StateStorage::pop_pending`] in the order they
+    /// were recorded, and must keep recording keys until it has none left, so that states are
+    /// still expanded in breadth-first order.
+    fn push_pending(&self, _fingerprint: Fingerprint, _in_memory: usize) -> bool {
+        false
+    }
+
+    /// Removes and returns the least recently recorded key of a state awaiting expansion.
+    fn pop_pending(&self) -> Option<Fingerprint> {
+        None
+    }
+}
+
+/// The default [`StateStorage`], which keeps all visited states in memory.
+#[derive(Default)]
+pub struct InMemoryStorage(
+    DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
+);
+
+impl StateStorage for InMemoryStorage {
+    fn insert(&self, fingerprint: Fingerprint, parent: Option<Fingerprint>) -> bool {
+        match self.0.entry(fingerprint) {
+            Entry::Vacant(entry) => {
+                entry.insert(parent);
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    fn parent(&self, fingerprint: Fingerprint) -> Option<Option<Fingerprint>> {
+        self.0.get(&fingerprint).map(|parent| *parent)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Fingerprint, Option<Fingerprint>)) {
+        for entry in self.0.iter() {
+            f(*entry.key(), *entry.value());
+        }
+    }
+}
+
+/// A [`StateStorage`] that keeps visited states in open addressing hash tables on disk, similar
+/// to the fingerprint sets of the TLC model checker. The visited set then no longer occupies
+/// memory, at the cost of reading from a file when a lookup misses a small cache of recently read
+/// pages.
+///
+/// Once a checker thread holds [`DiskStorage::max_pending_in_memory`] states awaiting expansion,
+/// further ones are queued in a file as fingerprints, and their states are rebuilt when dequeued
+/// by replaying the path from an initial state. The checker still holds the fingerprints of states
+/// whose visited key differs from their fingerprint in memory (see
+/// [`CheckerBuilder::sound_eventually`]), as well as the keys of every pending state when saving
+/// checkpoints (see [`CheckerBuilder::checkpoint_to`]).
+///
+/// States are partitioned across a number of files in a directory, each of which is locked
+/// independently and doubles in size when half full. The files are removed when the storage is
+/// dropped, as is the directory if the storage created it.
+///
+/// [`CheckerBuilder::checkpoint_to`]: crate::CheckerBuilder::checkpoint_to
+/// [`CheckerBuilder::sound_eventually`]: crate::CheckerBuilder::sound_eventually
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::{Checker, DiskStorage, Model};
+/// # let model = ();
+/// model.checker()
+///     .storage(DiskStorage::new("/tmp/stateright").unwrap())
+///     .spawn_bfs()
+///     .join();
+/// ```
+pub struct DiskStorage {
+    shards: Vec<Mutex<Shard>>,
+    len: AtomicUsize,
+    pending: Mutex<PendingQueue>,
+    max_pending_in_memory: usize,
+    /// The directory to remove on drop, if created by this storage.
+    created_dir: Option<PathBuf>,
+}
+
+impl DiskStorage {
+    const SHARD_COUNT: usize = 16;
+    const INITIAL_CAPACITY: u64 = 1 << 12;
+
+    /// Creates storage backed by files in the specified directory, which is created if absent.
+    /// Any files left behind by an earlier instance are overwritten, so two instances must not
+    /// share a directory.
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        let created_dir = if dir.exists() {
+            None
+        } else {
+            std::fs::create_dir_all(&dir)?;
+            Some(dir.clone())
+        };
+        let mut shards = Vec::with_capacity(Self::SHARD_COUNT);
+        for i in 0..Self::SHARD_COUNT {
+            let path = dir.join(format!("visited-{}.bin", i));
+            shards.push(Mutex::new(Shard::create(path, Self::INITIAL_CAPACITY)?));
+        }
+        Ok(Self {
+            shards,
+            len: AtomicUsize::new(0),
+            pending: Mutex::new(PendingQueue::create(dir.join("pending.bin"))?),
+            max_pending_in_memory: 1 << 16,
+            created_dir,
+        })
+    }
+
+    /// Sets how many states awaiting expansion each checker thread holds in memory before
+    /// queueing the rest on disk. Defaults to 65536. Each state dequeued from disk costs a replay
+    /// of its path, so a smaller value trades speed for memory.
+    pub fn max_pending_in_memory(mut self, count: usize) -> Self {
+        self.max_pending_in_memory = count;
+        self
+    }
+
+    fn shard(&self, fingerprint: Fingerprint) -> &Mutex<Shard> {
+        // The low bits select a slot within the shard, so use the high bits here.
+        &self.shards[(fingerprint.get() >> 56) as usize % self.shards.len()]
+    }
+}
+
+impl StateStorage for DiskStorage {
+    fn insert(&self, fingerprint: Fingerprint, parent: Option<Fingerprint>) -> bool {
+        let inserted = self
+            .shard(fingerprint)
+            .lock()
+            .insert(fingerprint, parent)
+            .expect("Failed to write visited state");
+        if inserted {
+            self.len.fetch_add(1, Ordering::Relaxed);
+        }
+        inserted
+    }
+
+    fn parent(&self, fingerprint: Fingerprint) -> Option<Option<Fingerprint>> {
+        self.shard(fingerprint)
+            .lock()
+            .parent(fingerprint)
+            .expect("Failed to read visited state")
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Fingerprint, Option<Fingerprint>)) {
+        for shard in &self.shards {
+            shard
+                .lock()
+                .for_each(f)
+                .expect("Failed to read visited states");
+        }
+    }
+
+    fn push_pending(&self, fingerprint: Fingerprint, in_memory: usize) -> bool {
+        let mut pending = self.pending.lock();
+        if in_memory < self.max_pending_in_memory && pending.is_empty() {
+            return false;
+        }
+        pending
+            .push(fingerprint)
+            .expect("Failed to write pending state");
+        true
+    }
+
+    fn pop_pending(&self) -> Option<Fingerprint> {
+        self.pending
+            .lock()
+            .pop()
+            .expect("Failed to read pending state")
+    }
+}
+
+impl Drop for DiskStorage {
+    fn drop(&mut self) {
+        for shard in &self.shards {
+            let _ = std::fs::remove_file(&shard.lock().path);
+        }
+        let _ = std::fs::remove_file(&self.pending.lock().path);
+        if let Some(dir) = &self.created_dir {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// A file of fingerprints in the order they were queued. The file is emptied whenever every
+/// queued fingerprint has been read, so it only grows while the frontier is on disk.
+struct PendingQueue {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    pushed: u64,
+    popped: u64,
+}
+
+impl PendingQueue {
+    fn create(path: PathBuf) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        Ok(Self {
+            reader: BufReader::new(File::open(&path)?),
+            writer: BufWriter::new(file),
+            path,
+            pushed: 0,
+            popped: 0,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pushed == self.popped
+    }
+
+    fn push(&mut self, fingerprint: Fingerprint) -> std::io::Result<()> {
+        self.writer.write_all(&fingerprint.get().to_le_bytes())?;
+        self.pushed += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> std::io::Result<Option<Fingerprint>> {
+        if self.is_empty() {
+            if self.pushed > 0 {
+                self.writer.flush()?;
+                self.writer.get_ref().set_len(0)?;
+                self.writer.seek(SeekFrom::Start(0))?;
+                self.reader.seek(SeekFrom::Start(0))?;
+                self.pushed = 0;
+                self.popped = 0;
+            }
+            return Ok(None);
+        }
+        self.writer.flush()?;
+        let mut buf = [0; 8];
+        self.reader.read_exact(&mut buf)?;
+        self.popped += 1;
+        Ok(Fingerprint::new(u64::from_le_bytes(buf)))
+    }
+}
+
+/// A file of fixed size slots, each holding a fingerprint and the fingerprint of its parent. Zero
+/// indicates an empty slot (as fingerprints are nonzero), and a state with no parent is recorded
+/// as its own parent (which is otherwise impossible).
+///
+/// Slots are read a page at a time, and the most recently read pages are cached, as probes for
+/// nearby slots tend to follow one another.
+struct Shard {
+    path: PathBuf,
+    file: File,
+    capacity: u64,
+    len: u64,
+    pages: HashMap<u64, Box<[u8]>>,
+    page_order: VecDeque<u64>,
+}
+
+impl Shard {
+    const SLOT_SIZE: u64 = 16;
+    const PAGE_SLOTS: u64 = 256;
+    const CACHED_PAGES: usize = 64;
+
+    fn create(path: PathBuf, capacity: u64) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len(capacity * Self::SLOT_SIZE)?;
+        Ok(Self {
+            path,
+            file,
+            capacity,
+            len: 0,
+            pages: HashMap::new(),
+            page_order: VecDeque::new(),
+        })
+    }
+
+    /// Returns the cached page that holds a slot, reading it from the file if necessary.
+    fn page(&mut self, slot: u64) -> std::io::Result<&mut [u8]> {
+        let index = slot / Self::PAGE_SLOTS;
+        if !self.pages.contains_key(&index) {
+            if self.pages.len() >= Self::CACHED_PAGES {
+                if let Some(evicted) = self.page_order.pop_front() {
+                    self.pages.remove(&evicted);
+                }
+            }
+            let page_size = Self::PAGE_SLOTS * Self::SLOT_SIZE;
+            let mut page = vec![0; page_size as usize].into_boxed_slice();
+            self.file.seek(SeekFrom::Start(index * page_size))?;
+            self.file.read_exact(&mut page)?;
+            self.pages.insert(index, page);
+            self.page_order.push_back(index);
+        }
+        Ok(self.pages.get_mut(&index).unwrap())
+    }
+
+    fn read_slot(&mut self, slot: u64) -> std::io::Result<(u64, u64)> {
+        let offset = ((slot % Self::PAGE_SLOTS) * Self::SLOT_SIZE) as usize;
+        let page = self.page(slot)?;
+        Ok(decode_slot(
+            page[offset..offset + Self::SLOT_SIZE as usize]
+                .try_into()
+                .unwrap(),
+        ))
+    }
+
+    /// Returns the slot that holds the fingerprint or else where it would be inserted, along with
+    /// the recorded parent if present.
+    fn find(&mut self, fingerprint: u64) -> std::io::Result<(u64, Option<u64>)> {
+        let mut slot = fingerprint % self.capacity;
+        loop {
+            let (found, parent) = self.read_slot(slot)?;
+            if found == 0 {
+                return Ok((slot, None));
+            }
+            if found == fingerprint {
+                return Ok((slot, Some(parent)));
+            }
+            slot = (slot + 1) % self.capacity;
+        }
+    }
+
+    fn insert(
+        &mut self,
+        fingerprint: Fingerprint,
+        parent: Option<Fingerprint>,
+    ) -> std::io::Result<bool> {
+        let fingerprint = fingerprint.get();
+        let parent = parent.map_or(fingerprint, |p| p.get());
+        if 2 * (self.len + 1) > self.capacity {
+            self.grow()?;
+        }
+        let (slot, found) = self.find(fingerprint)?;
+        if found.is_some() {
+            return Ok(false);
+        }
+        let mut buf = [0; Self::SLOT_SIZE as usize];
+        buf[..8].copy_from_slice(&fingerprint.to_le_bytes());
+        buf[8..].copy_from_slice(&parent.to_le_bytes());
+        self.file.seek(SeekFrom::Start(slot * Self::SLOT_SIZE))?;
+        self.file.write_all(&buf)?;
+        let offset = ((slot % Self::PAGE_SLOTS) * Self::SLOT_SIZE) as usize;
+        self.page(slot)?[offset..offset + Self::SLOT_SIZE as usize].copy_from_slice(&buf);
+        self.len += 1;
+        Ok(true)
+    }
+
+    fn parent(&mut self, fingerprint: Fingerprint) -> std::io::Result<Option<Option<Fingerprint>>> {
+        let (_, found) = self.find(fingerprint.get())?;
+        Ok(found.map(|parent| {
+            if parent == fingerprint.get() {
+                None
+            } else {
+                Fingerprint::new(parent)
+            }
+        }))
+    }
+
+    fn for_each(
+        &mut self,
+        f: &mut dyn FnMut(Fingerprint, Option<Fingerprint>),
+    ) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&self.file);
+        let mut buf = [0; Self::SLOT_SIZE as usize];
+        for _ in 0..self.capacity {
+            reader.read_exact(&mut buf)?;
+            let (fingerprint, parent) = decode_slot(&buf);
+            if let Some(fingerprint) = Fingerprint::new(fingerprint) {
+                let parent = if parent == fingerprint.get() {
+                    None
+                } else {
+                    Fingerprint::new(parent)
+                };
+                f(fingerprint, parent);
+            }
+        }
+        Ok(())
+    }
+
+    /// Doubles the capacity by rehashing into a new file that then replaces this one.
+    fn grow(&mut self) -> std::io::Result<()> {
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut grown = Shard::create(PathBuf::from(tmp), 2 * self.capacity)?;
+        let mut result = Ok(());
+        self.for_each(&mut |fingerprint, parent| {
+            if result.is_ok() {
+                result = grown.insert(fingerprint, parent).map(|_| ());
+            }
+        })?;
+        result?;
+        std::fs::rename(&grown.path, &self.path)?;
+        grown.path = std::mem::take(&mut self.path);
+        *self = grown;
+        Ok(())
+    }
+}
+
+fn decode_slot(buf: &[u8; Shard::SLOT_SIZE as usize]) -> (u64, u64) {
+    let mut fingerprint = [0; 8];
+    let mut parent = [0; 8];
+    fingerprint.copy_from_slice(&buf[..8]);
+    parent.copy_from_slice(&buf[8..]);
+    (u64::from_le_bytes(fingerprint), u64::from_le_bytes(parent))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fingerprint;
+
+    #[test]
+    fn can_insert_and_look_up_parents() {
+        let dir = std::env::temp_dir().join(format!("stateright-storage-{}", std::process::id()));
+        let storages: Vec<Box<dyn StateStorage>> = vec![
+            Box::<InMemoryStorage>::default(),
+            Box::new(DiskStorage::new(&dir).unwrap()),
+        ];
+        for storage in storages {
+            assert!(storage.is_empty());
+            assert!(storage.insert(fingerprint(&0), None));
+            assert!(storage.insert(fingerprint(&1), Some(fingerprint(&0))));
+            assert!(!storage.insert(fingerprint(&1), None));
+            assert_eq!(storage.len(), 2);
+            assert_eq!(storage.parent(fingerprint(&0)), Some(None));
+            assert_eq!(storage.parent(fingerprint(&1)), Some(Some(fingerprint(&0))));
+            assert_eq!(storage.parent(fingerprint(&2)), None);
+        }
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn disk_storage_queues_pending_states_in_order() {
+        let dir = std::env::temp_dir().join(format!("stateright-pending-{}", std::process::id()));
+        let storage = DiskStorage::new(&dir).unwrap().max_pending_in_memory(2);
+        assert!(!storage.push_pending(fingerprint(&0), 1));
+        for round in 0..2 {
+            assert!(storage.push_pending(fingerprint(&1), 2));
+            // Once any state is on disk, later ones follow it there.
+            assert!(storage.push_pending(fingerprint(&2), 0));
+            assert_eq!(storage.pop_pending(), Some(fingerprint(&1)), "{}", round);
+            assert!(storage.push_pending(fingerprint(&3), 0));
+            assert_eq!(storage.pop_pending(), Some(fingerprint(&2)));
+            assert_eq!(storage.pop_pending(), Some(fingerprint(&3)));
+            assert_eq!(storage.pop_pending(), None);
+        }
+        drop(storage);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn disk_storage_grows_and_cleans_up() {
+        let dir = std::env::temp_dir().join(format!("stateright-grow-{}", std::process::id()));
+        let storage = DiskStorage::new(&dir).unwrap();
+        let count = DiskStorage::SHARD_COUNT as u64 * DiskStorage::INITIAL_CAPACITY;
+        for i in 0..count {
+            assert!(storage.insert(fingerprint(&i), Fingerprint::new(i)));
+        }
+        assert_eq!(storage.len(), count as usize);
+        for i in (0..count).step_by(97) {
+            assert_eq!(storage.parent(fingerprint(&i)), Some(Fingerprint::new(i)));
+        }
+        let mut visited = 0;
+        storage.for_each(&mut |_, _| visited += 1);
+        assert_eq!(visited, count);
+
+        drop(storage);
+        assert!(!dir.exists());
+
+        // A directory that already existed is kept, but emptied of the storage's files.
+        std::fs::create_dir(&dir).unwrap();
+        drop(DiskStorage::new(&dir).unwrap());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(dir).unwrap();
+    }
+}
@@ -62,6 +62,7 @@ where
     M::State: Clone,
 {
     /// Instantiates a ([`PathRecorder`], accessor) pair.
+    #[allow(clippy::type_complexity)]
     pub fn new_with_accessor() -> (Self, impl Fn() -> HashSet<Path<M::State, M::Action>>) {
         let recorder = Self(Arc::new(Mutex::new(Default::default())));
         let accessor = {
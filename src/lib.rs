@@ -139,6 +139,7 @@ mod job_market;
 pub mod report;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 #[cfg(test)]
 mod test_util;
@@ -172,6 +173,22 @@ pub trait Model: Sized {
     /// does not change the state.
     fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State>;
 
+    /// Converts a previous state and the actions collected from it to the resulting states,
+    /// draining the actions. The actions are in the order that [`Model::actions`] collected them,
+    /// less any that a checker skipped via [`Model::is_redundant`]. [`None`] indicates that an
+    /// action does not change the state. Checkers call this when expanding a state, so a model can
+    /// share work across the actions. Defaults to calling [`Model::next_state`] for each action.
+    fn next_states_for(
+        &self,
+        last_state: &Self::State,
+        actions: &mut Vec<Self::Action>,
+    ) -> Vec<Option<Self::State>> {
+        actions
+            .drain(..)
+            .map(|action| self.next_state(last_state, action))
+            .collect()
+    }
+
     /// Converts an action of this model to a more intuitive representation (e.g. for Explorer).
     fn format_action(&self, action: &Self::Action) -> String
     where
@@ -246,6 +263,22 @@ pub trait Model: Sized {
         true
     }
 
+    /// Indicates whether a search can skip an action collected by [`Model::actions`] because the
+    /// transition it takes is redundant, with every state that follows reachable via other
+    /// actions, as with a partial-order reduction. Checkers that need every transition, such as
+    /// those checking liveness, ignore this.
+    fn is_redundant(&self, _state: &Self::State, _action: &Self::Action) -> bool {
+        false
+    }
+
+    /// Indicates the [`Fairness`] constraint, if any, that applies to an action. Liveness checking
+    /// (see [`Property::ltl`] and [`CheckerBuilder::liveness`]) disregards cycles that are unfair
+    /// with respect to these constraints, such as one in which an action is continuously enabled
+    /// yet never taken.
+    fn fairness(&self, _action: &Self::Action) -> Option<Fairness> {
+        None
+    }
+
     /// Instantiates a [`CheckerBuilder`] for this model.
     fn checker(self) -> CheckerBuilder<Self>
     where
@@ -260,11 +293,14 @@ pub trait Model: Sized {
 /// checker would find an example) or "an epoch *always* has at most one leader" (for which the
 /// model checker would find a counterexample) or "a proposal is *eventually* accepted" (for
 /// which the model checker would find a counterexample path leading from the initial state
-/// through to a terminal state).
+/// through to a terminal state). Temporal properties over entire behaviors can be expressed with
+/// [`Property::ltl`].
 pub struct Property<M: Model> {
     pub expectation: Expectation,
     pub name: &'static str,
     pub condition: fn(&M, &M::State) -> bool,
+    /// The temporal formula for an [`Expectation::Ltl`] property.
+    pub formula: Option<Arc<Ltl<M>>>,
 }
 impl<M: Model> Property<M> {
     /// An invariant that defines a [safety
@@ -275,6 +311,7 @@ impl<M: Model> Property<M> {
             expectation: Expectation::Always,
             name,
             condition,
+            formula: None,
         }
     }
 
@@ -283,16 +320,19 @@ impl<M: Model> Property<M> {
     /// discover a counterexample path leading from the initial state through to a
     /// terminal state.
     ///
-    /// Note that in this implementation `eventually` properties only work correctly on acyclic
-    /// paths (those that end in either states with no successors or checking boundaries). A path
-    /// ending in a cycle is not viewed as _terminating_ in that cycle, as the checker does not
-    /// differentiate cycles from DAG joins, and so an `eventually` property that has not been met
-    /// by the cycle-closing edge will ignored -- a false negative.
+    /// Note that by default `eventually` properties only work correctly on acyclic paths (those
+    /// that end in either states with no successors or checking boundaries). A path ending in a
+    /// cycle is not viewed as _terminating_ in that cycle, as the checker does not differentiate
+    /// cycles from DAG joins, and so an `eventually` property that has not been met by the
+    /// cycle-closing edge will ignored -- a false negative. Enable
+    /// [`CheckerBuilder::liveness`] to also detect cycles, in which case a counterexample path may
+    /// end by returning to an earlier state.
     pub fn eventually(name: &'static str, condition: fn(&M, &M::State) -> bool) -> Property<M> {
         Property {
             expectation: Expectation::Eventually,
             name,
             condition,
+            formula: None,
         }
     }
 
@@ -303,6 +343,29 @@ impl<M: Model> Property<M> {
             expectation: Expectation::Sometimes,
             name,
             condition,
+            formula: None,
+        }
+    }
+
+    /// A [linear temporal logic](https://en.wikipedia.org/wiki/Linear_temporal_logic) formula
+    /// that should hold for every behavior of the model. The model checker will try to discover a
+    /// counterexample, which is a [`Path`] whose last state either repeats an earlier state (the
+    /// behavior cycles from there forever) or is terminal (the behavior remains there forever).
+    ///
+    /// Only [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`] check these
+    /// properties, and spawning any other checker for a model that has them panics. Checking them
+    /// requires exploring the state graph on one thread while the other properties are being
+    /// checked. That exploration honors the [model boundary](Model::within_boundary) as well as
+    /// [`CheckerBuilder::symmetry`], [`CheckerBuilder::target_state_count`],
+    /// [`CheckerBuilder::target_max_depth`], and [`CheckerBuilder::timeout`], and states beyond
+    /// those bounds are not treated as terminal, so a bounded check only reports cycles among the
+    /// states that it explored.
+    pub fn ltl(name: &'static str, formula: Ltl<M>) -> Property<M> {
+        Property {
+            expectation: Expectation::Ltl,
+            name,
+            condition: |_, _| true,
+            formula: Some(Arc::new(formula)),
         }
     }
 }
@@ -312,11 +375,13 @@ impl<M: Model> Clone for Property<M> {
             expectation: self.expectation.clone(),
             name: self.name,
             condition: self.condition,
+            formula: self.formula.clone(),
         }
     }
 }
 
-/// Indicates whether a property is always, eventually, or sometimes true.
+/// Indicates whether a property is always, eventually, or sometimes true, or whether it is a
+/// temporal formula over behaviors.
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
 pub enum Expectation {
     /// The property is true for all reachable states.
@@ -325,6 +390,8 @@ pub enum Expectation {
     Eventually,
     /// The property is true for at least one reachable state.
     Sometimes,
+    /// The property's [`Ltl`] formula is true for all behavior paths.
+    Ltl,
 }
 
 impl Expectation {
@@ -333,10 +400,37 @@ impl Expectation {
             Expectation::Always => true,
             Expectation::Eventually => true,
             Expectation::Sometimes => false,
+            Expectation::Ltl => true,
         }
     }
 }
 
+/// A fairness constraint on a class of actions. See [`Model::fairness`].
+///
+/// Actions are grouped into classes by hashing a caller-provided value, so for example
+/// `Fairness::weak(("tick", process_id))` places the "tick" actions of each process in a separate
+/// class.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Fairness {
+    /// If an action of the class is continuously enabled from some point onward, then one is
+    /// eventually taken.
+    Weak(u64),
+    /// If an action of the class is enabled infinitely often, then one is taken infinitely often.
+    Strong(u64),
+}
+
+impl Fairness {
+    /// A weak fairness constraint for the class identified by a value.
+    pub fn weak(class: impl Hash) -> Self {
+        Fairness::Weak(fingerprint(&class).get())
+    }
+
+    /// A strong fairness constraint for the class identified by a value.
+    pub fn strong(class: impl Hash) -> Self {
+        Fairness::Strong(fingerprint(&class).get())
+    }
+}
+
 /// A state identifier. See [`fingerprint`].
 type Fingerprint = std::num::NonZeroU64;
 
@@ -14,6 +14,11 @@ pub struct ReportData {
     pub unique_states: usize,
     /// Maximum depth explored.
     pub max_depth: usize,
+    /// The estimated probability that the next new state is mistaken for a visited one, if
+    /// checking uses bitstate hashing.
+    pub false_positive_rate: Option<f64>,
+    /// The estimated number of states omitted so far, if checking uses bitstate hashing.
+    pub estimated_omitted_states: Option<f64>,
     /// The current duration checking has been running for.
     pub duration: Duration,
     /// Whether checking is done.
@@ -63,20 +68,29 @@ where
     W: Write,
 {
     fn report_checking(&mut self, data: ReportData) {
+        let false_positives = match (data.false_positive_rate, data.estimated_omitted_states) {
+            (Some(p), Some(omitted)) => format!(
+                ", false_positive_rate={:.1e}, estimated_omitted_states={:.1e}",
+                p, omitted
+            ),
+            (Some(p), None) => format!(", false_positive_rate={:.1e}", p),
+            _ => String::new(),
+        };
         if data.done {
             let _ = writeln!(
                 self.writer,
-                "Done. states={}, unique={}, depth={}, sec={}",
+                "Done. states={}, unique={}, depth={}, sec={}{}",
                 data.total_states,
                 data.unique_states,
                 data.max_depth,
                 data.duration.as_secs(),
+                false_positives,
             );
         } else {
             let _ = writeln!(
                 self.writer,
-                "Checking. states={}, unique={}, depth={}",
-                data.total_states, data.unique_states, data.max_depth
+                "Checking. states={}, unique={}, depth={}{}",
+                data.total_states, data.unique_states, data.max_depth, false_positives
             );
         }
     }
@@ -39,6 +39,7 @@ function getProperty(p, done) {
                     case 'Always':     return '⚠️ Counterexample found: ';
                     case 'Sometimes':  return '✅ Example found: ';
                     case 'Eventually': return '⚠️ Counterexample found: ';
+                    case 'Ltl':        return '⚠️ Counterexample found: ';
                     default:
                         throw new Error(`Invalid expectation ${expectation}.`);
                 }
@@ -48,6 +49,7 @@ function getProperty(p, done) {
                     case 'Always':     return '✅ Safety holds: ';
                     case 'Sometimes':  return '⚠️ Example not found: ';
                     case 'Eventually': return '✅ Liveness holds: ';
+                    case 'Ltl':        return '⏭️ Not checked by Explorer: ';
                     default:
                         throw new Error(`Invalid expectation ${expectation}.`);
                 }
@@ -81,6 +83,7 @@ function getPropertyForState(p, path) {
                     case 'Always': return [ '⚠️',' Counterexample found: ' ];
                     case 'Sometimes':  return [ '✅', ' Example found: ' ];
                     case 'Eventually': return [ '⚠️', ' Counterexample found: ' ];
+                    case 'Ltl':        return [ '⚠️', ' Counterexample found: ' ];
                     default:
                         throw new Error(`Invalid expectation ${expectation}.`);
                 }
@@ -90,6 +93,7 @@ function getPropertyForState(p, path) {
                 case 'Always':     return [ '✅', ' Safety holds: ' ];
                 case 'Sometimes':  return [ '⚠️', ' Example not found: ' ];
                 case 'Eventually': return [ '✅', ' Liveness holds: ' ];
+                case 'Ltl':        return [ '⏭️', ' Not checked by Explorer: ' ];
                 default:
                     throw new Error(`Invalid expectation ${expectation}.`);
             }